@@ -0,0 +1,101 @@
+// Prints the heap allocations that each read makes once the caches are warm. This is a separate binary from the
+// interpolation benchmarks, because counting every allocation through the global allocator would skew their
+// timings. Run with cargo bench --bench allocations
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::Infallible,
+    f32::consts::PI,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use index_signal::interpolator::{Interpolator, SampleProvider};
+
+const NUM_SAMPLES: usize = 1 << 16;
+const READS: usize = 1024;
+const WINDOW_SIZES: [usize; 4] = [16, 64, 256, 1024];
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+struct VecSampleProvider {
+    samples: Vec<f32>,
+}
+
+impl SampleProvider<(), Infallible> for VecSampleProvider {
+    fn get_sample(&self, _channel_id: (), index: usize) -> Result<f32, Infallible> {
+        Ok(self.samples[index])
+    }
+}
+
+fn create_interpolator(window_size: usize) -> Interpolator<VecSampleProvider, (), Infallible> {
+    let samples = (0..NUM_SAMPLES)
+        .map(|index| (2.0 * PI * (index as f32) / 37.3).sin())
+        .collect();
+
+    Interpolator::new(window_size, NUM_SAMPLES, VecSampleProvider { samples })
+}
+
+fn sequential_indexes(relative_speed: f32) -> Vec<f32> {
+    (0..READS)
+        .map(|read| 1000.0 + read as f32 * relative_speed)
+        .collect()
+}
+
+fn random_indexes() -> Vec<f32> {
+    let mut state = 12345u32;
+    (0..READS)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * (NUM_SAMPLES as f32)
+        })
+        .collect()
+}
+
+fn main() {
+    for (name, indexes, relative_speed) in [
+        ("sequential", sequential_indexes(0.75), 0.75),
+        ("sequential_aliasing_filter", sequential_indexes(2.5), 2.5),
+        ("random_aliasing_filter", random_indexes(), 2.5),
+    ] {
+        for window_size in WINDOW_SIZES {
+            let interpolator = create_interpolator(window_size);
+            let read_all = || {
+                for index in indexes.iter() {
+                    black_box(
+                        interpolator
+                            .get_interpolated_sample((), *index, relative_speed)
+                            .unwrap(),
+                    );
+                }
+            };
+
+            read_all();
+            let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+            read_all();
+            let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+            println!(
+                "{}/{}: {:.2} allocations per read",
+                name,
+                window_size,
+                allocations as f32 / indexes.len() as f32
+            );
+        }
+    }
+}
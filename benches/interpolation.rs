@@ -0,0 +1,138 @@
+use std::{convert::Infallible, f32::consts::PI};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use index_signal::interpolator::{Interpolator, InterpolatorBuilder, SampleProvider};
+
+const NUM_SAMPLES: usize = 1 << 16;
+const READS_PER_ITERATION: usize = 1024;
+const WINDOW_SIZES: [usize; 4] = [16, 64, 256, 1024];
+
+struct VecSampleProvider {
+    samples: Vec<f32>,
+}
+
+impl SampleProvider<(), Infallible> for VecSampleProvider {
+    fn get_sample(&self, _channel_id: (), index: usize) -> Result<f32, Infallible> {
+        Ok(self.samples[index])
+    }
+}
+
+fn create_interpolator(window_size: usize) -> Interpolator<VecSampleProvider, (), Infallible> {
+    InterpolatorBuilder::new(window_size, NUM_SAMPLES).build(create_sample_provider())
+}
+
+fn create_sample_provider() -> VecSampleProvider {
+    let samples = (0..NUM_SAMPLES)
+        .map(|index| (2.0 * PI * (index as f32) / 37.3).sin())
+        .collect();
+
+    VecSampleProvider { samples }
+}
+
+// Sequential reads at relative_speed: Below 1.0, consecutive reads usually share a window, so most reads hit
+// the transform cache. Above 1.0, the anti-aliasing filter is used
+fn sequential_indexes(relative_speed: f32) -> Vec<f32> {
+    (0..READS_PER_ITERATION)
+        .map(|read| 1000.0 + read as f32 * relative_speed)
+        .collect()
+}
+
+// Reads that jump around the signal, so every read misses the transform cache
+fn random_indexes() -> Vec<f32> {
+    let mut state = 12345u32;
+    (0..READS_PER_ITERATION)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            // 24 bits of state, so that indexes have fractional parts
+            (state >> 8) as f32 / (1 << 24) as f32 * (NUM_SAMPLES as f32)
+        })
+        .collect()
+}
+
+fn bench_reads(criterion: &mut Criterion, group_name: &str, indexes: &[f32], relative_speed: f32) {
+    bench_reads_with(
+        criterion,
+        group_name,
+        indexes,
+        relative_speed,
+        create_interpolator,
+    );
+}
+
+fn bench_reads_with(
+    criterion: &mut Criterion,
+    group_name: &str,
+    indexes: &[f32],
+    relative_speed: f32,
+    create_interpolator: impl Fn(usize) -> Interpolator<VecSampleProvider, (), Infallible>,
+) {
+    let mut group = criterion.benchmark_group(group_name);
+    group.throughput(Throughput::Elements(indexes.len() as u64));
+
+    for window_size in WINDOW_SIZES {
+        let interpolator = create_interpolator(window_size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(window_size),
+            &window_size,
+            |bencher, _| {
+                bencher.iter(|| {
+                    for index in indexes {
+                        black_box(
+                            interpolator
+                                .get_interpolated_sample((), *index, relative_speed)
+                                .unwrap(),
+                        );
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn sequential(criterion: &mut Criterion) {
+    bench_reads(criterion, "sequential", &sequential_indexes(0.75), 0.75);
+}
+
+fn random(criterion: &mut Criterion) {
+    bench_reads(criterion, "random", &random_indexes(), 1.0);
+}
+
+fn sequential_aliasing_filter(criterion: &mut Criterion) {
+    bench_reads(
+        criterion,
+        "sequential_aliasing_filter",
+        &sequential_indexes(2.5),
+        2.5,
+    );
+}
+
+fn random_aliasing_filter(criterion: &mut Criterion) {
+    bench_reads(criterion, "random_aliasing_filter", &random_indexes(), 2.5);
+}
+
+// Sequential reads with cached rotators, which are multiplied into each shifted window in one contiguous pass
+fn sequential_cached_rotators(criterion: &mut Criterion) {
+    bench_reads_with(
+        criterion,
+        "sequential_cached_rotators",
+        &sequential_indexes(0.75),
+        0.75,
+        |window_size| {
+            InterpolatorBuilder::new(window_size, NUM_SAMPLES)
+                .rotator_cache_resolution(64)
+                .build(create_sample_provider())
+        },
+    );
+}
+
+criterion_group!(
+    benches,
+    sequential,
+    random,
+    sequential_aliasing_filter,
+    random_aliasing_filter,
+    sequential_cached_rotators
+);
+criterion_main!(benches);
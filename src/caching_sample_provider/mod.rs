@@ -0,0 +1,151 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+};
+
+use crate::interpolator::SampleProvider;
+
+struct SampleCache<TChannelId> {
+    // Each sample, and when it was last read
+    samples: HashMap<(TChannelId, usize), (f32, u64)>,
+    // Keys by when they were last read, so the least recently read is evicted first
+    recency: BTreeMap<u64, (TChannelId, usize)>,
+    next_read: u64,
+    // The wrapped SampleProvider's generation when the samples were cached
+    generation: u64,
+}
+
+/// Wraps a SampleProvider and remembers the most recently read samples, so that overlapping windows don't
+/// read the same sample from the wrapped SampleProvider repeatedly. Once capacity samples are cached, the
+/// least recently read sample is evicted. Errors are not cached. When the wrapped SampleProvider's generation
+/// changes, the cached samples are discarded
+pub struct CachingSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    sample_provider: TSampleProvider,
+    capacity: usize,
+    cache: RefCell<SampleCache<TChannelId>>,
+
+    _phantom_data: PhantomData<TError>,
+}
+
+impl<TSampleProvider, TChannelId, TError> CachingSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    pub fn new(
+        sample_provider: TSampleProvider,
+        capacity: usize,
+    ) -> CachingSampleProvider<TSampleProvider, TChannelId, TError> {
+        assert!(capacity > 0, "capacity must be greater than 0");
+
+        let generation = sample_provider.generation();
+        CachingSampleProvider {
+            sample_provider,
+            capacity,
+            cache: RefCell::new(SampleCache {
+                samples: HashMap::with_capacity(capacity),
+                recency: BTreeMap::new(),
+                next_read: 0,
+                generation,
+            }),
+            _phantom_data: PhantomData,
+        }
+    }
+
+    pub fn sample_provider(&self) -> &TSampleProvider {
+        &self.sample_provider
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn get_cached_sample(&self, channel_id: TChannelId, index: usize) -> Option<f32> {
+        let generation = self.sample_provider.generation();
+        let mut cache = self.cache.borrow_mut();
+        if cache.generation != generation {
+            cache.samples.clear();
+            cache.recency.clear();
+            cache.generation = generation;
+        }
+
+        // A hit makes the sample the most recently read
+        let key = (channel_id, index);
+        let read = cache.next_read;
+        let (sample, last_read) = cache.samples.get_mut(&key)?;
+        let sample = *sample;
+        let last_read = std::mem::replace(last_read, read);
+        cache.recency.remove(&last_read);
+        cache.recency.insert(read, key);
+        cache.next_read += 1;
+
+        Some(sample)
+    }
+
+    fn cache_sample(&self, channel_id: TChannelId, index: usize, sample: f32) {
+        let key = (channel_id, index);
+
+        let mut cache = self.cache.borrow_mut();
+        if cache.samples.len() >= self.capacity {
+            if let Some((_, least_recent_key)) = cache.recency.pop_first() {
+                cache.samples.remove(&least_recent_key);
+            }
+        }
+
+        let read = cache.next_read;
+        cache.samples.insert(key, (sample, read));
+        cache.recency.insert(read, key);
+        cache.next_read += 1;
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> SampleProvider<TChannelId, TError>
+    for CachingSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        if let Some(sample) = self.get_cached_sample(channel_id, index) {
+            return Ok(sample);
+        }
+
+        let sample = self.sample_provider.get_sample(channel_id, index)?;
+        self.cache_sample(channel_id, index, sample);
+
+        Ok(sample)
+    }
+
+    // The end of the signal isn't cached, because a live signal may grow
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TError> {
+        if let Some(sample) = self.get_cached_sample(channel_id, index) {
+            return Ok(Some(sample));
+        }
+
+        let sample = self
+            .sample_provider
+            .get_sample_if_available(channel_id, index)?;
+        if let Some(sample) = sample {
+            self.cache_sample(channel_id, index, sample);
+        }
+
+        Ok(sample)
+    }
+
+    fn generation(&self) -> u64 {
+        self.sample_provider.generation()
+    }
+
+    fn prefetch(&self, channel_id: TChannelId, start: usize, len: usize) -> Result<(), TError> {
+        self.sample_provider.prefetch(channel_id, start, len)
+    }
+}
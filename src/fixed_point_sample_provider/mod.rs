@@ -0,0 +1,174 @@
+use std::{
+    io::{Error, ErrorKind},
+    marker::PhantomData,
+};
+
+use crate::interpolator::SampleProvider;
+
+/// An integer PCM sample, which is normalized to an f32 in [-1.0, 1.0) by dividing by 2^(BITS - 1)
+pub trait FixedPointSample: Copy {
+    const BITS: u32;
+
+    fn to_i32(self) -> i32;
+
+    /// value must be within the range of the type
+    fn from_i32(value: i32) -> Self;
+
+    fn to_f32(self) -> f32 {
+        self.to_i32() as f32 / scale::<Self>()
+    }
+
+    /// Requantizes sample, rounding to the nearest integer. Samples outside of [-1.0, 1.0) are clamped to
+    /// the type's range, so that overshoot saturates instead of wrapping
+    fn from_f32(sample: f32) -> Self {
+        let max = (1i32 << (Self::BITS - 1)) - 1;
+        let value = (sample * scale::<Self>()).round();
+        Self::from_i32(value.clamp(-(max as f32) - 1.0, max as f32) as i32)
+    }
+}
+
+fn scale<TSample: FixedPointSample>() -> f32 {
+    (1u32 << (TSample::BITS - 1)) as f32
+}
+
+impl FixedPointSample for i16 {
+    const BITS: u32 = 16;
+
+    fn to_i32(self) -> i32 {
+        self as i32
+    }
+
+    fn from_i32(value: i32) -> i16 {
+        value as i16
+    }
+}
+
+/// A packed, little-endian, 24-bit sample, as in 24-bit wav files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct I24(pub [u8; 3]);
+
+impl I24 {
+    pub const MIN: i32 = -(1 << 23);
+    pub const MAX: i32 = (1 << 23) - 1;
+
+    pub fn new(value: i32) -> I24 {
+        assert!(
+            (I24::MIN..=I24::MAX).contains(&value),
+            "{} is out of range for a 24-bit sample",
+            value
+        );
+
+        let bytes = value.to_le_bytes();
+        I24([bytes[0], bytes[1], bytes[2]])
+    }
+
+    pub fn value(self) -> i32 {
+        // Shifting the top byte into the sign bit and back sign-extends it
+        i32::from_le_bytes([0, self.0[0], self.0[1], self.0[2]]) >> 8
+    }
+}
+
+impl FixedPointSample for I24 {
+    const BITS: u32 = 24;
+
+    fn to_i32(self) -> i32 {
+        self.value()
+    }
+
+    fn from_i32(value: i32) -> I24 {
+        I24::new(value)
+    }
+}
+
+/// Reads interleaved integer samples, such as i16 or I24, and normalizes them to f32, so that integer PCM
+/// can be interpolated without converting it first. The channel id is the index of the channel within each
+/// frame, and samples are indexed by frame. Reading past the last frame, or reading a channel that doesn't
+/// exist, returns an error, like MmapSampleProvider. (See FixedPointSample::from_f32 and
+/// Interpolator::resample_all_fixed_point to convert interpolated samples back)
+pub struct FixedPointSampleProvider<TSamples, TSample>
+where
+    TSamples: AsRef<[TSample]>,
+    TSample: FixedPointSample,
+{
+    samples: TSamples,
+    num_channels: usize,
+
+    _phantom_data: PhantomData<TSample>,
+}
+
+impl<TSamples, TSample> FixedPointSampleProvider<TSamples, TSample>
+where
+    TSamples: AsRef<[TSample]>,
+    TSample: FixedPointSample,
+{
+    /// samples is interleaved, with num_channels samples in each frame
+    pub fn new(
+        samples: TSamples,
+        num_channels: usize,
+    ) -> FixedPointSampleProvider<TSamples, TSample> {
+        assert!(num_channels > 0, "num_channels must be greater than 0");
+
+        FixedPointSampleProvider {
+            samples,
+            num_channels,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    pub fn samples(&self) -> &TSamples {
+        &self.samples
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    /// The number of complete frames. This is the num_samples for an Interpolator
+    pub fn num_frames(&self) -> usize {
+        self.samples.as_ref().len() / self.num_channels
+    }
+}
+
+impl<TSamples, TSample> SampleProvider<usize, Error> for FixedPointSampleProvider<TSamples, TSample>
+where
+    TSamples: AsRef<[TSample]>,
+    TSample: FixedPointSample,
+{
+    fn get_sample(&self, channel_id: usize, index: usize) -> Result<f32, Error> {
+        if channel_id >= self.num_channels {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "channel {} is out of range, there are {} channels",
+                    channel_id, self.num_channels
+                ),
+            ));
+        }
+
+        if index >= self.num_frames() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "sample {} is out of range, there are {} frames",
+                    index,
+                    self.num_frames()
+                ),
+            ));
+        }
+
+        Ok(self.samples.as_ref()[index * self.num_channels + channel_id].to_f32())
+    }
+
+    // Reading past the last frame is the end of the signal, instead of an error
+    fn get_sample_if_available(
+        &self,
+        channel_id: usize,
+        index: usize,
+    ) -> Result<Option<f32>, Error> {
+        match self.get_sample(channel_id, index) {
+            Ok(sample) => Ok(Some(sample)),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use crate::interpolator::SampleProvider;
+
+/// Wraps a SampleProvider and multiplies each of its samples by a gain, such as to set a layer's level before
+/// it's mixed with SumSampleProvider
+pub struct GainSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    sample_provider: TSampleProvider,
+    gain: f32,
+
+    _phantom_data: PhantomData<(TChannelId, TError)>,
+}
+
+impl<TSampleProvider, TChannelId, TError> GainSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    pub fn new(
+        sample_provider: TSampleProvider,
+        gain: f32,
+    ) -> GainSampleProvider<TSampleProvider, TChannelId, TError> {
+        GainSampleProvider {
+            sample_provider,
+            gain,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    pub fn sample_provider(&self) -> &TSampleProvider {
+        &self.sample_provider
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> SampleProvider<TChannelId, TError>
+    for GainSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        Ok(self.sample_provider.get_sample(channel_id, index)? * self.gain)
+    }
+
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TError> {
+        Ok(self
+            .sample_provider
+            .get_sample_if_available(channel_id, index)?
+            .map(|sample| sample * self.gain))
+    }
+
+    fn generation(&self) -> u64 {
+        self.sample_provider.generation()
+    }
+
+    fn prefetch(&self, channel_id: TChannelId, start: usize, len: usize) -> Result<(), TError> {
+        self.sample_provider.prefetch(channel_id, start, len)
+    }
+}
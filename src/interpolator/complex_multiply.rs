@@ -0,0 +1,67 @@
+use rustfft::num_complex::Complex32;
+
+#[cfg(feature = "simd")]
+use wide::f32x8;
+
+// The number of Complex32s in an f32x8
+#[cfg(feature = "simd")]
+const LANES: usize = 4;
+
+// values[n] *= rotators[n]. With the simd feature, the values are multiplied in f32x8 lanes. (See multiply_simd)
+pub(crate) fn multiply(values: &mut [Complex32], rotators: &[Complex32]) {
+    #[cfg(feature = "simd")]
+    multiply_simd(values, rotators);
+
+    #[cfg(not(feature = "simd"))]
+    multiply_scalar(values, rotators);
+}
+
+// values[n] *= rotators[n]. Both slices are contiguous, so the compiler multiplies them in SIMD lanes on its own,
+// which benchmarks as fast as multiply_simd on x86_64, or faster
+pub(crate) fn multiply_scalar(values: &mut [Complex32], rotators: &[Complex32]) {
+    assert_eq!(values.len(), rotators.len());
+    for (value, rotator) in values.iter_mut().zip(rotators) {
+        *value *= rotator;
+    }
+}
+
+// multiply_scalar, LANES values at a time in f32x8 lanes. The values stay interleaved, (re, im, re, im, ...,) so
+// each product is the values times their rotators' real parts, plus the values with re and im swapped times
+// their rotators' imaginary parts, where the real parts' signs are flipped. The values after the last whole
+// group of LANES are multiplied by multiply_scalar
+#[cfg(feature = "simd")]
+pub(crate) fn multiply_simd(values: &mut [Complex32], rotators: &[Complex32]) {
+    assert_eq!(values.len(), rotators.len());
+    let signs = f32x8::from([-1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0]);
+    let (values, remaining_values) = values.split_at_mut(values.len() / LANES * LANES);
+    let (rotators, remaining_rotators) = rotators.split_at(values.len());
+
+    for (value_lanes, rotator_lanes) in as_lanes_mut(values)
+        .chunks_exact_mut(2 * LANES)
+        .zip(as_lanes(rotators).chunks_exact(2 * LANES))
+    {
+        let v: [f32; 8] = (&*value_lanes).try_into().unwrap();
+        let r: [f32; 8] = rotator_lanes.try_into().unwrap();
+        let swapped = [v[1], v[0], v[3], v[2], v[5], v[4], v[7], v[6]];
+        let rotators_re = [r[0], r[0], r[2], r[2], r[4], r[4], r[6], r[6]];
+        let rotators_im = [r[1], r[1], r[3], r[3], r[5], r[5], r[7], r[7]];
+
+        let product = f32x8::from(v) * f32x8::from(rotators_re)
+            + f32x8::from(swapped) * f32x8::from(rotators_im) * signs;
+        value_lanes.copy_from_slice(&product.to_array());
+    }
+
+    multiply_scalar(remaining_values, remaining_rotators);
+}
+
+#[cfg(feature = "simd")]
+fn as_lanes(values: &[Complex32]) -> &[f32] {
+    // Safety: Complex32 is #[repr(C)], with re followed by im, so n Complex32s are 2n f32s
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const f32, 2 * values.len()) }
+}
+
+#[cfg(feature = "simd")]
+fn as_lanes_mut(values: &mut [Complex32]) -> &mut [f32] {
+    // Safety: See as_lanes
+    unsafe { std::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut f32, 2 * values.len()) }
+}
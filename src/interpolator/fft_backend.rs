@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use rustfft::{num_complex::Complex32, Fft, FftPlanner};
+
+/// A planned FFT of one size and direction. This is the part of rustfft::Fft that the interpolator uses
+pub trait FftTransform: Send + Sync {
+    /// The length of the scratch buffer that process_with_scratch needs
+    fn get_inplace_scratch_len(&self) -> usize;
+
+    /// Transforms buffer in place. scratch is at least get_inplace_scratch_len long, and its contents are
+    /// undefined before and after
+    fn process_with_scratch(&self, buffer: &mut [Complex32], scratch: &mut [Complex32]);
+}
+
+/// Plans the FFTs that the interpolator uses, so that an FFT library other than rustfft can be used. (See
+/// InterpolatorBuilder::fft_backend.) The transforms may be scaled however the library scales them: The
+/// interpolator measures each plan's scale by transforming a DC signal when it's planned
+pub trait FftBackend: Send {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn FftTransform>;
+    fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn FftTransform>;
+}
+
+// rustfft::Fft can't be used as an FftTransform directly, because Arc<dyn Fft<f32>> can't be converted to
+// Arc<dyn FftTransform>
+struct RustFftTransform(Arc<dyn Fft<f32>>);
+
+impl FftTransform for RustFftTransform {
+    fn get_inplace_scratch_len(&self) -> usize {
+        self.0.get_inplace_scratch_len()
+    }
+
+    fn process_with_scratch(&self, buffer: &mut [Complex32], scratch: &mut [Complex32]) {
+        self.0.process_with_scratch(buffer, scratch)
+    }
+}
+
+/// The default backend
+impl FftBackend for FftPlanner<f32> {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn FftTransform> {
+        Arc::new(RustFftTransform(FftPlanner::plan_fft_forward(self, len)))
+    }
+
+    fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn FftTransform> {
+        Arc::new(RustFftTransform(FftPlanner::plan_fft_inverse(self, len)))
+    }
+}
@@ -0,0 +1,95 @@
+use std::{error::Error, fmt};
+
+/// An error from one of the Interpolator's checked methods, (IE, try_get_interpolated_sample,) which tells a
+/// bad input apart from a failed read. The unchecked methods return the SampleProvider's error as-is, so that
+/// callers that only handle TError aren't affected
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolatorError<TError> {
+    /// The SampleProvider failed to read a sample
+    Provider(TError),
+    /// The index is NaN or infinite
+    InvalidIndex(f32),
+    /// The relative_speed, (or ratio,) is NaN, infinite, or not greater than 0.0
+    InvalidSpeed(f32),
+    /// The window is longer than the channel, so every window is mostly padding. (See
+    /// Interpolator::window_exceeds_signal)
+    WindowTooLarge {
+        window_size: usize,
+        num_samples: usize,
+    },
+}
+
+impl<TError> InterpolatorError<TError> {
+    /// The SampleProvider's error, or None if the input was invalid
+    pub fn into_provider_error(self) -> Option<TError> {
+        match self {
+            InterpolatorError::Provider(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<TError> fmt::Display for InterpolatorError<TError>
+where
+    TError: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolatorError::Provider(error) => {
+                write!(f, "The sample provider failed: {}", error)
+            }
+            InterpolatorError::InvalidIndex(index) => write!(f, "Invalid index: {}", index),
+            InterpolatorError::InvalidSpeed(relative_speed) => {
+                write!(f, "Invalid relative_speed: {}", relative_speed)
+            }
+            InterpolatorError::WindowTooLarge {
+                window_size,
+                num_samples,
+            } => write!(
+                f,
+                "The window size, {}, is larger than the number of samples, {}",
+                window_size, num_samples
+            ),
+        }
+    }
+}
+
+impl<TError> Error for InterpolatorError<TError>
+where
+    TError: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InterpolatorError::Provider(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A setting in an InterpolatorConfig that InterpolatorBuilder::build would panic on, (IE, in a config that was
+/// deserialized from untrusted input.) See InterpolatorConfig::validate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    message: String,
+}
+
+impl ConfigError {
+    pub(crate) fn new(message: impl Into<String>) -> ConfigError {
+        ConfigError {
+            message: message.into(),
+        }
+    }
+
+    /// Which setting is invalid, and why
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid interpolator config: {}", self.message)
+    }
+}
+
+impl Error for ConfigError {}
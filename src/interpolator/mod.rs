@@ -1,191 +1,3907 @@
-use std::{cell::RefCell, collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashMap},
+    f32::consts::PI,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex, OnceLock, Weak},
+};
 
-use rustfft::{num_complex::Complex32, Fft, FftPlanner};
+use rustfft::{num_complex::Complex32, FftPlanner};
+
+use crate::fixed_point_sample_provider::FixedPointSample;
+
+pub(crate) mod complex_multiply;
+mod fft_backend;
+mod interpolator_error;
+mod time_stretch;
+mod window_function;
+
+pub use fft_backend::{FftBackend, FftTransform};
+pub use interpolator_error::{ConfigError, InterpolatorError};
+pub use window_function::WindowFunction;
 
 pub type GetSampleClosure = dyn Fn(usize) -> f32;
 
+/// Called with the index that a window is centered on and the window's forward transform. (See
+/// InterpolatorBuilder::on_transform)
+pub type TransformHook = dyn Fn(isize, &[Complex32]) + Send + Sync;
+
+// A frame from reconstruct_frame, and the window function shifted the same way
+type ReconstructedFrame = (Vec<f32>, Option<Arc<[f32]>>);
+
+/// How many output samples resample_all_with_progress produces between calls to its progress callback
+pub const PROGRESS_INTERVAL: usize = 4096;
+
+// The fractions of a sample where frequency_response measures, which avoid whole samples, because those are
+// read directly
+const FREQUENCY_RESPONSE_FRACTS: [f32; 8] = [
+    0.0625, 0.1875, 0.3125, 0.4375, 0.5625, 0.6875, 0.8125, 0.9375,
+];
+
+// estimate_period picks the shortest lag whose correlation is at least this fraction of the best correlation,
+// because multiples of the period correlate about as well as the period itself
+const PERIOD_PEAK_THRESHOLD: f32 = 0.9;
+
+/// How far results may differ between platforms. Results are deterministic on any one platform, but rustfft
+/// picks its algorithms by CPU features, (IE, AVX, SSE, NEON, or scalar,) which round differently. Resampling
+/// with the default settings should stay within this of the golden vectors in the tests on every platform, so
+/// regression tests that compare against stored output should allow this much difference
+pub const REPRODUCIBILITY_TOLERANCE: f32 = 1e-5;
+
+/// The default for InterpolatorBuilder::aliasing_filter_epsilon
+pub const DEFAULT_ALIASING_FILTER_EPSILON: f32 = 1e-4;
+
+/// The default for InterpolatorBuilder::max_oversampling_ratio
+pub const DEFAULT_MAX_OVERSAMPLING_RATIO: usize = 4096;
+
 pub trait SampleProvider<TChannelId, TError>
 where
     TChannelId: Copy,
 {
     fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError>;
+
+    /// Reads the sample at index, or returns None if the signal ended before index. This is for signals
+    /// whose length isn't known in advance, such as live capture: The interpolator treats samples after the
+    /// end like samples after num_samples, instead of as an error. The default calls get_sample, so providers
+    /// that know their length don't need to implement this
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TError> {
+        self.get_sample(channel_id, index).map(Some)
+    }
+
+    /// A version number for the samples, for providers whose samples can change, such as an editable buffer.
+    /// Interpolators don't reuse transforms that they cached under a different generation, so a provider
+    /// should return a new value whenever its samples change. The default is always 0. (See also
+    /// Interpolator::bump_generation)
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    /// Called before the interpolator reads the len samples starting at start, one at a time, so that
+    /// providers that are backed by a disk or a network can read them all at once and serve the following
+    /// calls to get_sample from a buffer. Only samples within 0..num_samples are prefetched. The default does
+    /// nothing
+    fn prefetch(&self, _channel_id: TChannelId, _start: usize, _len: usize) -> Result<(), TError> {
+        Ok(())
+    }
+}
+
+/// Lets an interpolator own a boxed provider, such as a Box<dyn SampleProvider>, so that interpolators whose
+/// providers are chosen at runtime have the same type
+impl<TSampleProvider, TChannelId, TError> SampleProvider<TChannelId, TError>
+    for Box<TSampleProvider>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError> + ?Sized,
+    TChannelId: Copy,
+{
+    fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        self.as_ref().get_sample(channel_id, index)
+    }
+
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TError> {
+        self.as_ref().get_sample_if_available(channel_id, index)
+    }
+
+    fn generation(&self) -> u64 {
+        self.as_ref().generation()
+    }
+
+    fn prefetch(&self, channel_id: TChannelId, start: usize, len: usize) -> Result<(), TError> {
+        self.as_ref().prefetch(channel_id, start, len)
+    }
+}
+
+/// A provider of complex samples, such as IQ baseband data, for Interpolator::get_interpolated_complex_sample.
+/// The methods that return real samples still use get_sample, which can return the real part
+pub trait ComplexSampleProvider<TChannelId, TError>: SampleProvider<TChannelId, TError>
+where
+    TChannelId: Copy,
+{
+    fn get_complex_sample(&self, channel_id: TChannelId, index: usize)
+        -> Result<Complex32, TError>;
+}
+
+/// The counterpart to SampleProvider, which receives the samples that Interpolator::resample_to_writer produces,
+/// such as to write them to a file. Its errors are the same type as the SampleProvider's, so that errors from
+/// either side come back through one Result. (See MapErrorSampleProvider to convert a provider's errors)
+pub trait SampleWriter<TChannelId, TError>
+where
+    TChannelId: Copy,
+{
+    /// Receives the output sample at index in channel_id. Samples are written in order of index, and for each
+    /// index, in the order of the channel_ids that were passed to resample_to_writer
+    fn write_sample(
+        &mut self,
+        channel_id: TChannelId,
+        index: usize,
+        sample: f32,
+    ) -> Result<(), TError>;
+}
+
+/// How samples before index 0 and after num_samples - 1 are read, when a window extends past either end of
+/// the signal
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryMode {
+    /// Samples outside of the signal are 0.0
+    #[default]
+    Zero,
+    /// The signal is reflected across index 0 and index num_samples - 1, (IE, sample -1 is sample 1,) so that
+    /// a window at either end doesn't see a step from the signal to silence
+    Mirror,
+    /// Samples before index 0 are sample 0, and samples after num_samples - 1 are sample num_samples - 1, so
+    /// that the edges are held. With BoundaryMode::Zero, the step from the signal to silence distorts
+    /// fractional reads near either end; with this, a DC signal stays at its level all the way to the ends
+    Clamp,
+}
+
+/// What Interpolator::get_envelope measures over its window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EnvelopeMode {
+    /// The root mean square of the samples
+    Rms,
+    /// The largest absolute value of the samples
+    Peak,
+}
+
+/// How the spectra that the interpolator returns, (IE, Interpolator::get_spectrum,) are scaled, named after
+/// NumPy's and SciPy's norm argument. Reconstruction doesn't depend on this: The FFT backend's own scale is
+/// measured by transforming a DC signal, (see Interpolator::fft_scale,) and divided out, so any backend's
+/// spectra can be converted to any of these
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Normalization {
+    /// The forward transform isn't scaled, and the inverse is scaled by 1 / N, so a DC signal of 1.0 has a DC
+    /// frequency of N. This is numpy.fft.fft's default
+    #[default]
+    Backward,
+    /// The forward transform is scaled by 1 / N, so a DC signal of 1.0 has a DC frequency of 1.0
+    Forward,
+    /// Both transforms are scaled by 1 / sqrt(N), so that they preserve energy
+    Ortho,
+}
+
+impl Normalization {
+    // What a forward transform is multiplied by, after dividing out the backend's scale
+    fn forward_gain(self, size: usize) -> f32 {
+        match self {
+            Normalization::Backward => size as f32,
+            Normalization::Forward => 1.0,
+            Normalization::Ortho => (size as f32).sqrt(),
+        }
+    }
+}
+
+/// What the interpolator does with NaN and infinite samples from the SampleProvider, such as from a corrupt
+/// float in a file. A single non-finite sample spreads to every sample that's reconstructed from a window that
+/// contains it. (See also Interpolator::find_non_finite)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NonFiniteSamples {
+    /// Non-finite samples are used as they are
+    #[default]
+    Propagate,
+    /// Non-finite samples are read as 0.0
+    Zero,
+    /// Non-finite samples are read as the nearest finite sample up to window_size samples before them, or 0.0
+    /// if there isn't one. The search is bounded, so that reads within a long run of non-finite samples don't
+    /// each search back to the start of the run
+    Previous,
+}
+
+/// Where, within the window that's read around an index, the sample is reconstructed. The window for index
+/// starts at floor(index) - window_size / 2, so that floor(index) is at window_size / 2 within it
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EvaluationPoint {
+    /// The sample is reconstructed at window_size / 2 + fract(index), so index 10.25 returns the signal at
+    /// 10.25
+    #[default]
+    Center,
+    /// The sample is reconstructed at offset + fract(index) from the first sample of the window, so index
+    /// returns the signal at index + offset - window_size / 2. For example, an offset of window_size / 2 -
+    /// 0.5 matches resamplers whose output sample n is centered between input samples n - 1 and n. The
+    /// window is re-centered on the shifted index, so this doesn't reduce accuracy
+    ///
+    /// The offset applies to every method that takes an index into the signal, and to the methods that
+    /// resample the whole signal. Methods that take the index of a window, (get_window, get_spectrum,
+    /// estimate_period, and is_cached,) take floor(index + offset - window_size / 2), the window that
+    /// interpolating at index reads. time_stretch analyzes its own frames, and ignores the offset
+    FromLeftEdge(f32),
+}
+
+/// How samples between whole-sample indexes are reconstructed
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationMode {
+    /// The window around each index is transformed, shifted, and transformed back
+    #[default]
+    BandLimited,
+    /// Catmull-Rom cubic Hermite interpolation of the 4 samples from floor(index) - 1 through floor(index) + 2,
+    /// without FFTs. This is much cheaper than BandLimited, but less accurate at high frequencies.
+    /// relative_speed, the window function, and remove_dc are ignored, so there's no anti-aliasing filter
+    CatmullRom,
+    /// Convolves the taps samples around index with a sinc kernel, tapered by a Kaiser window with beta, without
+    /// FFTs. This is the textbook reference for BandLimited's quality and speed. The kernel is normalized to
+    /// unity gain at DC. As with CatmullRom, relative_speed, the window function, and remove_dc are ignored.
+    /// taps must be at least 2
+    WindowedSinc { taps: usize, beta: f32 },
+    /// Reads the sample at round(index). This is a baseline for measuring how much the other modes improve on
+    /// no interpolation at all. As with CatmullRom, relative_speed, the window function, and remove_dc are
+    /// ignored
+    Nearest,
+    /// Reads the sample at floor(index), as a sample-and-hold would. Like Nearest, this is a baseline
+    Hold,
+}
+
+// How shifting treats the frequencies above Nyquist
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    // The signal is real, so each mirrored frequency is set to the conjugate of its rotated positive frequency,
+    // instead of being rotated on its own
+    Conjugate,
+    // Each mirrored frequency is rotated on its own, the opposite way, for transforms that aren't
+    // conjugate-symmetric, such as those of complex and analytic signals
+    Independent,
 }
 
 struct TransformCacheEntry {
-    index: usize,
+    index: isize,
+    // The interpolator's and the SampleProvider's generations when the window was read
+    generation: (u64, u64),
     transform: Vec<Complex32>,
 }
 
+struct CachedRotators {
+    rotators: Vec<Complex32>,
+    // The window function's gain at the same fraction, (see Interpolator::window_gain,) or 1.0 for the
+    // rectangular window
+    window_gain: f32,
+}
+
+thread_local! {
+    // Scratch for FFTs. Each thread has its own, so that clones of an interpolator on different threads never
+    // share scratch, and every interpolator on a thread shares it. It grows to the longest scratch that an FFT on
+    // the thread has needed, and shorter FFTs use the start of it
+    static FFT_SCRATCH: RefCell<Vec<Complex32>> = const { RefCell::new(Vec::new()) };
+}
+
+fn process_with_thread_scratch(fft: &dyn FftTransform, buffer: &mut [Complex32]) {
+    let scratch_length = fft.get_inplace_scratch_len();
+    FFT_SCRATCH.with(|fft_scratch| {
+        let mut fft_scratch = fft_scratch.borrow_mut();
+        if fft_scratch.len() < scratch_length {
+            fft_scratch.resize(scratch_length, Complex32::new(0.0, 0.0));
+        }
+
+        fft.process_with_scratch(buffer, &mut fft_scratch[..scratch_length]);
+    });
+}
+
+// The plans are immutable, so entries can be shared between clones and threads. Each direction is planned the
+// first time it's used, so sizes that only transform one way, (IE, the anti-aliasing filter's,) don't plan the
+// other direction
+#[derive(Clone)]
+struct FFTCacheEntry {
+    size: usize,
+    // Weak, so that entries don't count as owners of a shared planner. Entries are only used by the interpolators
+    // that hold them, which own the planner
+    planner: Weak<Mutex<dyn FftBackend>>,
+    // The plan, and the value of the DC bin after a forward transform of a DC signal of 1.0
+    forward: Arc<OnceLock<(Arc<dyn FftTransform>, f32)>>,
+    // The plan, and the value of each sample after an inverse transform of 1.0 in the DC bin
+    inverse: Arc<OnceLock<(Arc<dyn FftTransform>, f32)>>,
+}
+
+impl FFTCacheEntry {
+    fn new(size: usize, planner: Weak<Mutex<dyn FftBackend>>) -> FFTCacheEntry {
+        FFTCacheEntry {
+            size,
+            planner,
+            forward: Arc::new(OnceLock::new()),
+            inverse: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn forward(&self, buffer: &mut [Complex32]) {
+        process_with_thread_scratch(self.forward_plan().0.as_ref(), buffer);
+    }
+
+    fn inverse(&self, buffer: &mut [Complex32]) {
+        process_with_thread_scratch(self.inverse_plan().0.as_ref(), buffer);
+    }
+
+    // Value of the DC bin after a forward transform of a DC signal of 1.0
+    fn forward_scale(&self) -> f32 {
+        self.forward_plan().1
+    }
+
+    // Value of each sample after transforming a DC signal of 1.0 forward and back
+    fn inverse_scale(&self) -> f32 {
+        self.forward_scale() * self.inverse_plan().1
+    }
+
+    fn forward_plan(&self) -> &(Arc<dyn FftTransform>, f32) {
+        self.forward.get_or_init(|| {
+            let fft_forward = self.plan(|planner, size| planner.plan_fft_forward(size));
+            let mut scale_transform = vec![Complex32::new(1.0, 0.0); self.size];
+            process_with_thread_scratch(fft_forward.as_ref(), &mut scale_transform);
+            (fft_forward, scale_transform[0].re)
+        })
+    }
+
+    fn inverse_plan(&self) -> &(Arc<dyn FftTransform>, f32) {
+        self.inverse.get_or_init(|| {
+            let fft_inverse = self.plan(|planner, size| planner.plan_fft_inverse(size));
+            let mut scale_transform = vec![Complex32::new(0.0, 0.0); self.size];
+            scale_transform[0] = Complex32::new(1.0, 0.0);
+            process_with_thread_scratch(fft_inverse.as_ref(), &mut scale_transform);
+            (fft_inverse, scale_transform[0].re)
+        })
+    }
+
+    // Planning doesn't leave the planner in an inconsistent state, so a panic on another thread while it was
+    // locked doesn't matter
+    fn plan(
+        &self,
+        plan: impl FnOnce(&mut dyn FftBackend, usize) -> Arc<dyn FftTransform>,
+    ) -> Arc<dyn FftTransform> {
+        let planner = self
+            .planner
+            .upgrade()
+            .expect("The interpolators that use an FFT cache entry own its planner");
+        let mut planner = planner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        plan(&mut *planner, self.size)
+    }
+}
+
+/// Interpolates the signal from a SampleProvider. (See InterpolatorBuilder.) Reads take &self, but an
+/// Interpolator isn't Sync, because its caches are RefCells: To interpolate on several threads, clone it for
+/// each thread
 pub struct Interpolator<TSampleProvider, TChannelId, TError>
 where
     TSampleProvider: SampleProvider<TChannelId, TError>,
     TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
 {
-    fft_forward: Arc<dyn Fft<f32>>,
-    scratch_forward: RefCell<Vec<Complex32>>,
-    fft_inverse: Arc<dyn Fft<f32>>,
-    scratch_inverse: RefCell<Vec<Complex32>>,
+    planner: Arc<Mutex<dyn FftBackend>>,
+    fft_cache: RefCell<HashMap<usize, FFTCacheEntry>>,
     sample_provider: TSampleProvider,
     window_size: usize,
-    scale: f32,
+    round_window_to_fast_size: bool,
     num_samples: usize,
-    phase_shifts_per_sample: Vec<f32>,
+    min_oversampling_ratio: usize,
+    max_oversampling_ratio: usize,
+    aliasing_filter_epsilon: f32,
+    rotator_cache_resolution: Option<u32>,
+    remove_dc: bool,
+    boundary_mode: BoundaryMode,
+    non_finite_samples: NonFiniteSamples,
+    normalization: Normalization,
+    evaluation_point: EvaluationPoint,
+    window_offset: usize,
+    fade_out_tail: bool,
+    clamp: Option<(f32, f32)>,
+    interpolation_mode: InterpolationMode,
+    // The furthest that a sample was outside of the clamp range, (or -1.0 to 1.0,) before it was clamped
+    max_overshoot: Cell<f32>,
+    // Added to every index, to evaluate at evaluation_point instead of the window's center
+    index_offset: f32,
+    window_function: WindowFunction,
+    // None for the rectangular window, so that unwindowed reads don't multiply by 1.0
+    window_coefficients: Option<Arc<[f32]>>,
+    // The forward transform of window_coefficients, used to undo the window's gain after each shift
+    window_transform: Option<Arc<[Complex32]>>,
+    // window_transform's frequencies from DC through Nyquist, multiplied by the inverse transform's weight at the
+    // center sample, so that the window's gain after a shift is a single pass over the rotators. (See window_gain)
+    window_gain_weights: Option<Arc<[Complex32]>>,
+    // The inverse transform's weight for each frequency at the center sample. (See compute_center_weights)
+    center_weights: Arc<[Complex32]>,
+    phase_shifts_per_sample: Arc<[f32]>,
     transform_cache: RefCell<HashMap<TChannelId, TransformCacheEntry>>,
+    // Incremented by bump_generation, so that transforms cached before then aren't reused
+    generation: Cell<u64>,
+    // Set when a read reaches the end of a signal of unknown length, (see SampleProvider::get_sample_if_available,)
+    // so that windows padded past the end aren't cached while the signal may still grow
+    reached_end: Cell<bool>,
+    // Rotators for each frequency, and the window's gain, keyed by the fraction of a sample quantized to
+    // rotator_cache_resolution
+    rotator_cache: RefCell<HashMap<u32, CachedRotators>>,
+    // The window function shifted by the fraction that reconstruct_frame was last called with, keyed by the
+    // fraction's bits, because overlap-add reads every frame at the same fraction
+    window_frame_cache: RefCell<Option<(u32, Arc<[f32]>)>>,
+    // Buffers for the points that the anti-aliasing filter averages, for shifting cached transforms, and for
+    // reading new windows, reused so that reads don't allocate once the caches are warm
+    scratch_buffers: RefCell<Vec<Vec<Complex32>>>,
+    // Replaces the averaging anti-aliasing filter. (See InterpolatorBuilder::aliasing_filter_kernel)
+    aliasing_filter_kernel: Option<Arc<[f32]>>,
+    on_transform: Option<Arc<TransformHook>>,
+    // Overrides num_samples for individual channels. (See set_channel_num_samples)
+    channel_num_samples: HashMap<TChannelId, usize>,
 
     _phantom_data: PhantomData<(TChannelId, TError)>,
 }
 
+/// The settings of an Interpolator, without its sample provider, caches, or FFT plans. With the serde feature,
+/// this can be serialized to save resampling settings, and passed to Interpolator::from_config to reconstruct
+/// the interpolator later. See InterpolatorBuilder for what each setting does
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterpolatorConfig {
+    pub window_size: usize,
+    pub round_window_to_fast_size: bool,
+    pub num_samples: usize,
+    pub min_oversampling_ratio: usize,
+    pub max_oversampling_ratio: usize,
+    pub aliasing_filter_epsilon: f32,
+    pub rotator_cache_resolution: Option<u32>,
+    pub remove_dc: bool,
+    pub boundary_mode: BoundaryMode,
+    pub non_finite_samples: NonFiniteSamples,
+    pub normalization: Normalization,
+    pub evaluation_point: EvaluationPoint,
+    pub window_offset: usize,
+    pub fade_out_tail: bool,
+    pub clamp: Option<(f32, f32)>,
+    pub interpolation_mode: InterpolationMode,
+    pub window_function: WindowFunction,
+    pub aliasing_filter_kernel: Option<Vec<f32>>,
+}
+
+impl InterpolatorConfig {
+    /// The default configuration, which is what Interpolator::new uses
+    pub fn new(window_size: usize, num_samples: usize) -> InterpolatorConfig {
+        InterpolatorConfig {
+            window_size,
+            round_window_to_fast_size: false,
+            num_samples,
+            min_oversampling_ratio: 1,
+            max_oversampling_ratio: DEFAULT_MAX_OVERSAMPLING_RATIO,
+            aliasing_filter_epsilon: DEFAULT_ALIASING_FILTER_EPSILON,
+            rotator_cache_resolution: None,
+            remove_dc: false,
+            boundary_mode: BoundaryMode::Zero,
+            non_finite_samples: NonFiniteSamples::Propagate,
+            normalization: Normalization::Backward,
+            evaluation_point: EvaluationPoint::Center,
+            window_offset: 0,
+            fade_out_tail: false,
+            clamp: None,
+            interpolation_mode: InterpolationMode::BandLimited,
+            window_function: WindowFunction::Rectangular,
+            aliasing_filter_kernel: None,
+        }
+    }
+
+    /// Checks every setting that InterpolatorBuilder::build would panic on, so that a config from untrusted
+    /// input, (IE, deserialized with the serde feature,) can be rejected instead. (See Interpolator::from_config)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let window_size = if self.round_window_to_fast_size {
+            fast_window_size(self.window_size)
+        } else {
+            self.window_size
+        };
+
+        require(
+            window_size >= 2,
+            format!("window_size must be at least 2, but was {}", window_size),
+        )?;
+        require(
+            self.max_oversampling_ratio >= self.min_oversampling_ratio.max(1),
+            "max_oversampling_ratio must be at least 1 and at least min_oversampling_ratio",
+        )?;
+        require(
+            self.aliasing_filter_epsilon >= 0.0,
+            "aliasing_filter_epsilon must not be negative",
+        )?;
+        if let Some((min, max)) = self.clamp {
+            require(min < max, "The clamp minimum must be less than the maximum")?;
+        }
+        require(
+            self.rotator_cache_resolution != Some(0),
+            "rotator_cache_resolution must be greater than 0",
+        )?;
+        require(
+            self.window_offset <= (window_size - window_size / 2).saturating_sub(2),
+            "window_offset can be at most window_size - 2 - window_size / 2",
+        )?;
+        if let EvaluationPoint::FromLeftEdge(offset) = self.evaluation_point {
+            require(
+                offset.is_finite(),
+                "The evaluation point's offset must be finite",
+            )?;
+        }
+        if let InterpolationMode::WindowedSinc { taps, beta } = self.interpolation_mode {
+            require(taps >= 2, "WindowedSinc needs at least 2 taps")?;
+            require(beta >= 0.0, "WindowedSinc's beta must not be negative")?;
+        }
+        if let Some(aliasing_filter_kernel) = &self.aliasing_filter_kernel {
+            require(
+                !aliasing_filter_kernel.is_empty(),
+                "The aliasing filter kernel needs at least 1 tap",
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Configures and builds an Interpolator. Interpolator::new is equivalent to building with the defaults
+pub struct InterpolatorBuilder {
+    config: InterpolatorConfig,
+    planner: Option<Arc<Mutex<dyn FftBackend>>>,
+    on_transform: Option<Arc<TransformHook>>,
+}
+
+impl InterpolatorBuilder {
+    /// window_size is the number of samples that are transformed around each index, and must be at least 2. It
+    /// may be odd or even; either way, the index is at window_size / 2 within the window. A window_size larger
+    /// than num_samples is allowed, but reconstruction suffers; see Interpolator::window_exceeds_signal
+    pub fn new(window_size: usize, num_samples: usize) -> InterpolatorBuilder {
+        InterpolatorBuilder::with_config(InterpolatorConfig::new(window_size, num_samples))
+    }
+
+    /// Starts with the settings in config, which may be changed before building. Returns an error if config is
+    /// invalid, (see InterpolatorConfig::validate)
+    pub fn from_config(config: InterpolatorConfig) -> Result<InterpolatorBuilder, ConfigError> {
+        config.validate()?;
+        Ok(InterpolatorBuilder::with_config(config))
+    }
+
+    fn with_config(config: InterpolatorConfig) -> InterpolatorBuilder {
+        InterpolatorBuilder {
+            config,
+            planner: None,
+            on_transform: None,
+        }
+    }
+
+    /// The minimum number of points that the anti-aliasing filter averages when downsampling. By default,
+    /// the filter uses the smallest power of two that keeps the points no further than one sample apart,
+    /// blended with half as many points. (See Interpolator::oversampling_ratio.)
+    /// More points improve stopband attenuation at the cost of more interpolations per sample
+    pub fn min_oversampling_ratio(mut self, min_oversampling_ratio: usize) -> InterpolatorBuilder {
+        self.config.min_oversampling_ratio = min_oversampling_ratio;
+        self
+    }
+
+    /// The maximum number of points that the anti-aliasing filter averages, so that an absurd relative_speed,
+    /// (IE, 1e9,) doesn't allocate and interpolate billions of points for each sample. Above
+    /// max_oversampling_ratio samples per read, the points are further than one sample apart, and aliasing
+    /// increases. Defaults to DEFAULT_MAX_OVERSAMPLING_RATIO (4096,) and can't be less than
+    /// min_oversampling_ratio
+    pub fn max_oversampling_ratio(mut self, max_oversampling_ratio: usize) -> InterpolatorBuilder {
+        self.config.max_oversampling_ratio = max_oversampling_ratio;
+        self
+    }
+
+    /// The anti-aliasing filter is only used when relative_speed is greater than 1.0 + epsilon, so that speeds
+    /// that drift slightly above 1.0 from rounding errors don't pay for filtering. Defaults to
+    /// DEFAULT_ALIASING_FILTER_EPSILON (0.0001.) 0.0 filters every speed above 1.0
+    pub fn aliasing_filter_epsilon(mut self, epsilon: f32) -> InterpolatorBuilder {
+        self.config.aliasing_filter_epsilon = epsilon;
+        self
+    }
+
+    /// Quantizes the fractional part of each index to 1 / resolution of a sample, and caches the rotations
+    /// that shift each frequency by that fraction. When resampling at a fixed ratio, the same fractions
+    /// repeat, so this avoids recalculating sin and cos for every frequency on every read. The window
+    /// function's gain at each fraction is cached with its rotators. At most resolution + 1 sets of
+    /// window_size / 2 rotators are cached. By default, fractions are not quantized and rotations are not cached
+    pub fn rotator_cache_resolution(mut self, resolution: u32) -> InterpolatorBuilder {
+        self.config.rotator_cache_resolution = Some(resolution);
+        self
+    }
+
+    /// Removes DC offset by discarding the DC frequency of each window before the signal is reconstructed.
+    /// This applies to whole-sample indexes as well, so they are reconstructed instead of read directly
+    pub fn remove_dc(mut self, remove_dc: bool) -> InterpolatorBuilder {
+        self.config.remove_dc = remove_dc;
+        self
+    }
+
+    /// How windows that extend past either end of the signal are filled. Defaults to BoundaryMode::Zero
+    pub fn boundary_mode(mut self, boundary_mode: BoundaryMode) -> InterpolatorBuilder {
+        self.config.boundary_mode = boundary_mode;
+        self
+    }
+
+    /// What to do with NaN and infinite samples from the SampleProvider. Defaults to
+    /// NonFiniteSamples::Propagate
+    pub fn non_finite_samples(
+        mut self,
+        non_finite_samples: NonFiniteSamples,
+    ) -> InterpolatorBuilder {
+        self.config.non_finite_samples = non_finite_samples;
+        self
+    }
+
+    /// How the spectra that the interpolator returns are scaled. This doesn't change the interpolated samples.
+    /// Defaults to Normalization::Backward
+    pub fn normalization(mut self, normalization: Normalization) -> InterpolatorBuilder {
+        self.config.normalization = normalization;
+        self
+    }
+
+    /// Where the sample is reconstructed within the window that's read around each index. Defaults to
+    /// EvaluationPoint::Center, where index returns the signal at index
+    pub fn evaluation_point(mut self, evaluation_point: EvaluationPoint) -> InterpolatorBuilder {
+        self.config.evaluation_point = evaluation_point;
+        self
+    }
+
+    /// Moves the window that's read around each index window_offset samples into the past, so that it needs
+    /// fewer samples after the index, (IE, for real-time input where later samples haven't arrived yet.) The
+    /// window normally runs from window_size / 2 samples before floor(index) to window_size - window_size / 2 - 1
+    /// samples after it. The largest window_offset, (window_size - 2 - window_size / 2,) ends the window at
+    /// floor(index) + 1, which is the latest sample that interpolating between floor(index) and floor(index) + 1
+    /// needs. Whole-sample indexes are still exact, but between samples, accuracy drops as the index moves
+    /// away from the center of the window: The transform treats the window as periodic, so the index
+    /// approaches the seam where the window's last sample wraps around to its first. (With a window of 64
+    /// samples, the largest error on a smooth test signal grows from under 0.001 at the center to about 0.01
+    /// at a window_offset of 16, and 0.15 at the largest window_offset.) Window functions other than
+    /// Rectangular, which taper toward the edges, make this worse. Defaults to 0, (a centered window)
+    pub fn window_offset(mut self, window_offset: usize) -> InterpolatorBuilder {
+        self.config.window_offset = window_offset;
+        self
+    }
+
+    /// Rounds window_size up to the next size whose only prime factors are 2, 3, 5 and 7, (IE, 127 becomes 128,)
+    /// because FFTs of those sizes are much faster than FFTs of sizes with large prime factors. The size that's
+    /// used is Interpolator::window_size. Defaults to false, so that the window is exactly window_size
+    pub fn round_window_to_fast_size(
+        mut self,
+        round_window_to_fast_size: bool,
+    ) -> InterpolatorBuilder {
+        self.config.round_window_to_fast_size = round_window_to_fast_size;
+        self
+    }
+
+    /// Fades the last window_size samples of the signal out with a raised cosine, so that the signal decays
+    /// smoothly to silence at num_samples instead of ringing where the window crosses from the signal into
+    /// padding. This changes the signal's last samples, including whole-sample indexes. Defaults to false
+    pub fn fade_out_tail(mut self, fade_out_tail: bool) -> InterpolatorBuilder {
+        self.config.fade_out_tail = fade_out_tail;
+        self
+    }
+
+    /// Clamps every sample that's returned to min..=max, (IE, -1.0 and 1.0,) so that overshoot from
+    /// band-limited reconstruction doesn't clip when the samples are converted to a fixed-point format. See
+    /// Interpolator::max_overshoot to measure how far samples overshoot. By default, samples aren't clamped
+    pub fn clamp(mut self, min: f32, max: f32) -> InterpolatorBuilder {
+        self.config.clamp = Some((min, max));
+        self
+    }
+
+    /// How samples between whole-sample indexes are reconstructed. Defaults to InterpolationMode::BandLimited
+    pub fn interpolation_mode(
+        mut self,
+        interpolation_mode: InterpolationMode,
+    ) -> InterpolatorBuilder {
+        self.config.interpolation_mode = interpolation_mode;
+        self
+    }
+
+    /// The window that each block of window_size samples is multiplied by before it is transformed. Defaults
+    /// to WindowFunction::Rectangular, (no windowing)
+    pub fn window_function(mut self, window_function: WindowFunction) -> InterpolatorBuilder {
+        self.config.window_function = window_function;
+        self
+    }
+
+    /// Plans FFTs with a planner that's shared with other interpolators. FftPlanner reuses the plans it has
+    /// already made, so interpolators with the same window size share their plans and twiddle factors instead
+    /// of each computing their own. By default, each interpolator has its own planner
+    pub fn planner(mut self, planner: Arc<Mutex<FftPlanner<f32>>>) -> InterpolatorBuilder {
+        self.planner = Some(planner);
+        self
+    }
+
+    /// Plans FFTs with an FFT library other than rustfft. As with planner, the backend may be shared with
+    /// other interpolators. By default, each interpolator has its own rustfft FftPlanner
+    pub fn fft_backend(mut self, fft_backend: Arc<Mutex<dyn FftBackend>>) -> InterpolatorBuilder {
+        self.planner = Some(fft_backend);
+        self
+    }
+
+    /// Replaces the anti-aliasing filter's averaging with a low-pass FIR filter: When the anti-aliasing filter
+    /// is used, each read interpolates a point for each tap, spaced one input sample apart and centered on the
+    /// index, and sums the points weighted by the taps. The kernel is applied at the input's sample rate, so
+    /// it should be designed with the cutoff for the relative_speed that it's used at, and its taps should sum
+    /// to 1.0 for unity gain. This gives control over the transition band and stopband, at the cost of an
+    /// interpolation per tap. The kernel needs at least 1 tap
+    pub fn aliasing_filter_kernel(mut self, taps: &[f32]) -> InterpolatorBuilder {
+        self.config.aliasing_filter_kernel = Some(taps.to_vec());
+        self
+    }
+
+    /// Calls on_transform with every forward transform that's computed from the signal, (IE, each time a window
+    /// isn't in the transform cache, and for every window of a resample pass,) for analysis. It's called with
+    /// the index that the window is centered on, and all window_size frequencies, after the window function is
+    /// applied and before they're shifted, scaled according to the Normalization as get_spectrum scales them.
+    /// Transforms read from the cache aren't reported again. Clones share the hook. Like the planner, the hook
+    /// isn't part of the InterpolatorConfig
+    pub fn on_transform(
+        mut self,
+        on_transform: impl Fn(isize, &[Complex32]) + Send + Sync + 'static,
+    ) -> InterpolatorBuilder {
+        self.on_transform = Some(Arc::new(on_transform));
+        self
+    }
+
+    pub fn build<TSampleProvider, TChannelId, TError>(
+        self,
+        sample_provider: TSampleProvider,
+    ) -> Interpolator<TSampleProvider, TChannelId, TError>
+    where
+        TSampleProvider: SampleProvider<TChannelId, TError>,
+        TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+    {
+        let mut config = self.config;
+        if let Err(error) = config.validate() {
+            panic!("{}", error.message());
+        }
+
+        if config.round_window_to_fast_size {
+            config.window_size = fast_window_size(config.window_size);
+        }
+
+        let mut interpolator = Interpolator {
+            planner: self
+                .planner
+                .unwrap_or_else(|| Arc::new(Mutex::new(FftPlanner::new()))),
+            fft_cache: RefCell::new(HashMap::new()),
+            sample_provider,
+            window_size: config.window_size,
+            round_window_to_fast_size: config.round_window_to_fast_size,
+            num_samples: config.num_samples,
+            min_oversampling_ratio: config.min_oversampling_ratio,
+            max_oversampling_ratio: config.max_oversampling_ratio,
+            aliasing_filter_epsilon: config.aliasing_filter_epsilon,
+            rotator_cache_resolution: config.rotator_cache_resolution,
+            remove_dc: config.remove_dc,
+            boundary_mode: config.boundary_mode,
+            non_finite_samples: config.non_finite_samples,
+            normalization: config.normalization,
+            evaluation_point: config.evaluation_point,
+            window_offset: config.window_offset,
+            fade_out_tail: config.fade_out_tail,
+            clamp: config.clamp,
+            interpolation_mode: config.interpolation_mode,
+            max_overshoot: Cell::new(0.0),
+            index_offset: match config.evaluation_point {
+                EvaluationPoint::Center => 0.0,
+                EvaluationPoint::FromLeftEdge(offset) => offset - (config.window_size / 2) as f32,
+            },
+            window_function: config.window_function,
+            window_coefficients: None,
+            window_transform: None,
+            window_gain_weights: None,
+            center_weights: Arc::from([]),
+            phase_shifts_per_sample: Arc::from([]),
+            transform_cache: RefCell::new(HashMap::new()),
+            generation: Cell::new(0),
+            reached_end: Cell::new(false),
+            rotator_cache: RefCell::new(HashMap::new()),
+            window_frame_cache: RefCell::new(None),
+            scratch_buffers: RefCell::new(Vec::new()),
+            aliasing_filter_kernel: config.aliasing_filter_kernel.as_deref().map(Arc::from),
+            on_transform: self.on_transform,
+            channel_num_samples: HashMap::new(),
+            _phantom_data: PhantomData,
+        };
+
+        // Calculate phase shifts per sample: Transform sine waves of 1.0, shift by one sample, transform back
+        let mut phase_transform = vec![Complex32::from_polar(1.0, 0.0); config.window_size];
+        phase_transform[0] = Complex32::from_polar(0.0, 0.0);
+        interpolator.with_fft_cache_entry(config.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(&mut phase_transform);
+
+            let first_sample = phase_transform.remove(0);
+            phase_transform.push(first_sample);
+            fft_cache_entry.forward(&mut phase_transform);
+        });
+
+        interpolator.phase_shifts_per_sample = phase_transform
+            .iter()
+            .take(config.window_size / 2 + 1)
+            .map(|freq| {
+                let (_, phase_shift_for_frequency) = freq.to_polar();
+                phase_shift_for_frequency
+            })
+            .collect();
+
+        interpolator.center_weights = interpolator.compute_center_weights().into();
+
+        // The coefficients are computed once, instead of for every window that's read
+        if config.window_function != WindowFunction::Rectangular {
+            let window_coefficients = config.window_function.coefficients(config.window_size);
+            let mut window_transform: Vec<Complex32> = window_coefficients
+                .iter()
+                .map(|coefficient| Complex32::new(*coefficient, 0.0))
+                .collect();
+            interpolator.with_fft_cache_entry(config.window_size, |fft_cache_entry| {
+                fft_cache_entry.forward(&mut window_transform)
+            });
+
+            // Past DC, (and Nyquist, when window_size is even,) each frequency's mirror is its conjugate, so the
+            // pair's contribution to the center sample is twice the real part of the positive frequency's
+            let half_window_size = config.window_size / 2;
+            let window_gain_weights: Vec<Complex32> = window_transform
+                .iter()
+                .zip(interpolator.center_weights.iter())
+                .take(half_window_size + 1)
+                .enumerate()
+                .map(|(freq_index, (frequency, weight))| {
+                    if freq_index == 0 || freq_index * 2 == config.window_size {
+                        frequency * weight
+                    } else {
+                        frequency * weight * 2.0
+                    }
+                })
+                .collect();
+
+            interpolator.window_coefficients = Some(window_coefficients.into());
+            interpolator.window_transform = Some(window_transform.into());
+            interpolator.window_gain_weights = Some(window_gain_weights.into());
+        }
+
+        interpolator
+    }
+}
+
 impl<TSampleProvider, TChannelId, TError> Interpolator<TSampleProvider, TChannelId, TError>
 where
     TSampleProvider: SampleProvider<TChannelId, TError>,
     TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
 {
+    /// Creates an interpolator with the default configuration. window_size must be at least 2; panics otherwise
     pub fn new(
         window_size: usize,
         num_samples: usize,
         sample_provider: TSampleProvider,
     ) -> Interpolator<TSampleProvider, TChannelId, TError> {
-        let mut planner = FftPlanner::new();
+        InterpolatorBuilder::new(window_size, num_samples).build(sample_provider)
+    }
 
-        let fft_forward = planner.plan_fft_forward(window_size);
-        let scratch_forward_length = fft_forward.get_inplace_scratch_len();
-        let mut scratch_forward = vec![Complex32::new(0.0, 0.0); scratch_forward_length];
+    /// Creates an interpolator with the settings in config. Returns an error instead of panicking if config is
+    /// invalid, so that configs from untrusted input can be rejected. (See InterpolatorConfig::validate)
+    pub fn from_config(
+        config: InterpolatorConfig,
+        sample_provider: TSampleProvider,
+    ) -> Result<Interpolator<TSampleProvider, TChannelId, TError>, ConfigError> {
+        Ok(InterpolatorBuilder::from_config(config)?.build(sample_provider))
+    }
 
-        let fft_inverse = planner.plan_fft_inverse(window_size);
-        let scratch_inverse_length = fft_forward.get_inplace_scratch_len();
-        let mut scratch_inverse = vec![Complex32::new(0.0, 0.0); scratch_inverse_length];
+    /// This interpolator's settings, which Interpolator::from_config can use to create an equivalent
+    /// interpolator
+    pub fn config(&self) -> InterpolatorConfig {
+        InterpolatorConfig {
+            window_size: self.window_size,
+            round_window_to_fast_size: self.round_window_to_fast_size,
+            num_samples: self.num_samples,
+            min_oversampling_ratio: self.min_oversampling_ratio,
+            max_oversampling_ratio: self.max_oversampling_ratio,
+            aliasing_filter_epsilon: self.aliasing_filter_epsilon,
+            rotator_cache_resolution: self.rotator_cache_resolution,
+            remove_dc: self.remove_dc,
+            boundary_mode: self.boundary_mode,
+            non_finite_samples: self.non_finite_samples,
+            normalization: self.normalization,
+            evaluation_point: self.evaluation_point,
+            window_offset: self.window_offset,
+            fade_out_tail: self.fade_out_tail,
+            clamp: self.clamp,
+            interpolation_mode: self.interpolation_mode,
+            window_function: self.window_function,
+            aliasing_filter_kernel: self.aliasing_filter_kernel.as_deref().map(<[f32]>::to_vec),
+        }
+    }
 
-        // Calculate scale: Transform a DC signal of 1.0 back and forth to determine scale
-        let mut scale_transform = vec![Complex32::new(1.0, 0.0); window_size];
-        fft_forward.process_with_scratch(&mut scale_transform, &mut scratch_forward);
-        fft_inverse.process_with_scratch(&mut scale_transform, &mut scratch_inverse);
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
 
-        // Calculate phase shifts per sample: Transform sine waves of 1.0, shift by one sample, transform back
-        let mut phase_transform = vec![Complex32::from_polar(1.0, 0.0); window_size];
-        phase_transform[0] = Complex32::from_polar(0.0, 0.0);
-        fft_inverse.process_with_scratch(&mut phase_transform, &mut scratch_inverse);
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
 
-        let first_sample = phase_transform.remove(0);
-        phase_transform.push(first_sample);
-        fft_forward.process_with_scratch(&mut phase_transform, &mut scratch_forward);
+    /// The number of samples in channel_id: The num_samples set with set_channel_num_samples, or else the
+    /// interpolator's num_samples
+    pub fn channel_num_samples(&self, channel_id: TChannelId) -> usize {
+        self.channel_num_samples
+            .get(&channel_id)
+            .copied()
+            .unwrap_or(self.num_samples)
+    }
 
-        let mut phase_shifts_per_sample = Vec::with_capacity(window_size / 2);
-        for freq_index in 0..=(window_size / 2) {
-            let (_, phase_shift_for_frequency) = phase_transform[freq_index].to_polar();
-            phase_shifts_per_sample.push(phase_shift_for_frequency);
-        }
+    /// Sets the number of samples in channel_id, for interpolating channels of different lengths through one
+    /// interpolator. Each channel's windows are padded according to the BoundaryMode past its own end, its
+    /// tail fades out before its own end, and whole-signal operations such as resample_all return as many
+    /// samples as it has. Channels that aren't set use num_samples. channel_id's cached transform is dropped
+    pub fn set_channel_num_samples(&mut self, channel_id: TChannelId, num_samples: usize) {
+        self.channel_num_samples.insert(channel_id, num_samples);
+        self.invalidate_channel(channel_id);
+    }
 
-        Interpolator {
-            fft_forward,
-            scratch_forward: RefCell::new(scratch_forward),
-            fft_inverse,
-            scratch_inverse: RefCell::new(scratch_inverse),
-            sample_provider,
-            window_size,
-            scale: scale_transform[0].re,
-            num_samples,
-            phase_shifts_per_sample,
-            transform_cache: RefCell::new(HashMap::new()),
-            _phantom_data: PhantomData,
-        }
+    /// Whether window_size is larger than num_samples. Every window then extends past both ends of the signal,
+    /// so most of each window is the BoundaryMode's padding instead of signal, and the reconstruction is much
+    /// less accurate. Short clips need a smaller window_size
+    pub fn window_exceeds_signal(&self) -> bool {
+        self.window_size > self.num_samples
     }
 
-    pub fn get_interpolated_sample(
-        &self,
-        channel_id: TChannelId,
-        index: f32,
-    ) -> Result<f32, TError> {
-        let index_truncated = index.trunc();
-        if index == index_truncated {
-            return self
-                .sample_provider
-                .get_sample(channel_id, index_truncated as usize);
-        }
+    pub fn sample_provider(&self) -> &TSampleProvider {
+        &self.sample_provider
+    }
 
-        let index_truncated_isize = index_truncated as isize;
-        let half_window_size_usize = self.window_size / 2;
-        let half_window_size_isize = half_window_size_usize as isize;
+    pub fn boundary_mode(&self) -> BoundaryMode {
+        self.boundary_mode
+    }
 
-        let mut transform = {
-            let mut transform_cache = self.transform_cache.borrow_mut();
+    pub fn non_finite_samples(&self) -> NonFiniteSamples {
+        self.non_finite_samples
+    }
 
-            // Check cache first
-            if let Some(cache_entry) = transform_cache.get(&channel_id) {
-                if cache_entry.index == index_truncated as usize {
-                    cache_entry.transform.clone()
-                } else {
-                    // Index doesn't match, need to compute new transform
-                    self.compute_transform(&mut transform_cache, channel_id, index_truncated_isize, half_window_size_isize)?
-                }
-            } else {
-                self.compute_transform(&mut transform_cache, channel_id, index_truncated_isize, half_window_size_isize)?
-            }
-        };
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
 
-        for freq_index in 1..=(self.window_size / 2) {
-            let (freq_amplitude, phase) = transform[freq_index].to_polar();
+    pub fn evaluation_point(&self) -> EvaluationPoint {
+        self.evaluation_point
+    }
 
-            // Adjust phase for frequency
-            let phase_shift_for_sample = self.phase_shifts_per_sample[freq_index];
-            let phase_adjustment = phase_shift_for_sample * index.fract();
-            let adjusted_phase = phase + phase_adjustment;
+    pub fn window_offset(&self) -> usize {
+        self.window_offset
+    }
 
-            transform[freq_index] = Complex32::from_polar(freq_amplitude, adjusted_phase);
-            let opposite_freq_index = self.window_size - freq_index;
-            if opposite_freq_index != freq_index {
-                transform[opposite_freq_index] =
-                    Complex32::from_polar(freq_amplitude, adjusted_phase * -1.0);
-            }
-        }
+    pub fn fade_out_tail(&self) -> bool {
+        self.fade_out_tail
+    }
+
+    pub fn round_window_to_fast_size(&self) -> bool {
+        self.round_window_to_fast_size
+    }
 
-        let mut scratch_inverse = self.scratch_inverse.borrow_mut();
-        self.fft_inverse
-            .process_with_scratch(&mut transform, &mut scratch_inverse);
+    pub fn clamp(&self) -> Option<(f32, f32)> {
+        self.clamp
+    }
 
-        let interpolated_sample = transform[half_window_size_usize].re / self.scale;
-        Ok(interpolated_sample)
+    pub fn interpolation_mode(&self) -> InterpolationMode {
+        self.interpolation_mode
     }
 
-    // Helper function to compute and cache transform
-    fn compute_transform(
+    /// The furthest that any sample this interpolator returned was outside of the clamp range, or -1.0 to 1.0
+    /// if samples aren't clamped, before clamping. 0.0 if no sample was out of range. This is measured whether
+    /// or not samples are clamped, to help decide between clamping, normalizing, or accepting the overshoot
+    pub fn max_overshoot(&self) -> f32 {
+        self.max_overshoot.get()
+    }
+
+    /// Resets max_overshoot to 0.0, to measure the overshoot of another read
+    pub fn reset_max_overshoot(&self) {
+        self.max_overshoot.set(0.0);
+    }
+
+    pub fn window_function(&self) -> WindowFunction {
+        self.window_function
+    }
+
+    /// The taps set with InterpolatorBuilder::aliasing_filter_kernel, or None for the averaging filter
+    pub fn aliasing_filter_kernel(&self) -> Option<&[f32]> {
+        self.aliasing_filter_kernel.as_deref()
+    }
+
+    /// The phase shift, in radians, that each frequency bin (from DC through Nyquist) undergoes when the
+    /// signal is shifted by one sample
+    pub fn phase_shifts_per_sample(&self) -> &[f32] {
+        &self.phase_shifts_per_sample
+    }
+
+    /// Gets the sample at index. When relative_speed is greater than 1.0, (IE, the signal is being
+    /// downsampled,) the result is filtered to avoid aliasing.
+    ///
+    /// The window_size samples starting at floor(index) - window_size / 2 are transformed, shifted by
+    /// fract(index), and transformed back; the sample at window_size / 2 is returned. Whole-sample indexes are
+    /// read directly. See EvaluationPoint to reconstruct elsewhere in the window, and
+    /// InterpolatorBuilder::window_offset to read fewer samples after index
+    pub fn get_interpolated_sample(
         &self,
-        transform_cache: &mut HashMap<TChannelId, TransformCacheEntry>,
         channel_id: TChannelId,
-        index_truncated_isize: isize,
-        half_window_size_isize: isize,
-    ) -> Result<Vec<Complex32>, TError> {
-        let mut new_transform = Vec::with_capacity(self.window_size);
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        let index = index + self.index_offset;
+        let sample = self.interpolate(channel_id, index, relative_speed)?;
 
-        for window_sample_index in (index_truncated_isize - half_window_size_isize)
-            ..(index_truncated_isize + half_window_size_isize)
-        {
-            let sample =
-                if window_sample_index >= 0 && window_sample_index < self.num_samples as isize {
-                    self.sample_provider
-                        .get_sample(channel_id, window_sample_index as usize)?
-                } else {
-                    0.0
-                };
+        Ok(self.limit(sample * self.tail_fade_gain(channel_id, index)))
+    }
 
-            new_transform.push(Complex32 {
-                re: sample,
-                im: 0.0,
-            });
+    /// get_interpolated_sample, but a NaN or infinite index, a relative_speed that isn't finite and greater than
+    /// 0.0, or a window that's longer than the channel is returned as an InterpolatorError instead of being
+    /// interpolated, and the SampleProvider's errors are wrapped in InterpolatorError::Provider
+    pub fn try_get_interpolated_sample(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, InterpolatorError<TError>> {
+        if !index.is_finite() {
+            return Err(InterpolatorError::InvalidIndex(index));
         }
 
-        let mut scratch_forward = self.scratch_forward.borrow_mut();
-        self.fft_forward
-            .process_with_scratch(&mut new_transform, &mut scratch_forward);
+        self.check_speed_and_window(channel_id, relative_speed)?;
+        self.get_interpolated_sample(channel_id, index, relative_speed)
+            .map_err(InterpolatorError::Provider)
+    }
 
-        // Store in cache
-        transform_cache.insert(
+    /// resample_all, but a ratio that isn't finite and greater than 0.0, or a window that's longer than the
+    /// channel, is returned as an InterpolatorError, and the SampleProvider's errors are wrapped in
+    /// InterpolatorError::Provider
+    pub fn try_resample_all(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+    ) -> Result<Vec<f32>, InterpolatorError<TError>> {
+        self.check_speed_and_window(channel_id, ratio)?;
+        self.resample_all(channel_id, ratio)
+            .map_err(InterpolatorError::Provider)
+    }
+
+    fn check_speed_and_window(
+        &self,
+        channel_id: TChannelId,
+        relative_speed: f32,
+    ) -> Result<(), InterpolatorError<TError>> {
+        if !relative_speed.is_finite() || relative_speed <= 0.0 {
+            return Err(InterpolatorError::InvalidSpeed(relative_speed));
+        }
+
+        let num_samples = self.channel_num_samples(channel_id);
+        if self.window_size > num_samples {
+            return Err(InterpolatorError::WindowTooLarge {
+                window_size: self.window_size,
+                num_samples,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The signal in channel_id as a function of the index, for code that takes a function handle, such as
+    /// root finders, plotters, and integrators. The function calls get_interpolated_sample with channel_id and
+    /// relative_speed, and reads through this interpolator's caches
+    pub fn as_fn(
+        &self,
+        channel_id: TChannelId,
+        relative_speed: f32,
+    ) -> impl Fn(f32) -> Result<f32, TError> + '_ {
+        move |index| self.get_interpolated_sample(channel_id, index, relative_speed)
+    }
+
+    /// Gets the samples at integer_index + each of fracs, (see get_interpolated_sample,) such as to draw a smooth
+    /// curve through a window. The forward transform is computed once for each window that the offsets fall in,
+    /// and each offset's center sample is accumulated directly from the shifted frequencies, instead of
+    /// transforming the whole window back for each offset. The results are the same as get_interpolated_sample
+    /// with a relative_speed of 1.0. The anti-aliasing filter doesn't read its points this way: They're up to a
+    /// sample apart, so nearly every point needs its own window, and transforming the windows forward dominates
+    pub fn interpolate_offsets(
+        &self,
+        channel_id: TChannelId,
+        integer_index: usize,
+        fracs: &[f32],
+    ) -> Result<Vec<f32>, TError> {
+        if self.interpolation_mode != InterpolationMode::BandLimited {
+            return fracs
+                .iter()
+                .map(|fract| {
+                    self.get_interpolated_sample(channel_id, integer_index as f32 + fract, 1.0)
+                })
+                .collect();
+        }
+
+        let mut output = Vec::with_capacity(fracs.len());
+        let mut forward_transform: Option<(isize, Vec<Complex32>)> = None;
+        let mut transform = Vec::with_capacity(self.window_size);
+        for fract in fracs {
+            let index = integer_index as f32 + fract + self.index_offset;
+            let index_floor = index.floor();
+            let index_floor_isize = index_floor as isize;
+
+            let sample = if index == index_floor && !self.remove_dc {
+                self.get_boundary_sample(channel_id, index_floor_isize)?
+            } else {
+                let forward_transform = match forward_transform {
+                    Some((forward_index, ref forward_transform))
+                        if forward_index == index_floor_isize =>
+                    {
+                        forward_transform
+                    }
+                    _ => {
+                        let new_forward_transform =
+                            self.get_transform(channel_id, index_floor_isize)?;
+                        &forward_transform
+                            .insert((index_floor_isize, new_forward_transform))
+                            .1
+                    }
+                };
+
+                transform.clear();
+                transform.extend_from_slice(forward_transform);
+                self.reconstruct_center(&mut transform, index - index_floor)
+            };
+
+            output.push(self.limit(sample * self.tail_fade_gain(channel_id, index)));
+        }
+
+        Ok(output)
+    }
+
+    /// Gets the sample at seconds into the signal, where the signal has sample_rate samples per second. (See
+    /// get_interpolated_sample.) seconds is an f64, like most clocks, and is multiplied by sample_rate without
+    /// rounding. The index is then an f32, like get_interpolated_sample's, so its fraction of a sample is still
+    /// coarse late in long signals: 1 / 128 of a sample at 2^16 samples, and none at all from 2^23 samples
+    pub fn get_interpolated_sample_at_time(
+        &self,
+        channel_id: TChannelId,
+        seconds: f64,
+        sample_rate: u32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        let index = (seconds * sample_rate as f64) as f32;
+        self.get_interpolated_sample(channel_id, index, relative_speed)
+    }
+
+    /// Gets the sample at index, (see get_interpolated_sample,) reconstructed from only the frequency bins lo_bin
+    /// through hi_bin of the window. Bin k is k / window_size cycles per sample, and hi_bin can be at most
+    /// window_size / 2, (Nyquist.) Splitting a signal into adjacent bands this way, (IE, 0 through 3 and 4
+    /// through window_size / 2,) gives bands that sum to the whole signal. This is always a band-limited
+    /// reconstruction, regardless of the InterpolationMode, and whole-sample indexes are reconstructed too
+    pub fn get_interpolated_sample_band(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+        lo_bin: usize,
+        hi_bin: usize,
+    ) -> Result<f32, TError> {
+        assert!(
+            lo_bin <= hi_bin && hi_bin <= self.window_size / 2,
+            "lo_bin must not be greater than hi_bin, and hi_bin must not be greater than window_size / 2"
+        );
+
+        let index = index + self.index_offset;
+        let sample = if !self.uses_aliasing_filter(relative_speed) {
+            self.get_band_sample(channel_id, index, lo_bin, hi_bin)?
+        } else {
+            self.average_oversampled(index, relative_speed, |oversample_index| {
+                self.get_band_sample(channel_id, oversample_index, lo_bin, hi_bin)
+            })?
+        };
+
+        Ok(self.limit(sample * self.tail_fade_gain(channel_id, index)))
+    }
+
+    fn get_band_sample(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        lo_bin: usize,
+        hi_bin: usize,
+    ) -> Result<f32, TError> {
+        let index_floor = index.floor();
+        let mut transform = self.get_transform(channel_id, index_floor as isize)?;
+
+        for freq_index in (0..lo_bin).chain((hi_bin + 1)..=(self.window_size / 2)) {
+            transform[freq_index] = Complex32::new(0.0, 0.0);
+            transform[(self.window_size - freq_index) % self.window_size] =
+                Complex32::new(0.0, 0.0);
+        }
+
+        Ok(self.reconstruct_sample(&mut transform, index - index_floor))
+    }
+
+    /// Gets the sample at index, (see get_interpolated_sample,) after multiplying each frequency bin of the
+    /// window by a gain, to preview a per-bin EQ. bin_gains holds window_size / 2 + 1 gains, for bins 0 (DC)
+    /// through window_size / 2, (Nyquist,) where bin k is k / window_size cycles per sample. Each gain applies
+    /// to the bin's negative frequency too, so the reconstruction stays real. This reuses the cached forward
+    /// transform, is always a band-limited reconstruction, regardless of the InterpolationMode, and whole-sample
+    /// indexes are reconstructed too
+    pub fn get_interpolated_sample_with_bin_gains(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+        bin_gains: &[f32],
+    ) -> Result<f32, TError> {
+        assert_eq!(
+            self.window_size / 2 + 1,
+            bin_gains.len(),
+            "bin_gains must have window_size / 2 + 1 gains"
+        );
+
+        let index = index + self.index_offset;
+        let sample = if !self.uses_aliasing_filter(relative_speed) {
+            self.get_bin_gains_sample(channel_id, index, bin_gains)?
+        } else {
+            self.average_oversampled(index, relative_speed, |oversample_index| {
+                self.get_bin_gains_sample(channel_id, oversample_index, bin_gains)
+            })?
+        };
+
+        Ok(self.limit(sample * self.tail_fade_gain(channel_id, index)))
+    }
+
+    fn get_bin_gains_sample(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        bin_gains: &[f32],
+    ) -> Result<f32, TError> {
+        let index_floor = index.floor();
+        let mut transform = self.get_transform(channel_id, index_floor as isize)?;
+
+        for (freq_index, gain) in bin_gains.iter().enumerate() {
+            let opposite_freq_index = (self.window_size - freq_index) % self.window_size;
+            transform[freq_index] *= *gain;
+            if opposite_freq_index != freq_index {
+                transform[opposite_freq_index] *= *gain;
+            }
+        }
+
+        Ok(self.reconstruct_sample(&mut transform, index - index_floor))
+    }
+
+    /// Gets the analytic signal at index: The real part is the signal, and the imaginary part is its Hilbert
+    /// transform, so the magnitude is the instantaneous amplitude and the argument is the instantaneous phase.
+    /// The window's negative frequencies are zeroed and its positive frequencies are doubled before it's
+    /// shifted and transformed back, reusing the same cached forward transform as get_interpolated_sample.
+    /// The sample isn't clamped, and this is always a band-limited reconstruction, regardless of the
+    /// InterpolationMode
+    pub fn get_analytic_sample(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+    ) -> Result<Complex32, TError> {
+        let index = index + self.index_offset;
+        let index_floor = index.floor();
+        let fract = index - index_floor;
+
+        let mut transform = self.get_transform(channel_id, index_floor as isize)?;
+        if self.remove_dc {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        // Nyquist, when window_size is even, is its own mirror, so it isn't doubled
+        let half_window_size = self.window_size / 2;
+        for freq_index in 1..=half_window_size {
+            let opposite_freq_index = self.window_size - freq_index;
+            if opposite_freq_index != freq_index {
+                transform[freq_index] *= 2.0;
+                transform[opposite_freq_index] = Complex32::new(0.0, 0.0);
+            }
+        }
+
+        // The negative frequencies are zero, so they stay zero when they're rotated
+        self.shift_transform_with_symmetry(&mut transform, fract, Symmetry::Independent);
+        let sample = self.inverse_center(&mut transform) / self.window_gain(fract);
+        Ok(sample * self.tail_fade_gain(channel_id, index))
+    }
+
+    /// Gets the order-th derivative of the band-limited signal at index, in units per sample. (order 0 is the
+    /// signal itself.) Each frequency of the cached forward transform is multiplied by
+    /// (j * 2 * PI * frequency / window_size) ^ order before it's transformed back. For odd orders, Nyquist is
+    /// dropped, because its derivative isn't defined. As with samples, the window function's gain is divided
+    /// out, which is only approximate for derivatives with windows other than Rectangular. The result isn't
+    /// clamped
+    pub fn get_interpolated_derivative(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        order: u32,
+    ) -> Result<f32, TError> {
+        let index = index + self.index_offset;
+        let index_floor = index.floor();
+        let fract = index - index_floor;
+
+        let mut transform = self.get_transform(channel_id, index_floor as isize)?;
+        if self.remove_dc || order > 0 {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        self.shift_transform(&mut transform, fract);
+
+        let half_window_size = self.window_size / 2;
+        for freq_index in 1..=half_window_size {
+            let opposite_freq_index = self.window_size - freq_index;
+            let angular_frequency = 2.0 * PI * freq_index as f32 / self.window_size as f32;
+            if opposite_freq_index == freq_index {
+                if order % 2 == 1 {
+                    transform[freq_index] = Complex32::new(0.0, 0.0);
+                } else {
+                    transform[freq_index] *= Complex32::new(0.0, angular_frequency).powu(order);
+                }
+            } else {
+                transform[freq_index] *= Complex32::new(0.0, angular_frequency).powu(order);
+                transform[opposite_freq_index] *=
+                    Complex32::new(0.0, -angular_frequency).powu(order);
+            }
+        }
+
+        let derivative = self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(&mut transform);
+            transform[half_window_size].re / fft_cache_entry.inverse_scale()
+        });
+
+        Ok(derivative / self.window_gain(fract) * self.tail_fade_gain(channel_id, index))
+    }
+
+    /// Gets the sample at index, like get_interpolated_sample, but through &mut self, so that the channel's
+    /// cached transform is read without borrowing the transform cache. The anti-aliasing filter and the
+    /// InterpolationModes other than BandLimited use get_interpolated_sample
+    pub fn get_interpolated_sample_mut(
+        &mut self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        if self.interpolation_mode != InterpolationMode::BandLimited
+            || self.uses_aliasing_filter(relative_speed)
+        {
+            return self.get_interpolated_sample(channel_id, index, relative_speed);
+        }
+
+        let index = index + self.index_offset;
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+
+        let sample = if index == index_floor && !self.remove_dc {
+            self.get_boundary_sample(channel_id, index_floor_isize)?
+        } else {
+            self.reconstruct_sample_mut(channel_id, index_floor_isize, index - index_floor)?
+        };
+
+        Ok(self.limit(sample * self.tail_fade_gain(channel_id, index)))
+    }
+
+    // get_transform and reconstruct_sample, through &mut self. The channel's cached transform is taken out of the
+    // cache while it's shifted, so that it's read in place instead of being cloned
+    fn reconstruct_sample_mut(
+        &mut self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+        fract: f32,
+    ) -> Result<f32, TError> {
+        let generation = self.cache_generation();
+        match self.transform_cache.get_mut().remove(&channel_id) {
+            Some(cache_entry)
+                if cache_entry.index == index_floor_isize
+                    && cache_entry.generation == generation =>
+            {
+                let sample = self.reconstruct_sample_from(&cache_entry.transform, fract);
+                self.transform_cache
+                    .get_mut()
+                    .insert(channel_id, cache_entry);
+                Ok(sample)
+            }
+            _ => self.compute_transform_with(channel_id, index_floor_isize, |transform| {
+                self.reconstruct_sample_from(transform, fract)
+            }),
+        }
+    }
+
+    /// Invalidates every cached transform, for when the samples behind the SampleProvider change, (IE, an
+    /// editable buffer,) and the provider doesn't report its own generation. (See SampleProvider::generation)
+    pub fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+
+    fn cache_generation(&self) -> (u64, u64) {
+        (self.generation.get(), self.sample_provider.generation())
+    }
+
+    /// Interpolates from samples that the caller already has, instead of reading them from the SampleProvider.
+    /// center_index_in_slice is an index into samples, and is interpolated as if samples were the whole
+    /// signal: Samples outside of the slice are 0.0, and nothing is cached. The evaluation point, window
+    /// function, and anti-aliasing filter apply as they do in get_interpolated_sample, but fade_out_tail
+    /// doesn't. For the best accuracy, samples should extend at least window_size / 2 past
+    /// center_index_in_slice on either side
+    pub fn get_interpolated_sample_from_slice(
+        &self,
+        samples: &[f32],
+        center_index_in_slice: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        let index = center_index_in_slice + self.index_offset;
+        let sample = self.interpolate_slice(samples, index, relative_speed)?;
+        Ok(self.limit(sample))
+    }
+
+    /// The magnitude response of reading at relative_speed, including the anti-aliasing filter, at num_points
+    /// frequencies evenly spaced from DC through Nyquist: Point n is at 0.5 * n / (num_points - 1) cycles per
+    /// input sample. Each frequency is measured by interpolating a cosine and a sine of that frequency, which
+    /// give the real and imaginary parts of the response, at several fractions of a sample, and averaging the
+    /// magnitudes. This is for analysis, (IE, to compare against other resamplers,) and doesn't read from the
+    /// SampleProvider, clamp, or change max_overshoot. num_points must be at least 2
+    pub fn frequency_response(&self, relative_speed: f32, num_points: usize) -> Vec<f32> {
+        assert!(num_points >= 2, "num_points must be at least 2");
+
+        // Long enough that neither the window nor the anti-aliasing filter's span reaches the ends
+        let center = self.window_size + relative_speed.ceil() as usize;
+        let slice_len = center * 2 + 1;
+
+        (0..num_points)
+            .map(|point| {
+                let angular_frequency = PI * point as f32 / (num_points - 1) as f32;
+                let (cosine, sine): (Vec<f32>, Vec<f32>) = (0..slice_len)
+                    .map(|n| {
+                        let phase = angular_frequency * (n as f32 - center as f32);
+                        (phase.cos(), phase.sin())
+                    })
+                    .unzip();
+
+                let magnitude_sum: f32 = FREQUENCY_RESPONSE_FRACTS
+                    .iter()
+                    .map(|fract| {
+                        let index = center as f32 + fract + self.index_offset;
+                        let response = Complex32::new(
+                            self.interpolate_slice_infallible(&cosine, index, relative_speed),
+                            self.interpolate_slice_infallible(&sine, index, relative_speed),
+                        );
+                        response.norm()
+                    })
+                    .sum();
+
+                magnitude_sum / FREQUENCY_RESPONSE_FRACTS.len() as f32
+            })
+            .collect()
+    }
+
+    /// The highest frequency, in Hz, that reads at relative_speed reconstruct from a signal with sample_rate
+    /// samples per second. This is the highest frequency bin below Nyquist in a window, (A window's Nyquist
+    /// bin is only partially reconstructed,) and, when the anti-aliasing filter is used, no higher than the
+    /// Nyquist frequency of the output: sample_rate / (2.0 * relative_speed.) Content above this is attenuated
+    /// or lost, and the anti-aliasing filter starts to roll off content below it. Only meaningful for
+    /// InterpolationMode::BandLimited
+    pub fn max_reliable_frequency(&self, sample_rate: u32, relative_speed: f32) -> f32 {
+        let sample_rate = sample_rate as f32;
+        let window_limit =
+            sample_rate * ((self.window_size - 1) / 2) as f32 / self.window_size as f32;
+
+        if self.uses_aliasing_filter(relative_speed) {
+            window_limit.min(sample_rate / (2.0 * relative_speed))
+        } else {
+            window_limit
+        }
+    }
+
+    fn interpolate_slice_infallible(
+        &self,
+        samples: &[f32],
+        index: f32,
+        relative_speed: f32,
+    ) -> f32 {
+        match self.interpolate_slice(samples, index, relative_speed) {
+            Ok(sample) => sample,
+            Err(_) => unreachable!("Reading from a slice never fails"),
+        }
+    }
+
+    // Interpolates from samples at index, which already includes index_offset, without clamping
+    fn interpolate_slice(
+        &self,
+        samples: &[f32],
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        if self.interpolation_mode != InterpolationMode::BandLimited {
+            self.interpolate_time_domain(index, |index| {
+                Ok(usize::try_from(index)
+                    .ok()
+                    .and_then(|index| samples.get(index))
+                    .copied()
+                    .unwrap_or(0.0))
+            })
+        } else if !self.uses_aliasing_filter(relative_speed) {
+            self.interpolate_from_slice(samples, index)
+        } else {
+            self.average_oversampled(index, relative_speed, |oversample_index| {
+                self.interpolate_from_slice(samples, oversample_index)
+            })
+        }
+    }
+
+    fn interpolate_from_slice(&self, samples: &[f32], index: f32) -> Result<f32, TError> {
+        let read_sample = |index: isize| -> Result<f32, TError> {
+            Ok(usize::try_from(index)
+                .ok()
+                .and_then(|index| samples.get(index))
+                .copied()
+                .unwrap_or(0.0))
+        };
+
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+
+        if index == index_floor && !self.remove_dc {
+            return read_sample(index_floor_isize);
+        }
+
+        let mut transform = self.transform_window(index_floor_isize, read_sample)?;
+        Ok(self.reconstruct_sample(&mut transform, index - index_floor))
+    }
+
+    /// Drops channel_id's cached transform, so that the next read for channel_id starts clean. Each channel's
+    /// transform is reused when the same window is read again, so call this when the samples behind channel_id
+    /// change, (IE, a channel id is reused for a retriggered voice,) or to free the transform's memory when
+    /// channel_id won't be read again
+    pub fn invalidate_channel(&self, channel_id: TChannelId) {
+        self.transform_cache.borrow_mut().remove(&channel_id);
+    }
+
+    /// Whether the forward transform of the window that interpolates between index and index + 1 is cached
+    /// for channel_id, (see get_window,) so that reading there won't read from the SampleProvider. This only
+    /// peeks at the cache, and never computes a transform. Indexes here are after the evaluation point's
+    /// offset, as in get_window
+    pub fn is_cached(&self, channel_id: TChannelId, index: usize) -> bool {
+        let generation = self.cache_generation();
+        match self.transform_cache.borrow().get(&channel_id) {
+            Some(cache_entry) => {
+                cache_entry.index == index as isize && cache_entry.generation == generation
+            }
+            None => false,
+        }
+    }
+
+    /// The indexes of the samples that get_interpolated_sample reads for index and relative_speed, in order and
+    /// without duplicates, without reading them. This includes every window that the anti-aliasing filter
+    /// reads. Indexes outside of the signal aren't read from the SampleProvider, but are filled according to
+    /// the BoundaryMode, (IE, BoundaryMode::Mirror reads the reflected sample instead.) The transform cache is
+    /// ignored, so a read whose window is cached may read fewer samples
+    pub fn accessed_indices(&self, index: f32, relative_speed: f32) -> Vec<isize> {
+        let index = index + self.index_offset;
+        let mut indices = BTreeSet::new();
+
+        match self.interpolation_mode {
+            InterpolationMode::BandLimited => {
+                if !self.uses_aliasing_filter(relative_speed) {
+                    self.insert_window_indices(&mut indices, index);
+                } else if let Some(aliasing_filter_kernel) = &self.aliasing_filter_kernel {
+                    for kernel_index in kernel_indexes(index, aliasing_filter_kernel.len()) {
+                        self.insert_window_indices(&mut indices, kernel_index);
+                    }
+                } else {
+                    // The same oversampled points as average_oversampled
+                    let (lower_ratio, upper_ratio, upper_weight) =
+                        self.oversampling(relative_speed);
+                    let mut oversampling_ratios = vec![upper_ratio];
+                    if blends_lower_average(lower_ratio, upper_ratio, upper_weight) {
+                        oversampling_ratios.push(lower_ratio);
+                    }
+
+                    for oversampling_ratio in oversampling_ratios {
+                        for oversample_index in
+                            oversample_indexes(index, relative_speed, oversampling_ratio)
+                        {
+                            self.insert_window_indices(&mut indices, oversample_index);
+                        }
+                    }
+                }
+            }
+            _ => {
+                // The time-domain modes are run with a reader that only records the indexes
+                let indices = RefCell::new(&mut indices);
+                let _ =
+                    self.interpolate_time_domain(index, |sample_index| -> Result<f32, TError> {
+                        indices.borrow_mut().insert(sample_index);
+                        Ok(0.0)
+                    });
+            }
+        }
+
+        indices.into_iter().collect()
+    }
+
+    // Inserts the indexes of the samples that get_interpolated_sample_no_aliasing_filter reads for index
+    fn insert_window_indices(&self, indices: &mut BTreeSet<isize>, index: f32) {
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+        if index == index_floor && !self.remove_dc {
+            indices.insert(index_floor_isize);
+            return;
+        }
+
+        let window_start = self.window_start(index_floor_isize);
+        indices.extend(window_start..(window_start + self.window_size as isize));
+    }
+
+    /// The window_size samples that are transformed to interpolate between index and index + 1, as they're
+    /// read from the SampleProvider: index is at window_size / 2 + window_offset, samples outside of the signal
+    /// are filled according to the BoundaryMode, and the window function isn't applied. index is after the
+    /// evaluation point's offset, (see EvaluationPoint::FromLeftEdge)
+    pub fn get_window(&self, channel_id: TChannelId, index: usize) -> Result<Vec<f32>, TError> {
+        let window_start = self.window_start(index as isize);
+        let num_samples = self.channel_num_samples(channel_id);
+        (window_start..(window_start + self.window_size as isize))
+            .map(|window_sample_index| {
+                self.get_boundary_sample_with_len(channel_id, num_samples, window_sample_index)
+            })
+            .collect()
+    }
+
+    /// The spectrum of the window that get_window returns for index, after the window function is applied, with
+    /// all window_size frequencies from DC up, as numpy.fft.fft orders them. It's scaled according to the
+    /// Normalization
+    pub fn get_spectrum(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Vec<Complex32>, TError> {
+        let mut transform = self.read_window_transform(channel_id, index as isize)?;
+        let gain = self.spectrum_gain();
+        for value in transform.iter_mut() {
+            *value *= gain;
+        }
+
+        Ok(transform)
+    }
+
+    /// Estimates the fundamental period, in samples, of the window that get_window returns for index, for
+    /// pitch detection. Each whole-sample lag from min_period through max_period is scored by the normalized
+    /// autocorrelation of the window, and the shortest lag that's a peak, and nearly as strong as the best,
+    /// is refined to a fraction of a sample by fitting a parabola through its correlation and its neighbors'.
+    /// The samples are correlated as they're read, without the tail fade or the Limit. Returns 0.0 when no lag
+    /// correlates positively, (IE, in silence.) max_period must be less than window_size - 1, and the window
+    /// should hold at least two periods
+    pub fn estimate_period(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+        min_period: usize,
+        max_period: usize,
+    ) -> Result<f32, TError> {
+        assert!(
+            min_period >= 1 && min_period <= max_period,
+            "min_period must be at least 1 and no more than max_period"
+        );
+        assert!(
+            max_period + 1 < self.window_size,
+            "max_period must be less than window_size - 1"
+        );
+
+        let window = self.get_window(channel_id, index)?;
+        let correlation_at =
+            |lag: usize| normalized_correlation(&window[..self.window_size - lag], &window[lag..]);
+        let correlations: Vec<f32> = (min_period..=max_period).map(correlation_at).collect();
+
+        let best_correlation = correlations.iter().copied().fold(0.0, f32::max);
+        if best_correlation <= 0.0 {
+            return Ok(0.0);
+        }
+
+        // The best correlation is always a peak, so a lag is always found
+        let is_peak = |lag_index: usize| {
+            let correlation = correlations[lag_index];
+            correlation >= best_correlation * PERIOD_PEAK_THRESHOLD
+                && (lag_index == 0 || correlation >= correlations[lag_index - 1])
+                && correlations
+                    .get(lag_index + 1)
+                    .is_none_or(|next_correlation| correlation >= *next_correlation)
+        };
+        let lag = min_period
+            + (0..correlations.len())
+                .find(|lag_index| is_peak(*lag_index))
+                .unwrap_or(0);
+
+        // The vertex of the parabola through the neighboring lags' correlations. The neighbors are outside of
+        // correlations at min_period and max_period, and a flat or upturned parabola has no vertex to move to
+        let previous_correlation = correlation_at(lag - 1);
+        let correlation = correlations[lag - min_period];
+        let next_correlation = correlation_at(lag + 1);
+        let curvature = previous_correlation - 2.0 * correlation + next_correlation;
+        if curvature >= 0.0 {
+            return Ok(lag as f32);
+        }
+
+        let offset = (previous_correlation - next_correlation) / (2.0 * curvature);
+        Ok(lag as f32 + offset.clamp(-1.0, 1.0))
+    }
+
+    /// The forward and inverse scales of this interpolator's FFTs of size window_size: The value of the DC
+    /// frequency after transforming a DC signal of 1.0, and the value of each sample after transforming it
+    /// forward and back. Divide by these to match the interpolator's normalization. The FFTs for
+    /// window_size are planned if they haven't been already
+    pub fn fft_scale(&self, window_size: usize) -> (f32, f32) {
+        self.with_fft_cache_entry(window_size, |fft_cache_entry| {
+            (
+                fft_cache_entry.forward_scale(),
+                fft_cache_entry.inverse_scale(),
+            )
+        })
+    }
+
+    /// Whether get_interpolated_sample uses the anti-aliasing filter at relative_speed. (See
+    /// InterpolatorBuilder::aliasing_filter_epsilon)
+    pub fn uses_aliasing_filter(&self, relative_speed: f32) -> bool {
+        relative_speed > 1.0 + self.aliasing_filter_epsilon
+    }
+
+    /// The number of points that the anti-aliasing filter interpolates for each sample read at relative_speed.
+    /// Each point costs about as much as a read without the filter, so this is useful for estimating the cost
+    /// of a read. Returns 1 when the filter isn't used. Unless relative_speed is a power of two, the filter
+    /// averages two sets of points, one with half as many as the other, and blends the two averages so that the
+    /// output doesn't jump when relative_speed crosses a power of two; both sets are counted. With an aliasing
+    /// filter kernel, this is the number of taps
+    pub fn oversampling_ratio(&self, relative_speed: f32) -> usize {
+        if !self.uses_aliasing_filter(relative_speed) {
+            1
+        } else if let Some(aliasing_filter_kernel) = &self.aliasing_filter_kernel {
+            aliasing_filter_kernel.len()
+        } else {
+            let (lower_ratio, upper_ratio, upper_weight) = self.oversampling(relative_speed);
+            if blends_lower_average(lower_ratio, upper_ratio, upper_weight) {
+                lower_ratio + upper_ratio
+            } else {
+                upper_ratio
+            }
+        }
+    }
+
+    /// Gets the sample at index, (see get_interpolated_sample,) and the sample in the signal that's nearest to
+    /// it: The sample at round(index), or where the EvaluationPoint places index. The nearest sample is read
+    /// from the SampleProvider as-is, (or according to the BoundaryMode,) without the tail fade or clamping.
+    /// When index is a whole sample, the nearest sample is the interpolated sample, and isn't read twice
+    pub fn get_interpolated_with_nearest(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<(f32, f32), TError> {
+        let interpolated = self.get_interpolated_sample(channel_id, index, relative_speed)?;
+
+        let shifted_index = index + self.index_offset;
+        let nearest_index = shifted_index.round();
+        let nearest = if shifted_index == nearest_index
+            && self.interpolation_mode == InterpolationMode::BandLimited
+            && !self.uses_aliasing_filter(relative_speed)
+            && !self.remove_dc
+            && !self.fade_out_tail
+            && self.clamp.is_none()
+        {
+            interpolated
+        } else {
+            self.get_boundary_sample(channel_id, nearest_index as isize)?
+        };
+
+        Ok((interpolated, nearest))
+    }
+
+    /// Gets the sample at index, (see get_interpolated_sample,) and a confidence from 0.0 to 1.0 in how well
+    /// it was reconstructed. FFT interpolation is least reliable near Nyquist, so the confidence is the
+    /// fraction of the window's energy that's below the top quarter of its frequencies. The window is the one
+    /// that the sample is interpolated from, after the evaluation point's offset, and the DC frequency is
+    /// ignored. When the anti-aliasing filter is used, only the frequencies that it passes, (below
+    /// window_size / (2 * relative_speed),) are counted. A silent window has a confidence of 1.0. Measuring the
+    /// confidence doesn't replace the channel's cached transform
+    pub fn get_interpolated_sample_with_confidence(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<(f32, f32), TError> {
+        let sample = self.get_interpolated_sample(channel_id, index, relative_speed)?;
+        let index_floor_isize = (index + self.index_offset).floor() as isize;
+        let transform = self.peek_transform(channel_id, index_floor_isize)?;
+
+        let num_frequencies = if self.uses_aliasing_filter(relative_speed) {
+            ((self.window_size / 2) as f32 / relative_speed) as usize
+        } else {
+            self.window_size / 2
+        };
+        let high_frequency_start = num_frequencies - num_frequencies / 4;
+
+        let mut total_energy = 0.0;
+        let mut high_frequency_energy = 0.0;
+        for (freq_index, freq) in transform
+            .iter()
+            .enumerate()
+            .take(num_frequencies + 1)
+            .skip(1)
+        {
+            let energy = freq.norm_sqr();
+            total_energy += energy;
+            if freq_index > high_frequency_start {
+                high_frequency_energy += energy;
+            }
+        }
+
+        let confidence = if total_energy > 0.0 {
+            1.0 - high_frequency_energy / total_energy
+        } else {
+            1.0
+        };
+
+        Ok((sample, confidence))
+    }
+
+    /// Gets the sample at index for each of channel_ids, in the same order. (See get_interpolated_sample.) Each
+    /// channel is read through its own transform cache, but the rotations that shift each frequency, and the
+    /// window function's gain, are calculated once and shared by every channel
+    pub fn get_interpolated_frame(
+        &self,
+        channel_ids: &[TChannelId],
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<Vec<f32>, TError> {
+        if self.interpolation_mode != InterpolationMode::BandLimited
+            || self.uses_aliasing_filter(relative_speed)
+        {
+            return channel_ids
+                .iter()
+                .map(|channel_id| self.get_interpolated_sample(*channel_id, index, relative_speed))
+                .collect();
+        }
+
+        let index = index + self.index_offset;
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+
+        if index == index_floor && !self.remove_dc {
+            return channel_ids
+                .iter()
+                .map(|channel_id| {
+                    Ok(self.limit(
+                        self.get_boundary_sample(*channel_id, index_floor_isize)?
+                            * self.tail_fade_gain(*channel_id, index),
+                    ))
+                })
+                .collect();
+        }
+
+        let mut transforms = Vec::with_capacity(channel_ids.len());
+        for channel_id in channel_ids {
+            let mut transform = self.get_transform(*channel_id, index_floor_isize)?;
+            if self.remove_dc {
+                transform[0] = Complex32::new(0.0, 0.0);
+            }
+
+            transforms.push(transform);
+        }
+
+        let fract = index - index_floor;
+        let window_gain = self.window_gain(fract);
+
+        self.with_rotators(fract, |rotators| {
+            for transform in transforms.iter_mut() {
+                apply_rotators(transform, rotators, Symmetry::Conjugate);
+            }
+        });
+
+        Ok(
+            self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+                transforms
+                    .iter_mut()
+                    .zip(channel_ids)
+                    .map(|(transform, channel_id)| {
+                        fft_cache_entry.inverse(transform);
+                        let sample =
+                            transform[self.window_size / 2].re / fft_cache_entry.inverse_scale();
+                        self.limit(sample / window_gain * self.tail_fade_gain(*channel_id, index))
+                    })
+                    .collect()
+            }),
+        )
+    }
+
+    /// Writes interleaved frames into out, as audio APIs lay out their buffers: Frame n is at start + n * step,
+    /// and holds a sample for each of channel_ids, in the same order. (See get_interpolated_sample for
+    /// relative_speed.) Samples at or after the end of their channel are 0.0, so a fill that runs past the end
+    /// of the signal is padded with silence. The indexes are calculated in f64, so rounding errors don't build
+    /// up over long buffers. Once a fill has planned the transforms and filled the caches, (IE, the first fill at
+    /// a relative_speed,) later fills don't allocate, unless the SampleProvider does. out's length must be a
+    /// multiple of the number of channels
+    ///
+    /// channel_ids is a channel map: It may repeat a channel, (IE, to play a mono channel in both channels of
+    /// a stereo buffer,) and a repeated channel is interpolated once per frame and copied
+    pub fn fill_interleaved(
+        &self,
+        channel_ids: &[TChannelId],
+        out: &mut [f32],
+        start: f32,
+        step: f32,
+        relative_speed: f32,
+    ) -> Result<(), TError> {
+        self.fill_interleaved_f64(channel_ids, out, start as f64, step as f64, relative_speed)
+    }
+
+    // fill_interleaved, with start and step in f64 for callers that keep an f64 cursor
+    pub(crate) fn fill_interleaved_f64(
+        &self,
+        channel_ids: &[TChannelId],
+        out: &mut [f32],
+        start: f64,
+        step: f64,
+        relative_speed: f32,
+    ) -> Result<(), TError> {
+        assert!(!channel_ids.is_empty(), "channel_ids must not be empty");
+        assert!(
+            out.len().is_multiple_of(channel_ids.len()),
+            "out's length must be a multiple of the number of channels"
+        );
+
+        for (frame_index, frame) in out.chunks_exact_mut(channel_ids.len()).enumerate() {
+            let index = (frame_index as f64) * step + start;
+            for (output_channel, channel_id) in channel_ids.iter().enumerate() {
+                let earlier_output_channel = channel_ids[..output_channel]
+                    .iter()
+                    .position(|earlier_channel_id| earlier_channel_id == channel_id);
+
+                frame[output_channel] = if let Some(earlier_output_channel) = earlier_output_channel
+                {
+                    frame[earlier_output_channel]
+                } else if index < self.channel_num_samples(*channel_id) as f64 {
+                    self.get_interpolated_sample(*channel_id, index as f32, relative_speed)?
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resamples each of channel_ids, (see resample_all,) and writes each output sample to writer as soon as
+    /// it's interpolated, so that a signal can be streamed from a SampleProvider to a SampleWriter without
+    /// holding the output in memory. Frames are written in order, and each channel stops at its own
+    /// floor(num_samples / ratio) samples. Returns the number of frames, which is the longest channel's number
+    /// of output samples. The first error from either the SampleProvider or the writer stops resampling
+    pub fn resample_to_writer(
+        &self,
+        channel_ids: &[TChannelId],
+        ratio: f32,
+        writer: &mut impl SampleWriter<TChannelId, TError>,
+    ) -> Result<usize, TError> {
+        let num_frames = channel_ids
+            .iter()
+            .map(|channel_id| self.channel_output_len(*channel_id, ratio))
+            .max()
+            .unwrap_or(0);
+
+        for output_index in 0..num_frames {
+            let index = ((output_index as f64) * (ratio as f64)) as f32;
+            for channel_id in channel_ids {
+                if output_index < self.channel_output_len(*channel_id, ratio) {
+                    let sample = self.get_interpolated_sample(*channel_id, index, ratio)?;
+                    writer.write_sample(*channel_id, output_index, sample)?;
+                }
+            }
+        }
+
+        Ok(num_frames)
+    }
+
+    /// Resamples the entire signal, where ratio is the number of input samples per output sample. (IE, a
+    /// ratio of 2.0 halves the number of samples.) Returns floor(num_samples / ratio) samples
+    pub fn resample_all(&self, channel_id: TChannelId, ratio: f32) -> Result<Vec<f32>, TError> {
+        self.resample_all_with_progress(channel_id, ratio, |_| {})
+    }
+
+    /// Resamples the entire signal, (see resample_all,) calling progress with the fraction of the output
+    /// that's complete, from 0.0 to 1.0, every PROGRESS_INTERVAL output samples and once more when done.
+    /// progress is always called on the calling thread
+    pub fn resample_all_with_progress(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+        progress: impl FnMut(f32),
+    ) -> Result<Vec<f32>, TError> {
+        if let Some(step) = self.whole_sample_step(ratio) {
+            return self.read_whole_samples(channel_id, ratio, step, progress);
+        }
+
+        self.resample_indexes(
+            channel_id,
+            self.channel_output_len(channel_id, ratio),
+            ratio,
+            |output_index| self.resample_all_index(output_index, ratio),
+            progress,
+        )
+    }
+
+    /// Resamples the entire signal to exactly target_len samples, such as to fit a one-shot to a grid. The step
+    /// between output samples is channel_num_samples / target_len, calculated in f64, and the anti-aliasing
+    /// filter is used when that's greater than 1.0, as in resample_all
+    pub fn resample_to_length(
+        &self,
+        channel_id: TChannelId,
+        target_len: usize,
+    ) -> Result<Vec<f32>, TError> {
+        if target_len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let step = self.channel_num_samples(channel_id) as f64 / target_len as f64;
+        self.resample_indexes(
+            channel_id,
+            target_len,
+            step as f32,
+            |output_index| ((output_index as f64) * step + (self.index_offset as f64)) as f32,
+            |_| {},
+        )
+    }
+
+    /// Resamples the entire signal, (see resample_all,) and requantizes the result to an integer sample type,
+    /// such as i16 or I24, rounding to the nearest integer. Samples that overshoot the type's range saturate
+    pub fn resample_all_fixed_point<TSample: FixedPointSample>(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+    ) -> Result<Vec<TSample>, TError> {
+        Ok(self
+            .resample_all(channel_id, ratio)?
+            .into_iter()
+            .map(TSample::from_f32)
+            .collect())
+    }
+
+    /// Gets count samples starting at start, spaced step samples apart. (See get_interpolated_sample for
+    /// relative_speed.) The results are the same as calling get_interpolated_sample for each index, but each
+    /// forward transform is reused for all of the consecutive indexes that fall within it. The indexes are
+    /// calculated in f64, so rounding errors don't build up over long ranges
+    pub fn get_interpolated_range(
+        &self,
+        channel_id: TChannelId,
+        start: f32,
+        step: f32,
+        count: usize,
+        relative_speed: f32,
+    ) -> Result<Vec<f32>, TError> {
+        self.resample_indexes(
+            channel_id,
+            count,
+            relative_speed,
+            |output_index| {
+                ((output_index as f64) * (step as f64)
+                    + (start as f64)
+                    + (self.index_offset as f64)) as f32
+            },
+            |_| {},
+        )
+    }
+
+    // Interpolates count samples, where output sample n is at index(n), which already includes index_offset,
+    // and calls progress as resample_all_with_progress documents
+    fn resample_indexes(
+        &self,
+        channel_id: TChannelId,
+        count: usize,
+        relative_speed: f32,
+        index: impl Fn(usize) -> f32,
+        mut progress: impl FnMut(f32),
+    ) -> Result<Vec<f32>, TError> {
+        let mut output = Vec::with_capacity(count);
+
+        if self.interpolation_mode != InterpolationMode::BandLimited
+            || self.uses_aliasing_filter(relative_speed)
+        {
+            for output_index in 0..count {
+                report_progress(&mut progress, output_index, count);
+                output.push(self.interpolate(channel_id, index(output_index), relative_speed)?);
+            }
+
+            self.apply_tail_fade_at(channel_id, &mut output, &index);
+            self.limit_all(&mut output);
+            progress(1.0);
+            return Ok(output);
+        }
+
+        // Each forward transform is reused for all of the output samples that fall within it, without going
+        // through the transform cache
+        let mut forward_transform: Option<(isize, Vec<Complex32>)> = None;
+        let mut transform = Vec::with_capacity(self.window_size);
+        for output_index in 0..count {
+            report_progress(&mut progress, output_index, count);
+
+            let index = index(output_index);
+            let index_floor = index.floor();
+            let index_floor_isize = index_floor as isize;
+
+            if index == index_floor && !self.remove_dc {
+                output.push(self.get_boundary_sample(channel_id, index_floor_isize)?);
+                continue;
+            }
+
+            let forward_transform = match forward_transform {
+                Some((forward_index, ref forward_transform))
+                    if forward_index == index_floor_isize =>
+                {
+                    forward_transform
+                }
+                _ => {
+                    let new_forward_transform =
+                        self.read_window_transform(channel_id, index_floor_isize)?;
+                    &forward_transform
+                        .insert((index_floor_isize, new_forward_transform))
+                        .1
+                }
+            };
+
+            transform.clear();
+            transform.extend_from_slice(forward_transform);
+            output.push(self.reconstruct_sample(&mut transform, index - index_floor));
+        }
+
+        self.apply_tail_fade_at(channel_id, &mut output, &index);
+        self.limit_all(&mut output);
+        progress(1.0);
+        Ok(output)
+    }
+
+    /// Decimates the signal by an integer factor, returning every factor-th sample after removing the
+    /// frequencies above the new Nyquist frequency. Unlike resample_all, which averages oversampled points,
+    /// this zeroes each window's frequencies above window_size / (2 * factor) before transforming it back,
+    /// so the cutoff is as sharp as the window's frequency resolution. Returns floor(num_samples / factor)
+    /// samples
+    pub fn downsample_integer(
+        &self,
+        channel_id: TChannelId,
+        factor: usize,
+    ) -> Result<Vec<f32>, TError> {
+        assert!(factor > 0, "factor must be greater than 0");
+
+        let cutoff_freq_index = self.window_size / (2 * factor);
+        let output_len = self.channel_num_samples(channel_id) / factor;
+        let mut output = Vec::with_capacity(output_len);
+
+        // As in upsample_integer, the EvaluationPoint's offset is split into whole samples, which move the
+        // window, and a fraction, which shifts each transform
+        let offset_floor = self.index_offset.floor();
+        let offset_fract = self.index_offset - offset_floor;
+
+        // Every output sample is at the same fraction, so the low-passed window's gain is the same for all of them
+        let window_gain = self.window_gain_below(offset_fract, cutoff_freq_index);
+        for output_index in 0..output_len {
+            let mut transform = self.read_window_transform(
+                channel_id,
+                (output_index * factor) as isize + offset_floor as isize,
+            )?;
+            if self.remove_dc {
+                transform[0] = Complex32::new(0.0, 0.0);
+            }
+
+            let sample = if offset_fract == 0.0 {
+                self.low_pass_center_sample(&mut transform, cutoff_freq_index)
+            } else {
+                self.low_pass(&mut transform, cutoff_freq_index);
+                self.shift_and_inverse(&mut transform, offset_fract)
+            };
+            output.push(sample / window_gain);
+        }
+
+        self.apply_tail_fade(channel_id, &mut output, factor as f32);
+        self.limit_all(&mut output);
+        Ok(output)
+    }
+
+    /// Resamples the entire signal, (see resample_all,) low-passing and shifting each window in a single pass
+    /// when ratio is greater than 1.0: The frequencies of each window's forward transform above
+    /// window_size / (2 * ratio) are zeroed, and the transform is shifted to the output sample's position and
+    /// transformed back. This is one forward and one inverse transform per output sample, instead of the
+    /// anti-aliasing filter's transform for each oversampled point, and the cutoff is as sharp as the window's
+    /// frequency resolution, like downsample_integer. Ratios of 1.0 or less don't need a filter, and return the
+    /// same samples as resample_all. This is always a band-limited reconstruction, regardless of the
+    /// InterpolationMode
+    pub fn resample_fused(&self, channel_id: TChannelId, ratio: f32) -> Result<Vec<f32>, TError> {
+        if ratio <= 1.0 {
+            return self.resample_all(channel_id, ratio);
+        }
+
+        let cutoff_freq_index = (self.window_size as f32 / (2.0 * ratio)) as usize;
+        let output_len = self.channel_output_len(channel_id, ratio);
+        let mut output = Vec::with_capacity(output_len);
+
+        // Consecutive output samples in the same window reuse its forward transform
+        let mut forward_transform: Option<(isize, Vec<Complex32>)> = None;
+        let mut transform = Vec::with_capacity(self.window_size);
+        for output_index in 0..output_len {
+            let index = self.resample_all_index(output_index, ratio);
+            let index_floor = index.floor();
+            let index_floor_isize = index_floor as isize;
+            let fract = index - index_floor;
+
+            let forward_transform = match forward_transform {
+                Some((forward_index, ref forward_transform))
+                    if forward_index == index_floor_isize =>
+                {
+                    forward_transform
+                }
+                _ => {
+                    let mut new_forward_transform =
+                        self.read_window_transform(channel_id, index_floor_isize)?;
+                    if self.remove_dc {
+                        new_forward_transform[0] = Complex32::new(0.0, 0.0);
+                    }
+
+                    self.low_pass(&mut new_forward_transform, cutoff_freq_index);
+                    &forward_transform
+                        .insert((index_floor_isize, new_forward_transform))
+                        .1
+                }
+            };
+
+            transform.clear();
+            transform.extend_from_slice(forward_transform);
+            let sample = self.shift_and_inverse(&mut transform, fract);
+            output.push(sample / self.window_gain_below(fract, cutoff_freq_index));
+        }
+
+        self.apply_tail_fade(channel_id, &mut output, ratio);
+        self.limit_all(&mut output);
+        Ok(output)
+    }
+
+    /// Upsamples the signal by an integer factor, returning num_samples * factor samples, where output sample n
+    /// is the signal at n / factor. Each input sample's window is transformed once, zero-padded to
+    /// window_size * factor frequencies, and transformed back, so the factor output samples around it come from
+    /// a single inverse transform. Zero-padding the spectrum adds no frequencies above the original Nyquist
+    /// frequency, so there are no images like there are after zero-stuffing. This is always a band-limited
+    /// reconstruction, regardless of the InterpolationMode
+    pub fn upsample_integer(
+        &self,
+        channel_id: TChannelId,
+        factor: usize,
+    ) -> Result<Vec<f32>, TError> {
+        assert!(factor > 0, "factor must be greater than 0");
+
+        // The EvaluationPoint's offset is split into whole samples, which move the window, and a fraction, which
+        // shifts each transform
+        let offset_floor = self.index_offset.floor();
+        let offset_fract = self.index_offset - offset_floor;
+
+        let window_gains = self.window_transform.as_ref().map(|window_transform| {
+            let mut window_transform = window_transform.to_vec();
+            self.shift_transform(&mut window_transform, offset_fract);
+            self.upsample_center(&window_transform, factor)
+        });
+
+        let num_samples = self.channel_num_samples(channel_id);
+        let mut output = Vec::with_capacity(num_samples * factor);
+        for index in 0..num_samples {
+            let mut transform =
+                self.read_window_transform(channel_id, index as isize + offset_floor as isize)?;
+            if self.remove_dc {
+                transform[0] = Complex32::new(0.0, 0.0);
+            }
+
+            if offset_fract != 0.0 || self.window_offset != 0 {
+                self.shift_transform(&mut transform, offset_fract);
+            }
+
+            let samples = self.upsample_center(&transform, factor);
+            match &window_gains {
+                Some(window_gains) => output.extend(
+                    samples
+                        .iter()
+                        .zip(window_gains.iter())
+                        .map(|(sample, window_gain)| sample / window_gain),
+                ),
+                None => output.extend(samples),
+            }
+        }
+
+        self.apply_tail_fade(channel_id, &mut output, 1.0 / factor as f32);
+        self.limit_all(&mut output);
+        Ok(output)
+    }
+
+    // Zero-pads transform, (of window_size frequencies,) to window_size * factor frequencies, transforms it
+    // back, and returns the factor samples that start at the center of the window
+    fn upsample_center(&self, transform: &[Complex32], factor: usize) -> Vec<f32> {
+        let half_window_size = self.window_size / 2;
+        let padded_size = self.window_size * factor;
+        let mut padded = vec![Complex32::new(0.0, 0.0); padded_size];
+
+        padded[0] = transform[0];
+        for freq_index in 1..=((self.window_size - 1) / 2) {
+            padded[freq_index] = transform[freq_index];
+            padded[padded_size - freq_index] = transform[self.window_size - freq_index];
+        }
+
+        // Nyquist is split between the positive and negative frequencies, (which are the same bin when factor
+        // is 1)
+        if self.window_size.is_multiple_of(2) {
+            let nyquist = transform[half_window_size] / 2.0;
+            padded[half_window_size] += nyquist;
+            padded[padded_size - half_window_size] += nyquist;
+        }
+
+        // A window_size transform of a DC signal of 1.0 transforms back to inverse_scale at any padded size
+        let inverse_scale = self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse_scale()
+        });
+        self.with_fft_cache_entry(padded_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(&mut padded)
+        });
+
+        let center = half_window_size * factor;
+        padded[center..(center + factor)]
+            .iter()
+            .map(|sample| sample.re / inverse_scale)
+            .collect()
+    }
+
+    /// The number of samples that resample_all returns for ratio: floor(num_samples / ratio). The division is
+    /// in f64, so a ratio of 1.0 returns exactly num_samples. Channels with their own num_samples return
+    /// floor(channel_num_samples / ratio)
+    pub fn output_len(&self, ratio: f32) -> usize {
+        assert!(ratio > 0.0, "ratio must be greater than 0");
+
+        output_len(self.num_samples, ratio)
+    }
+
+    // output_len for channel_id's num_samples
+    fn channel_output_len(&self, channel_id: TChannelId, ratio: f32) -> usize {
+        assert!(ratio > 0.0, "ratio must be greater than 0");
+
+        output_len(self.channel_num_samples(channel_id), ratio)
+    }
+
+    // The gain of the fade out over the last window_size samples, (see InterpolatorBuilder::fade_out_tail,)
+    // or 1.0 when the tail isn't faded
+    fn tail_fade_gain(&self, channel_id: TChannelId, index: f32) -> f32 {
+        if !self.fade_out_tail {
+            return 1.0;
+        }
+
+        let num_samples = self.channel_num_samples(channel_id) as f32;
+        let fade_start = num_samples - self.window_size as f32;
+        if index <= fade_start {
+            1.0
+        } else if index >= num_samples {
+            0.0
+        } else {
+            let fade_position = (index - fade_start) / self.window_size as f32;
+            0.5 + 0.5 * (PI * fade_position).cos()
+        }
+    }
+
+    fn apply_tail_fade(&self, channel_id: TChannelId, output: &mut [f32], ratio: f32) {
+        self.apply_tail_fade_at(channel_id, output, |output_index| {
+            self.resample_all_index(output_index, ratio)
+        });
+    }
+
+    // Fades the tail of output, where output sample n is at index(n)
+    fn apply_tail_fade_at(
+        &self,
+        channel_id: TChannelId,
+        output: &mut [f32],
+        index: impl Fn(usize) -> f32,
+    ) {
+        if !self.fade_out_tail {
+            return;
+        }
+
+        for (output_index, sample) in output.iter_mut().enumerate() {
+            *sample *= self.tail_fade_gain(channel_id, index(output_index));
+        }
+    }
+
+    // Records how far sample is outside of the clamp range, (see max_overshoot,) and clamps it if clamping is
+    // enabled
+    fn limit(&self, sample: f32) -> f32 {
+        let (min, max) = self.clamp.unwrap_or((-1.0, 1.0));
+        let overshoot = (sample - max).max(min - sample);
+        if overshoot > self.max_overshoot.get() {
+            self.max_overshoot.set(overshoot);
+        }
+
+        match self.clamp {
+            Some((min, max)) => sample.clamp(min, max),
+            None => sample,
+        }
+    }
+
+    fn limit_all(&self, output: &mut [f32]) {
+        for sample in output.iter_mut() {
+            *sample = self.limit(*sample);
+        }
+    }
+
+    // When every index that resample_all reads at ratio is a whole sample, and nothing changes the samples
+    // there, (IE, the ratio is an integer that the anti-aliasing filter doesn't apply to, and the
+    // EvaluationPoint's offset is a whole number of samples,) the number of samples between them
+    fn whole_sample_step(&self, ratio: f32) -> Option<usize> {
+        if ratio >= 1.0
+            && ratio.fract() == 0.0
+            && self.index_offset.fract() == 0.0
+            && !self.uses_aliasing_filter(ratio)
+            && !self.remove_dc
+        {
+            Some(ratio as usize)
+        } else {
+            None
+        }
+    }
+
+    // resample_all for a ratio that whole_sample_step returns step for: The samples are read without
+    // interpolating. (See resample_all_with_progress for progress)
+    fn read_whole_samples(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+        step: usize,
+        mut progress: impl FnMut(f32),
+    ) -> Result<Vec<f32>, TError> {
+        let count = self.channel_output_len(channel_id, ratio);
+        let start = self.index_offset as isize;
+        let num_samples = self.channel_num_samples(channel_id);
+        let mut output = Vec::with_capacity(count);
+        for output_index in 0..count {
+            report_progress(&mut progress, output_index, count);
+            output.push(self.get_boundary_sample_with_len(
+                channel_id,
+                num_samples,
+                start + (output_index * step) as isize,
+            )?);
+        }
+
+        self.apply_tail_fade(channel_id, &mut output, ratio);
+        self.limit_all(&mut output);
+        progress(1.0);
+        Ok(output)
+    }
+
+    fn resample_all_index(&self, output_index: usize, ratio: f32) -> f32 {
+        ((output_index as f64) * (ratio as f64) + (self.index_offset as f64)) as f32
+    }
+
+    /// Reads count samples starting at start_index, where the playback speed changes for each output sample.
+    /// speed_fn is called with the output index and returns the relative_speed, which is both how far the
+    /// index advances and whether the anti-aliasing filter is used for that sample
+    pub fn resample_with_speed_fn(
+        &self,
+        channel_id: TChannelId,
+        start_index: f32,
+        count: usize,
+        mut speed_fn: impl FnMut(usize) -> f32,
+    ) -> Result<Vec<f32>, TError> {
+        let mut output = Vec::with_capacity(count);
+
+        // The index is accumulated in f64 so that rounding errors don't build up over long buffers
+        let mut index = start_index as f64;
+        for output_index in 0..count {
+            let relative_speed = speed_fn(output_index);
+            output.push(self.get_interpolated_sample(channel_id, index as f32, relative_speed)?);
+            index += relative_speed as f64;
+        }
+
+        Ok(output)
+    }
+
+    /// Resamples the entire signal, (see resample_all,) and then scales the result so that its peak absolute
+    /// value is target_peak. The scaled samples are clamped again, (see InterpolatorBuilder::clamp,) so a
+    /// target_peak outside of the clamp range is clamped. Silence is returned unscaled
+    pub fn resample_all_normalized(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+        target_peak: f32,
+    ) -> Result<Vec<f32>, TError> {
+        let mut output = self.resample_all(channel_id, ratio)?;
+
+        // The peak is measured on the output, because interpolation can overshoot the input's peak
+        let peak = output
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak > 0.0 {
+            let gain = target_peak / peak;
+            for sample in output.iter_mut() {
+                *sample *= gain;
+            }
+
+            self.limit_all(&mut output);
+        }
+
+        Ok(output)
+    }
+
+    /// Resamples the entire signal, (see resample_all,) and then scales the result so that its RMS matches the
+    /// RMS of the channel's input samples, so that the signal's power isn't changed by the window, the
+    /// anti-aliasing filter, or the tail fade. This is for analysis that compares energy across sample rates,
+    /// not for mastering: Unlike resample_all_normalized, which sets the peak, this can push peaks past 1.0, and
+    /// when downsampling it makes up for the energy that the anti-aliasing filter removed above the new Nyquist
+    /// frequency. The scaled samples are clamped again, (see InterpolatorBuilder::clamp,) so a clamped output's
+    /// RMS falls short of the input's by what's clamped. Silence is returned unscaled
+    pub fn resample_all_energy_preserving(
+        &self,
+        channel_id: TChannelId,
+        ratio: f32,
+    ) -> Result<Vec<f32>, TError> {
+        let mut output = self.resample_all(channel_id, ratio)?;
+
+        let num_samples = self.channel_num_samples(channel_id);
+        let mut input_energy = 0.0f64;
+        for index in 0..num_samples {
+            let sample = self.get_sample_or_zero(channel_id, index)? as f64;
+            input_energy += sample * sample;
+        }
+
+        let output_energy: f64 = output.iter().map(|sample| (*sample as f64).powi(2)).sum();
+        if input_energy > 0.0 && output_energy > 0.0 {
+            let input_rms = (input_energy / num_samples as f64).sqrt();
+            let output_rms = (output_energy / output.len() as f64).sqrt();
+            let gain = (input_rms / output_rms) as f32;
+            for sample in output.iter_mut() {
+                *sample *= gain;
+            }
+
+            self.limit_all(&mut output);
+        }
+
+        Ok(output)
+    }
+
+    /// The index of the first NaN or infinite sample that the SampleProvider returns for channel_id within the
+    /// len samples starting at start, or None if they're all finite. Reading stops at the end of the signal.
+    /// This reads the samples as they are, regardless of InterpolatorBuilder::non_finite_samples
+    pub fn find_non_finite(
+        &self,
+        channel_id: TChannelId,
+        start: usize,
+        len: usize,
+    ) -> Result<Option<usize>, TError> {
+        let end = (start + len).min(self.channel_num_samples(channel_id));
+        if start >= end {
+            return Ok(None);
+        }
+
+        self.sample_provider
+            .prefetch(channel_id, start, end - start)?;
+        for index in start..end {
+            match self
+                .sample_provider
+                .get_sample_if_available(channel_id, index)?
+            {
+                Some(sample) if !sample.is_finite() => return Ok(Some(index)),
+                Some(_) => {}
+                None => break,
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The envelope of the signal at index, for metering and dynamics: The RMS or peak of the window samples
+    /// from floor(index) - window / 2 through floor(index) - window / 2 + window - 1, read as windows for
+    /// interpolation are, (IE, outside of the signal according to the BoundaryMode,) without transforming them.
+    /// Between whole-sample indexes, the envelope is linearly interpolated between the windows at floor(index)
+    /// and floor(index) + 1, (for Rms, the mean squares are interpolated,) so it's continuous in index
+    pub fn get_envelope(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        window: usize,
+        mode: EnvelopeMode,
+    ) -> Result<f32, TError> {
+        assert!(window > 0, "window must be greater than 0");
+
+        let index_floor = index.floor();
+        let fract = index - index_floor;
+        let start = index_floor as isize - (window / 2) as isize;
+
+        // The window at floor(index) + 1 is the same samples, moved by one
+        let len = if fract == 0.0 { window } else { window + 1 };
+        self.prefetch_range(channel_id, start, len)?;
+        let num_samples = self.channel_num_samples(channel_id);
+        let samples = (start..(start + len as isize))
+            .map(|sample_index| {
+                self.get_boundary_sample_with_len(channel_id, num_samples, sample_index)
+            })
+            .collect::<Result<Vec<f32>, TError>>()?;
+
+        let measure = |samples: &[f32]| match mode {
+            EnvelopeMode::Rms => {
+                samples.iter().map(|sample| sample * sample).sum::<f32>() / window as f32
+            }
+            EnvelopeMode::Peak => samples
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs())),
+        };
+
+        let mut envelope = measure(&samples[..window]);
+        if fract != 0.0 {
+            envelope += (measure(&samples[1..]) - envelope) * fract;
+        }
+
+        Ok(match mode {
+            EnvelopeMode::Rms => envelope.sqrt(),
+            EnvelopeMode::Peak => envelope,
+        })
+    }
+
+    /// The largest absolute value of the samples in the signal, up to num_samples or the end of the signal
+    pub fn peak(&self, channel_id: TChannelId) -> Result<f32, TError> {
+        let mut peak = 0.0f32;
+        for index in 0..self.channel_num_samples(channel_id) {
+            match self
+                .sample_provider
+                .get_sample_if_available(channel_id, index)?
+            {
+                Some(sample) => peak = peak.max(sample.abs()),
+                None => break,
+            }
+        }
+
+        Ok(peak)
+    }
+
+    /// The largest absolute value of the signal, including points_per_sample - 1 interpolated points between
+    /// each sample. Interpolation can overshoot the peak of the samples themselves
+    pub fn interpolated_peak(
+        &self,
+        channel_id: TChannelId,
+        points_per_sample: usize,
+    ) -> Result<f32, TError> {
+        assert!(
+            points_per_sample > 0,
+            "points_per_sample must be greater than 0"
+        );
+
+        let interpolated = self.resample_all(channel_id, 1.0 / (points_per_sample as f32))?;
+        Ok(interpolated
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs())))
+    }
+
+    // Interpolates at index, which already includes index_offset, without the tail fade or clamping
+    fn interpolate(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        match self.interpolation_mode {
+            InterpolationMode::BandLimited => {
+                if !self.uses_aliasing_filter(relative_speed) {
+                    self.get_interpolated_sample_no_aliasing_filter(channel_id, index)
+                } else {
+                    self.get_interpolated_sample_with_aliasing_filter(
+                        channel_id,
+                        index,
+                        relative_speed,
+                    )
+                }
+            }
+            _ => {
+                let num_samples = self.channel_num_samples(channel_id);
+                self.interpolate_time_domain(index, |sample_index| {
+                    self.get_boundary_sample_with_len(channel_id, num_samples, sample_index)
+                })
+            }
+        }
+    }
+
+    // Interpolates with the InterpolationModes that read samples directly instead of transforming the window
+    fn interpolate_time_domain(
+        &self,
+        index: f32,
+        read_sample: impl Fn(isize) -> Result<f32, TError>,
+    ) -> Result<f32, TError> {
+        match self.interpolation_mode {
+            InterpolationMode::BandLimited => {
+                unreachable!("BandLimited interpolation transforms the window")
+            }
+            InterpolationMode::CatmullRom => catmull_rom(index, read_sample),
+            InterpolationMode::Nearest => read_sample(index.round() as isize),
+            InterpolationMode::Hold => read_sample(index.floor() as isize),
+            InterpolationMode::WindowedSinc { taps, beta } => {
+                windowed_sinc(index, taps, beta, read_sample)
+            }
+        }
+    }
+
+    fn get_interpolated_sample_no_aliasing_filter(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+    ) -> Result<f32, TError> {
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+
+        if index == index_floor && !self.remove_dc {
+            return self.get_boundary_sample(channel_id, index_floor_isize);
+        }
+
+        let fract = index - index_floor;
+        if let Some(sample) = self.reconstruct_cached_sample(channel_id, index_floor_isize, fract) {
+            return Ok(sample);
+        }
+
+        self.compute_transform_with(channel_id, index_floor_isize, |transform| {
+            self.reconstruct_sample_from(transform, fract)
+        })
+    }
+
+    // reconstruct_sample_from the channel's cached transform, so that it isn't cloned, or None when the window
+    // centered on index_floor_isize isn't cached
+    fn reconstruct_cached_sample(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+        fract: f32,
+    ) -> Option<f32> {
+        let generation = self.cache_generation();
+        match self.transform_cache.borrow().get(&channel_id) {
+            Some(cache_entry)
+                if cache_entry.index == index_floor_isize
+                    && cache_entry.generation == generation =>
+            {
+                Some(self.reconstruct_sample_from(&cache_entry.transform, fract))
+            }
+            _ => None,
+        }
+    }
+
+    // Gets the forward transform of the window centered on index_floor_isize, from the cache if possible
+    fn get_transform(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Result<Vec<Complex32>, TError> {
+        // Check cache first. The cache isn't borrowed while the transform is computed, because that calls into
+        // the SampleProvider
+        match self.cached_transform(channel_id, index_floor_isize) {
+            Some(transform) => Ok(transform),
+            None => self.compute_transform(channel_id, index_floor_isize),
+        }
+    }
+
+    // get_transform, but a window that isn't cached is read without replacing the channel's cached transform
+    fn peek_transform(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Result<Vec<Complex32>, TError> {
+        match self.cached_transform(channel_id, index_floor_isize) {
+            Some(transform) => Ok(transform),
+            None => self.read_window_transform(channel_id, index_floor_isize),
+        }
+    }
+
+    fn cached_transform(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Option<Vec<Complex32>> {
+        let generation = self.cache_generation();
+        match self.transform_cache.borrow().get(&channel_id) {
+            Some(cache_entry)
+                if cache_entry.index == index_floor_isize
+                    && cache_entry.generation == generation =>
+            {
+                Some(cache_entry.transform.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn get_interpolated_sample_with_aliasing_filter(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<f32, TError> {
+        self.average_oversampled(index, relative_speed, |oversample_index| {
+            self.get_interpolated_sample_no_aliasing_filter(channel_id, oversample_index)
+        })
+    }
+
+    // The anti-aliasing filter: Oversamples the span of relative_speed samples centered on index, such that
+    // the oversampled points are no further than one sample apart, and then averages them. interpolate reads
+    // each point without filtering.
+    //
+    // Doubling the number of points at each power of two would make the output jump when relative_speed
+    // crosses one, so the average is blended with the average of half as many points, (which are further
+    // apart,) in proportion to how far relative_speed is from the next power of two. The output is continuous
+    // in relative_speed, including at 1.0, where the "average" of one point is the unfiltered sample
+    fn average_oversampled(
+        &self,
+        index: f32,
+        relative_speed: f32,
+        mut interpolate: impl FnMut(f32) -> Result<f32, TError>,
+    ) -> Result<f32, TError> {
+        let sample =
+            self.average_oversampled_complex(index, relative_speed, |oversample_index| {
+                Ok(Complex32::new(interpolate(oversample_index)?, 0.0))
+            })?;
+
+        Ok(sample.re)
+    }
+
+    fn average_oversampled_complex(
+        &self,
+        index: f32,
+        relative_speed: f32,
+        mut interpolate: impl FnMut(f32) -> Result<Complex32, TError>,
+    ) -> Result<Complex32, TError> {
+        if let Some(aliasing_filter_kernel) = &self.aliasing_filter_kernel {
+            return kernel_indexes(index, aliasing_filter_kernel.len())
+                .zip(aliasing_filter_kernel.iter())
+                .try_fold(Complex32::new(0.0, 0.0), |sum, (kernel_index, tap)| {
+                    Ok(sum + interpolate(kernel_index)? * *tap)
+                });
+        }
+
+        let (lower_ratio, upper_ratio, upper_weight) = self.oversampling(relative_speed);
+
+        let upper = self.average_points(index, relative_speed, upper_ratio, &mut interpolate)?;
+        if !blends_lower_average(lower_ratio, upper_ratio, upper_weight) {
+            return Ok(upper);
+        }
+
+        let lower = self.average_points(index, relative_speed, lower_ratio, &mut interpolate)?;
+        Ok(lower + (upper - lower) * upper_weight)
+    }
+
+    // Averages oversampling_ratio evenly-spaced points across the span of relative_speed samples centered on
+    // index
+    fn average_points(
+        &self,
+        index: f32,
+        relative_speed: f32,
+        oversampling_ratio: usize,
+        interpolate: &mut impl FnMut(f32) -> Result<Complex32, TError>,
+    ) -> Result<Complex32, TError> {
+        // The buffer is taken out of the pool instead of borrowed, so that interpolate can read through this
+        // interpolator, (IE, for nested anti-aliasing filters,) without overlapping borrows. A buffer isn't
+        // returned if interpolate fails
+        let mut transform = self.scratch_buffers.borrow_mut().pop().unwrap_or_default();
+        transform.clear();
+        for oversample_index in oversample_indexes(index, relative_speed, oversampling_ratio) {
+            transform.push(interpolate(oversample_index)?);
+        }
+
+        // The DC bin of the oversampled points is their average
+        let average = self.with_fft_cache_entry(oversampling_ratio, |fft_cache_entry| {
+            fft_cache_entry.forward(&mut transform);
+            transform[0] / fft_cache_entry.forward_scale()
+        });
+
+        self.scratch_buffers.borrow_mut().push(transform);
+        Ok(average)
+    }
+
+    // The two numbers of points that the anti-aliasing filter averages, and the weight of the average of the
+    // larger number. (See average_oversampled.) The larger number is the smallest power of two that keeps the
+    // points no further than one sample apart, and neither is less than min_oversampling_ratio or more than
+    // max_oversampling_ratio. The doubling stops at max_oversampling_ratio, so it ends even for an infinite speed
+    fn oversampling(&self, relative_speed: f32) -> (usize, usize, f32) {
+        let mut oversampling_ratio = 1;
+        let mut oversample_rate = relative_speed;
+        while oversample_rate > 1.0 && oversampling_ratio < self.max_oversampling_ratio {
+            oversampling_ratio *= 2;
+            oversample_rate /= 2.0;
+        }
+
+        let half_ratio = (oversampling_ratio / 2).max(1);
+        let upper_weight = (relative_speed / half_ratio as f32 - 1.0).clamp(0.0, 1.0);
+
+        (
+            half_ratio
+                .max(self.min_oversampling_ratio)
+                .min(self.max_oversampling_ratio),
+            oversampling_ratio
+                .max(self.min_oversampling_ratio)
+                .min(self.max_oversampling_ratio),
+            upper_weight,
+        )
+    }
+
+    // Reads the sample at index, or the sample that BoundaryMode substitutes when index is outside the signal.
+    // Samples after the end of a signal of unknown length are 0.0 with either BoundaryMode
+    fn get_boundary_sample(&self, channel_id: TChannelId, index: isize) -> Result<f32, TError> {
+        self.get_boundary_sample_with_len(channel_id, self.channel_num_samples(channel_id), index)
+    }
+
+    // get_boundary_sample, where num_samples is the channel's length, so that callers that read a whole window
+    // look it up once instead of for every sample
+    fn get_boundary_sample_with_len(
+        &self,
+        channel_id: TChannelId,
+        num_samples: usize,
+        index: isize,
+    ) -> Result<f32, TError> {
+        match self.boundary_index(num_samples, index) {
+            Some(index) => self.get_sample_or_zero(channel_id, index),
+            None => Ok(0.0),
+        }
+    }
+
+    // The index within a signal of num_samples samples that get_boundary_sample reads for index, or None when
+    // the sample is 0.0
+    fn boundary_index(&self, num_samples: usize, index: isize) -> Option<usize> {
+        if index >= 0 && index < num_samples as isize {
+            return Some(index as usize);
+        }
+
+        match self.boundary_mode {
+            BoundaryMode::Zero => None,
+            BoundaryMode::Mirror => {
+                if num_samples == 0 {
+                    return None;
+                }
+
+                // Reflecting across both ends repeats every 2 * (num_samples - 1) samples
+                let last_index = num_samples as isize - 1;
+                let period = (2 * last_index).max(1);
+                let reflected_index = index.rem_euclid(period);
+                let reflected_index = if reflected_index > last_index {
+                    period - reflected_index
+                } else {
+                    reflected_index
+                };
+
+                Some(reflected_index as usize)
+            }
+            BoundaryMode::Clamp => {
+                if num_samples == 0 {
+                    return None;
+                }
+
+                Some(index.clamp(0, num_samples as isize - 1) as usize)
+            }
+        }
+    }
+
+    // Reads the sample at index, or 0.0 after the end of a signal of unknown length. Non-finite samples are
+    // replaced according to non_finite_samples
+    fn get_sample_or_zero(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        let sample = self.read_sample_or_zero(channel_id, index)?;
+        self.replace_non_finite(sample, index, 0.0, f32::is_finite, |previous_index| {
+            self.read_sample_or_zero(channel_id, previous_index)
+        })
+    }
+
+    // Replaces sample, which was read at index, according to non_finite_samples if it isn't finite. zero is 0.0
+    // as a T, and read_sample reads the samples before index that NonFiniteSamples::Previous searches
+    fn replace_non_finite<T: Copy>(
+        &self,
+        sample: T,
+        index: usize,
+        zero: T,
+        is_finite: impl Fn(T) -> bool,
+        read_sample: impl Fn(usize) -> Result<T, TError>,
+    ) -> Result<T, TError> {
+        if is_finite(sample) {
+            return Ok(sample);
+        }
+
+        match self.non_finite_samples {
+            NonFiniteSamples::Propagate => Ok(sample),
+            NonFiniteSamples::Zero => Ok(zero),
+            NonFiniteSamples::Previous => {
+                for previous_index in (index.saturating_sub(self.window_size)..index).rev() {
+                    let previous_sample = read_sample(previous_index)?;
+                    if is_finite(previous_sample) {
+                        return Ok(previous_sample);
+                    }
+                }
+
+                Ok(zero)
+            }
+        }
+    }
+
+    fn read_sample_or_zero(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        let sample = self
+            .sample_provider
+            .get_sample_if_available(channel_id, index)?;
+        match sample {
+            Some(sample) => Ok(sample),
+            None => {
+                self.reached_end.set(true);
+                Ok(0.0)
+            }
+        }
+    }
+
+    // Gets the forward and inverse transforms for the given size, which are planned as they're used. The FFT
+    // cache is borrowed while process runs, so process must not call back into the interpolator
+    fn with_fft_cache_entry<T>(&self, size: usize, process: impl FnOnce(&FFTCacheEntry) -> T) -> T {
+        let mut fft_cache = self.fft_cache.borrow_mut();
+        let fft_cache_entry = fft_cache
+            .entry(size)
+            .or_insert_with(|| self.plan_fft_cache_entry(size));
+
+        process(fft_cache_entry)
+    }
+
+    // The transforms themselves are planned when they're first used
+    fn plan_fft_cache_entry(&self, size: usize) -> FFTCacheEntry {
+        FFTCacheEntry::new(size, Arc::downgrade(&self.planner))
+    }
+
+    // Helper function to compute and cache transform
+    fn compute_transform(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Result<Vec<Complex32>, TError> {
+        self.compute_transform_with(channel_id, index_floor_isize, |transform| {
+            transform.to_vec()
+        })
+    }
+
+    // compute_transform, but process is called with the new transform before it's cached, so that callers that
+    // only read it, (IE, with reconstruct_sample_from,) don't clone it
+    fn compute_transform_with<T>(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+        process: impl FnOnce(&[Complex32]) -> T,
+    ) -> Result<T, TError> {
+        // The generation is read first, so that if the samples change while the window is read, the transform
+        // isn't reused
+        let generation = self.cache_generation();
+        self.reached_end.set(false);
+
+        // The window is read into a scratch buffer, and the transform that it replaces in the cache goes back
+        // into the pool, so that reading a new window doesn't allocate
+        let mut new_transform = self.scratch_buffers.borrow_mut().pop().unwrap_or_default();
+        self.read_window_transform_into(channel_id, index_floor_isize, &mut new_transform)?;
+        let result = process(&new_transform);
+
+        // A window that's padded past the end of a signal of unknown length is read again next time, in case
+        // the signal grew
+        if self.reached_end.get() {
+            self.scratch_buffers.borrow_mut().push(new_transform);
+            return Ok(result);
+        }
+
+        // Store in cache
+        let replaced_entry = self.transform_cache.borrow_mut().insert(
             channel_id,
             TransformCacheEntry {
-                index: index_truncated_isize as usize,
-                transform: new_transform.clone(),
+                index: index_floor_isize,
+                generation,
+                transform: new_transform,
             },
         );
+        if let Some(replaced_entry) = replaced_entry {
+            self.scratch_buffers
+                .borrow_mut()
+                .push(replaced_entry.transform);
+        }
+
+        Ok(result)
+    }
+
+    // Reads the window centered on index_floor_isize and transforms it, without caching
+    fn read_window_transform(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Result<Vec<Complex32>, TError> {
+        let mut transform = Vec::with_capacity(self.window_size);
+        self.read_window_transform_into(channel_id, index_floor_isize, &mut transform)?;
+        Ok(transform)
+    }
+
+    // read_window_transform, but into transform, which is cleared first, so that callers can reuse a buffer
+    fn read_window_transform_into(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+        transform: &mut Vec<Complex32>,
+    ) -> Result<(), TError> {
+        self.prefetch_window(channel_id, index_floor_isize)?;
+        let num_samples = self.channel_num_samples(channel_id);
+        self.transform_window_into(
+            index_floor_isize,
+            |window_sample_index| {
+                self.get_boundary_sample_with_len(channel_id, num_samples, window_sample_index)
+            },
+            transform,
+        )?;
+
+        if let Some(on_transform) = &self.on_transform {
+            let gain = self.spectrum_gain();
+            let mut spectrum = self.scratch_buffers.borrow_mut().pop().unwrap_or_default();
+            spectrum.clear();
+            spectrum.extend(transform.iter().map(|value| value * gain));
+            on_transform(index_floor_isize, &spectrum);
+            self.scratch_buffers.borrow_mut().push(spectrum);
+        }
+
+        Ok(())
+    }
+
+    // What a forward transform of size window_size is multiplied by to scale it according to the Normalization,
+    // for the spectra that get_spectrum and on_transform hand out
+    fn spectrum_gain(&self) -> f32 {
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            self.normalization.forward_gain(self.window_size) / fft_cache_entry.forward_scale()
+        })
+    }
+
+    // The index of the first sample of the window that's read for index_floor_isize. index_floor_isize is at
+    // window_size / 2 + window_offset within the window. When window_size is odd and there's no window_offset,
+    // there are as many samples before it as after it
+    fn window_start(&self, index_floor_isize: isize) -> isize {
+        index_floor_isize - (self.window_size / 2 + self.window_offset) as isize
+    }
+
+    // Prefetches the samples of the window centered on index_floor_isize that are within the signal
+    fn prefetch_window(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+    ) -> Result<(), TError> {
+        self.prefetch_range(
+            channel_id,
+            self.window_start(index_floor_isize),
+            self.window_size,
+        )
+    }
+
+    // Prefetches the samples from start through start + len - 1 that are within the signal
+    fn prefetch_range(
+        &self,
+        channel_id: TChannelId,
+        start: isize,
+        len: usize,
+    ) -> Result<(), TError> {
+        let num_samples = self.channel_num_samples(channel_id) as isize;
+        let end = (start + len as isize).clamp(0, num_samples) as usize;
+        let start = start.clamp(0, num_samples) as usize;
+
+        if start < end {
+            self.sample_provider
+                .prefetch(channel_id, start, end - start)?;
+        }
 
+        Ok(())
+    }
+
+    // Reads the window centered on index_floor_isize with read_sample, applies the window function, and
+    // transforms it
+    fn transform_window(
+        &self,
+        index_floor_isize: isize,
+        read_sample: impl Fn(isize) -> Result<f32, TError>,
+    ) -> Result<Vec<Complex32>, TError> {
+        let mut new_transform = Vec::with_capacity(self.window_size);
+        self.transform_window_into(index_floor_isize, read_sample, &mut new_transform)?;
         Ok(new_transform)
     }
+
+    // transform_window, but into new_transform, which is cleared first
+    fn transform_window_into(
+        &self,
+        index_floor_isize: isize,
+        read_sample: impl Fn(isize) -> Result<f32, TError>,
+        new_transform: &mut Vec<Complex32>,
+    ) -> Result<(), TError> {
+        self.read_window(index_floor_isize, read_sample, new_transform)?;
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.forward(new_transform)
+        });
+
+        Ok(())
+    }
+
+    // Reads the window centered on index_floor_isize with read_sample into window, (which is cleared first,) and
+    // applies the window function
+    fn read_window(
+        &self,
+        index_floor_isize: isize,
+        read_sample: impl Fn(isize) -> Result<f32, TError>,
+        window: &mut Vec<Complex32>,
+    ) -> Result<(), TError> {
+        let window_start = self.window_start(index_floor_isize);
+        window.clear();
+
+        for window_sample_index in window_start..(window_start + self.window_size as isize) {
+            let mut sample = read_sample(window_sample_index)?;
+            if let Some(window_coefficients) = &self.window_coefficients {
+                sample *= window_coefficients[(window_sample_index - window_start) as usize];
+            }
+
+            window.push(Complex32 {
+                re: sample,
+                im: 0.0,
+            });
+        }
+
+        Ok(())
+    }
+
+    // reconstruct_sample, but the transform is copied into a scratch buffer and shifted there, so that the
+    // caller's transform, (IE, a cached transform,) is left as is
+    fn reconstruct_sample_from(&self, transform: &[Complex32], fract: f32) -> f32 {
+        let mut shifted_transform = self.scratch_buffers.borrow_mut().pop().unwrap_or_default();
+        shifted_transform.clear();
+        shifted_transform.extend_from_slice(transform);
+        let sample = self.reconstruct_sample(&mut shifted_transform, fract);
+        self.scratch_buffers.borrow_mut().push(shifted_transform);
+        sample
+    }
+
+    // Shifts the (forward) transform by fract samples and transforms it back, returning the center sample.
+    // The transform is modified in place
+    fn reconstruct_sample(&self, transform: &mut [Complex32], fract: f32) -> f32 {
+        if self.remove_dc {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        // The windowed signal is the signal multiplied by the window, so dividing by the window shifted by the
+        // same fraction undoes the window's gain
+        self.shift_and_inverse(transform, fract) / self.window_gain(fract)
+    }
+
+    // reconstruct_sample, but the center sample is accumulated from the shifted frequencies with center_weights,
+    // (see compute_center_weights,) which is a single pass instead of an inverse FFT
+    fn reconstruct_center(&self, transform: &mut [Complex32], fract: f32) -> f32 {
+        if self.remove_dc {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        self.shift_and_accumulate_center(transform, fract) / self.window_gain(fract)
+    }
+
+    fn shift_and_accumulate_center(&self, transform: &mut [Complex32], fract: f32) -> f32 {
+        self.shift_transform(transform, fract);
+        transform
+            .iter()
+            .zip(self.center_weights.iter())
+            .map(|(value, weight)| (value * weight).re)
+            .sum()
+    }
+
+    // The inverse DFT's weight for each frequency at the center sample, (window_size / 2,) scaled like
+    // inverse_center, so that the center sample is the sum of each frequency multiplied by its weight. That's
+    // divided by the forward transform's scale, because the DFT's sum doesn't include the inverse FFT's own scale
+    fn compute_center_weights(&self) -> Vec<Complex32> {
+        let forward_scale = self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.forward_scale()
+        });
+        let center = (self.window_size / 2) as f64;
+        (0..self.window_size)
+            .map(|freq_index| {
+                let phase = 2.0 * std::f64::consts::PI * freq_index as f64 * center
+                    / self.window_size as f64;
+                Complex32::from_polar(1.0 / forward_scale, phase as f32)
+            })
+            .collect()
+    }
+
+    fn shift_and_inverse(&self, transform: &mut [Complex32], fract: f32) -> f32 {
+        self.shift_transform(transform, fract);
+        self.inverse_center(transform).re
+    }
+
+    // Reconstructs the whole window centered on index_floor_isize, shifted by fract samples, so that sample n
+    // of the frame is at index_floor_isize - window_size / 2 + n + fract. Also returns the window function
+    // shifted the same way, (see reconstruct_sample,) or None for the rectangular window. The frame's edges
+    // wrap around, so callers should taper them
+    pub(crate) fn reconstruct_frame(
+        &self,
+        channel_id: TChannelId,
+        index_floor_isize: isize,
+        fract: f32,
+    ) -> Result<ReconstructedFrame, TError> {
+        let mut transform = self.read_window_transform(channel_id, index_floor_isize)?;
+        if self.remove_dc {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        let frame = self.shift_and_inverse_frame(&mut transform, fract);
+        let window_frame = self.window_transform.as_ref().map(|window_transform| {
+            let mut window_frame_cache = self.window_frame_cache.borrow_mut();
+            match &*window_frame_cache {
+                Some((cached_fract, window_frame)) if *cached_fract == fract.to_bits() => {
+                    window_frame.clone()
+                }
+                _ => {
+                    let mut window_transform = window_transform.to_vec();
+                    let window_frame: Arc<[f32]> = self
+                        .shift_and_inverse_frame(&mut window_transform, fract)
+                        .into();
+                    *window_frame_cache = Some((fract.to_bits(), window_frame.clone()));
+                    window_frame
+                }
+            }
+        });
+
+        Ok((frame, window_frame))
+    }
+
+    fn shift_and_inverse_frame(&self, transform: &mut [Complex32], fract: f32) -> Vec<f32> {
+        self.shift_transform(transform, fract);
+
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(transform);
+            transform
+                .iter()
+                .map(|sample| sample.re / fft_cache_entry.inverse_scale())
+                .collect()
+        })
+    }
+
+    // Rotates each frequency so that the transform is shifted by fract samples
+    fn shift_transform(&self, transform: &mut [Complex32], fract: f32) {
+        self.shift_transform_with_symmetry(transform, fract, Symmetry::Conjugate);
+    }
+
+    fn shift_transform_with_symmetry(
+        &self,
+        transform: &mut [Complex32],
+        fract: f32,
+        symmetry: Symmetry,
+    ) {
+        match self.rotator_cache_resolution {
+            Some(_) => self.with_rotators(fract, |rotators| {
+                apply_rotators(transform, rotators, symmetry);
+            }),
+            None => {
+                for freq_index in 1..=(self.window_size / 2) {
+                    let rotator = self.get_rotator(freq_index, fract);
+                    self.rotate_frequency(transform, freq_index, rotator, symmetry);
+                }
+            }
+        }
+    }
+
+    // Transforms back, and returns the center sample, which is the index after shifting
+    fn inverse_center(&self, transform: &mut [Complex32]) -> Complex32 {
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(transform);
+            transform[self.window_size / 2] / fft_cache_entry.inverse_scale()
+        })
+    }
+
+    // Calls process with the rotators for every bin that shift by fract samples. (See rotators)
+    // The rotators are cached if rotator_cache_resolution is set, in which case the rotator cache is borrowed
+    // while process runs
+    fn with_rotators<T>(&self, fract: f32, process: impl FnOnce(&[Complex32]) -> T) -> T {
+        match self.rotator_cache_resolution {
+            Some(resolution) => self.with_cached_rotators(resolution, fract, |cached_rotators| {
+                process(&cached_rotators.rotators)
+            }),
+            None => process(&self.rotators(fract)),
+        }
+    }
+
+    // Calls process with the cache entry for fract quantized to resolution, which is borrowed while process runs
+    fn with_cached_rotators<T>(
+        &self,
+        resolution: u32,
+        fract: f32,
+        process: impl FnOnce(&CachedRotators) -> T,
+    ) -> T {
+        let quantized_fract = (fract * resolution as f32).round() as u32;
+        let mut rotator_cache = self.rotator_cache.borrow_mut();
+        let cached_rotators = rotator_cache
+            .entry(quantized_fract)
+            .or_insert_with(|| self.cached_rotators(quantized_fract as f32 / resolution as f32));
+
+        process(cached_rotators)
+    }
+
+    fn cached_rotators(&self, fract: f32) -> CachedRotators {
+        let rotators = self.rotators(fract);
+        let window_gain = match &self.window_gain_weights {
+            Some(window_gain_weights) => accumulate_window_gain(window_gain_weights, &rotators),
+            None => 1.0,
+        };
+
+        CachedRotators {
+            rotators,
+            window_gain,
+        }
+    }
+
+    // The window function's gain at fract samples past the index: The window shifted by fract, like the signal,
+    // at the center sample. Reconstructed samples are divided by it to undo the window. This is 1.0 for the
+    // rectangular window, and is cached with the rotators when rotator_cache_resolution is set
+    fn window_gain(&self, fract: f32) -> f32 {
+        match (&self.window_gain_weights, self.rotator_cache_resolution) {
+            (None, _) => 1.0,
+            (Some(_), Some(resolution)) => {
+                self.with_cached_rotators(resolution, fract, |cached_rotators| {
+                    cached_rotators.window_gain
+                })
+            }
+            (Some(_), None) => self.window_gain_below(fract, self.window_size / 2),
+        }
+    }
+
+    // window_gain, with the window's frequencies above cutoff_freq_index zeroed, (see low_pass)
+    fn window_gain_below(&self, fract: f32, cutoff_freq_index: usize) -> f32 {
+        let window_gain_weights = match &self.window_gain_weights {
+            Some(window_gain_weights) => {
+                &window_gain_weights[..=cutoff_freq_index.min(self.window_size / 2)]
+            }
+            None => return 1.0,
+        };
+
+        match self.rotator_cache_resolution {
+            Some(_) => self.with_rotators(fract, |rotators| {
+                accumulate_window_gain(window_gain_weights, rotators)
+            }),
+            None => window_gain_weights
+                .iter()
+                .enumerate()
+                .map(|(freq_index, weight)| (weight * self.get_rotator(freq_index, fract)).re)
+                .sum(),
+        }
+    }
+
+    // The rotators for every bin that shift by fract samples. Each mirrored bin's rotator is the conjugate of its
+    // positive frequency's, so that transforms that aren't conjugate-symmetric are rotated in one contiguous pass.
+    // (See apply_rotators)
+    fn rotators(&self, fract: f32) -> Vec<Complex32> {
+        let half_window_size = self.window_size / 2;
+        (0..self.window_size)
+            .map(|freq_index| {
+                if freq_index <= half_window_size {
+                    self.get_rotator(freq_index, fract)
+                } else {
+                    self.get_rotator(self.window_size - freq_index, fract)
+                        .conj()
+                }
+            })
+            .collect()
+    }
+
+    // Zeroes the frequencies above cutoff_freq_index, (and their mirrors,) transforms back, and returns the
+    // sample at the index, (window_offset samples after the center.) The transform is modified in place
+    fn low_pass_center_sample(&self, transform: &mut [Complex32], cutoff_freq_index: usize) -> f32 {
+        self.low_pass(transform, cutoff_freq_index);
+
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.inverse(transform);
+            transform[self.window_size / 2 + self.window_offset].re
+                / fft_cache_entry.inverse_scale()
+        })
+    }
+
+    // Zeroes the frequencies above cutoff_freq_index and their mirrors
+    fn low_pass(&self, transform: &mut [Complex32], cutoff_freq_index: usize) {
+        for freq_index in (cutoff_freq_index + 1)..=(self.window_size / 2) {
+            transform[freq_index] = Complex32::new(0.0, 0.0);
+            transform[self.window_size - freq_index] = Complex32::new(0.0, 0.0);
+        }
+    }
+
+    // Shifting by fract samples rotates each frequency by its phase shift per sample, scaled by fract. The
+    // window_offset is added, so that the index, which is window_offset samples after the center of the window,
+    // is shifted to the center
+    fn get_rotator(&self, freq_index: usize, fract: f32) -> Complex32 {
+        let phase_shift_for_sample = self.phase_shifts_per_sample[freq_index];
+        let phase_adjustment = phase_shift_for_sample * (fract + self.window_offset as f32);
+        Complex32::from_polar(1.0, phase_adjustment)
+    }
+
+    // Rotates freq_index and its mirrored frequency according to symmetry
+    fn rotate_frequency(
+        &self,
+        transform: &mut [Complex32],
+        freq_index: usize,
+        rotator: Complex32,
+        symmetry: Symmetry,
+    ) {
+        transform[freq_index] *= rotator;
+        let opposite_freq_index = self.window_size - freq_index;
+        if opposite_freq_index != freq_index {
+            match symmetry {
+                Symmetry::Conjugate => {
+                    transform[opposite_freq_index] = transform[freq_index].conj()
+                }
+                Symmetry::Independent => transform[opposite_freq_index] *= rotator.conj(),
+            }
+        }
+    }
+}
+
+// Catmull-Rom interpolation between the samples at floor(index) and floor(index) + 1, using the samples on either
+// side of them as tangents. Whole-sample indexes only read the sample at index
+fn catmull_rom<TError>(
+    index: f32,
+    read_sample: impl Fn(isize) -> Result<f32, TError>,
+) -> Result<f32, TError> {
+    let index_floor = index.floor();
+    let index_floor_isize = index_floor as isize;
+    let t = index - index_floor;
+
+    let p1 = read_sample(index_floor_isize)?;
+    if t == 0.0 {
+        return Ok(p1);
+    }
+
+    let p0 = read_sample(index_floor_isize - 1)?;
+    let p2 = read_sample(index_floor_isize + 1)?;
+    let p3 = read_sample(index_floor_isize + 2)?;
+
+    Ok(0.5
+        * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * (p1 - p2) + p3 - p0) * t * t * t))
+}
+
+// Convolves the taps samples nearest to index with a Kaiser-windowed sinc. The samples start at
+// ceil(index - taps / 2), so the window is centered on index, and no sample is further than taps / 2 from it,
+// where the Kaiser window ends. (With an odd number of taps, that's the same number of samples on either side
+// of the sample nearest to index.) Whole-sample indexes only read the sample at index
+fn windowed_sinc<TError>(
+    index: f32,
+    taps: usize,
+    beta: f32,
+    read_sample: impl Fn(isize) -> Result<f32, TError>,
+) -> Result<f32, TError> {
+    let index_floor = index.floor();
+    let index_floor_isize = index_floor as isize;
+    if index == index_floor {
+        return read_sample(index_floor_isize);
+    }
+
+    let half_width = taps as f32 / 2.0;
+    let first_sample_index = (index - half_width).ceil() as isize;
+    let mut sample = 0.0;
+    let mut kernel_sum = 0.0;
+    for sample_index in first_sample_index..(first_sample_index + taps as isize) {
+        let distance = index - sample_index as f32;
+        let sinc = (PI * distance).sin() / (PI * distance);
+        let weight = sinc * window_function::kaiser(beta, distance / half_width);
+        sample += read_sample(sample_index)? * weight;
+        kernel_sum += weight;
+    }
+
+    // The truncated kernel's taps don't add up to exactly 1.0, so it's normalized to unity gain at DC, IE, a
+    // constant signal is read back unchanged
+    Ok(sample / kernel_sum)
+}
+
+// Multiplies the frequencies of transform by their rotators, (see Interpolator::rotators,) according to
+// symmetry. The frequencies are multiplied in contiguous runs, which the compiler vectorizes, or which are
+// multiplied in SIMD lanes with the simd feature
+fn apply_rotators(transform: &mut [Complex32], rotators: &[Complex32], symmetry: Symmetry) {
+    let window_size = transform.len();
+    match symmetry {
+        Symmetry::Conjugate => {
+            let half_window_size = window_size / 2;
+            complex_multiply::multiply(
+                &mut transform[1..=half_window_size],
+                &rotators[1..=half_window_size],
+            );
+
+            // When window_size is even, Nyquist is its own mirror
+            let num_mirrored = (window_size - 1) / 2;
+            for freq_index in 1..=num_mirrored {
+                transform[window_size - freq_index] = transform[freq_index].conj();
+            }
+        }
+        Symmetry::Independent => complex_multiply::multiply(&mut transform[1..], &rotators[1..]),
+    }
+}
+
+// Whether the anti-aliasing filter blends the average of lower_ratio points into the average of upper_ratio
+// points, (see Interpolator::average_oversampled,) or uses the upper average alone
+fn blends_lower_average(lower_ratio: usize, upper_ratio: usize, upper_weight: f32) -> bool {
+    lower_ratio != upper_ratio && upper_weight < 1.0
+}
+
+// The window's gain after it's shifted by rotators. (See Interpolator::window_gain_weights)
+fn accumulate_window_gain(window_gain_weights: &[Complex32], rotators: &[Complex32]) -> f32 {
+    window_gain_weights
+        .iter()
+        .zip(rotators)
+        .map(|(weight, rotator)| (weight * rotator).re)
+        .sum()
+}
+
+// The correlation of a and b, scaled so that identical signals correlate at 1.0. Silence correlates at 0.0
+fn normalized_correlation(a: &[f32], b: &[f32]) -> f32 {
+    let (product, a_energy, b_energy) =
+        a.iter()
+            .zip(b)
+            .fold((0.0, 0.0, 0.0), |(product, a_energy, b_energy), (a, b)| {
+                (product + a * b, a_energy + a * a, b_energy + b * b)
+            });
+
+    if a_energy == 0.0 || b_energy == 0.0 {
+        0.0
+    } else {
+        product / (a_energy * b_energy).sqrt()
+    }
+}
+
+// A ConfigError with message, unless condition holds
+fn require(condition: bool, message: impl Into<String>) -> Result<(), ConfigError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(ConfigError::new(message))
+    }
+}
+
+// The smallest size, at least window_size, whose only prime factors are 2, 3, 5 and 7
+fn fast_window_size(window_size: usize) -> usize {
+    // 0 is divisible by every factor, so it would never be reduced to 1
+    (window_size.max(1)..)
+        .find(|size| {
+            let mut remainder = *size;
+            for factor in [2, 3, 5, 7] {
+                while remainder % factor == 0 {
+                    remainder /= factor;
+                }
+            }
+
+            remainder == 1
+        })
+        .unwrap_or(window_size)
+}
+
+fn output_len(num_samples: usize, ratio: f32) -> usize {
+    ((num_samples as f64) / (ratio as f64)).floor() as usize
+}
+
+// The oversampling_ratio evenly-spaced points across the span of relative_speed samples centered on index,
+// which the anti-aliasing filter averages
+fn oversample_indexes(
+    index: f32,
+    relative_speed: f32,
+    oversampling_ratio: usize,
+) -> impl Iterator<Item = f32> {
+    let oversample_rate = relative_speed / (oversampling_ratio as f32);
+    let start = index - (relative_speed / 2.0) + (oversample_rate / 2.0);
+    (0..oversampling_ratio)
+        .map(move |oversample_ctr| start + (oversample_ctr as f32 * oversample_rate))
+}
+
+// The points that an aliasing filter kernel with num_taps taps weights, one sample apart and centered on index
+fn kernel_indexes(index: f32, num_taps: usize) -> impl Iterator<Item = f32> {
+    let start = index - (num_taps - 1) as f32 / 2.0;
+    (0..num_taps).map(move |tap| start + tap as f32)
+}
+
+fn report_progress(progress: &mut impl FnMut(f32), output_index: usize, output_len: usize) {
+    if output_index.is_multiple_of(PROGRESS_INTERVAL) {
+        progress(output_index as f32 / output_len as f32);
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> Interpolator<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: ComplexSampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// Gets the complex sample at index, like get_interpolated_sample, for complex signals such as IQ baseband
+    /// data. The window is transformed as complex values, so the positive and negative frequencies are
+    /// shifted independently instead of assuming that they're conjugates; this takes one FFT instead of one
+    /// for each of the real and imaginary parts. Complex transforms aren't cached, the sample isn't clamped,
+    /// and this is always a band-limited reconstruction, regardless of the InterpolationMode
+    pub fn get_interpolated_complex_sample(
+        &self,
+        channel_id: TChannelId,
+        index: f32,
+        relative_speed: f32,
+    ) -> Result<Complex32, TError> {
+        let index = index + self.index_offset;
+        let sample = if !self.uses_aliasing_filter(relative_speed) {
+            self.interpolate_complex(channel_id, index)?
+        } else {
+            self.average_oversampled_complex(index, relative_speed, |oversample_index| {
+                self.interpolate_complex(channel_id, oversample_index)
+            })?
+        };
+
+        Ok(sample * self.tail_fade_gain(channel_id, index))
+    }
+
+    fn interpolate_complex(&self, channel_id: TChannelId, index: f32) -> Result<Complex32, TError> {
+        let index_floor = index.floor();
+        let index_floor_isize = index_floor as isize;
+
+        let num_samples = self.channel_num_samples(channel_id);
+        if index == index_floor && !self.remove_dc {
+            return self.get_complex_boundary_sample(channel_id, num_samples, index_floor_isize);
+        }
+
+        self.prefetch_window(channel_id, index_floor_isize)?;
+        let window_start = self.window_start(index_floor_isize);
+        let mut transform = Vec::with_capacity(self.window_size);
+        for window_sample_index in window_start..(window_start + self.window_size as isize) {
+            let mut sample =
+                self.get_complex_boundary_sample(channel_id, num_samples, window_sample_index)?;
+            if let Some(window_coefficients) = &self.window_coefficients {
+                sample *= window_coefficients[(window_sample_index - window_start) as usize];
+            }
+
+            transform.push(sample);
+        }
+
+        self.with_fft_cache_entry(self.window_size, |fft_cache_entry| {
+            fft_cache_entry.forward(&mut transform)
+        });
+
+        if self.remove_dc {
+            transform[0] = Complex32::new(0.0, 0.0);
+        }
+
+        let fract = index - index_floor;
+        self.shift_transform_with_symmetry(&mut transform, fract, Symmetry::Independent);
+        // The window is real, so its gain is the same as for real signals
+        Ok(self.inverse_center(&mut transform) / self.window_gain(fract))
+    }
+
+    // get_boundary_sample_with_len, for complex signals
+    fn get_complex_boundary_sample(
+        &self,
+        channel_id: TChannelId,
+        num_samples: usize,
+        index: isize,
+    ) -> Result<Complex32, TError> {
+        let index = match self.boundary_index(num_samples, index) {
+            Some(index) => index,
+            None => return Ok(Complex32::new(0.0, 0.0)),
+        };
+
+        let sample = self.sample_provider.get_complex_sample(channel_id, index)?;
+        self.replace_non_finite(
+            sample,
+            index,
+            Complex32::new(0.0, 0.0),
+            Complex32::is_finite,
+            |previous_index| {
+                self.sample_provider
+                    .get_complex_sample(channel_id, previous_index)
+            },
+        )
+    }
+}
+
+/// Clones share the FFT planner, plans and phase shifts with the original, but start with their own empty
+/// transform cache; caches are never shared between clones. This allows cloning an interpolator for each
+/// thread. FFT scratch is per thread, so clones on different threads don't contend for it, but a single
+/// interpolator can't be shared between threads, because its caches aren't thread safe
+impl<TSampleProvider, TChannelId, TError> Clone
+    for Interpolator<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError> + Clone,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    fn clone(&self) -> Self {
+        Interpolator {
+            planner: self.planner.clone(),
+            fft_cache: RefCell::new(self.fft_cache.borrow().clone()),
+            sample_provider: self.sample_provider.clone(),
+            window_size: self.window_size,
+            round_window_to_fast_size: self.round_window_to_fast_size,
+            num_samples: self.num_samples,
+            min_oversampling_ratio: self.min_oversampling_ratio,
+            max_oversampling_ratio: self.max_oversampling_ratio,
+            aliasing_filter_epsilon: self.aliasing_filter_epsilon,
+            rotator_cache_resolution: self.rotator_cache_resolution,
+            remove_dc: self.remove_dc,
+            boundary_mode: self.boundary_mode,
+            non_finite_samples: self.non_finite_samples,
+            normalization: self.normalization,
+            evaluation_point: self.evaluation_point,
+            window_offset: self.window_offset,
+            fade_out_tail: self.fade_out_tail,
+            clamp: self.clamp,
+            interpolation_mode: self.interpolation_mode,
+            max_overshoot: Cell::new(0.0),
+            index_offset: self.index_offset,
+            window_function: self.window_function,
+            window_coefficients: self.window_coefficients.clone(),
+            window_transform: self.window_transform.clone(),
+            window_gain_weights: self.window_gain_weights.clone(),
+            center_weights: self.center_weights.clone(),
+            phase_shifts_per_sample: self.phase_shifts_per_sample.clone(),
+            transform_cache: RefCell::new(HashMap::new()),
+            generation: Cell::new(self.generation.get()),
+            reached_end: Cell::new(false),
+            rotator_cache: RefCell::new(HashMap::new()),
+            window_frame_cache: RefCell::new(None),
+            scratch_buffers: RefCell::new(Vec::new()),
+            aliasing_filter_kernel: self.aliasing_filter_kernel.clone(),
+            on_transform: self.on_transform.clone(),
+            channel_num_samples: self.channel_num_samples.clone(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> fmt::Debug
+    for Interpolator<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    // The caches are inspected with try_borrow, so that formatting never panics while a read is in progress
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cached_transforms = self
+            .transform_cache
+            .try_borrow()
+            .map(|transform_cache| transform_cache.len())
+            .ok();
+        let cached_fft_sizes = self.fft_cache.try_borrow().ok().map(|fft_cache| {
+            let mut sizes: Vec<usize> = fft_cache.keys().copied().collect();
+            sizes.sort_unstable();
+            sizes
+        });
+
+        f.debug_struct("Interpolator")
+            .field("window_size", &self.window_size)
+            .field("round_window_to_fast_size", &self.round_window_to_fast_size)
+            .field("num_samples", &self.num_samples)
+            .field("min_oversampling_ratio", &self.min_oversampling_ratio)
+            .field("max_oversampling_ratio", &self.max_oversampling_ratio)
+            .field("aliasing_filter_epsilon", &self.aliasing_filter_epsilon)
+            .field("rotator_cache_resolution", &self.rotator_cache_resolution)
+            .field("remove_dc", &self.remove_dc)
+            .field("boundary_mode", &self.boundary_mode)
+            .field("non_finite_samples", &self.non_finite_samples)
+            .field("normalization", &self.normalization)
+            .field("evaluation_point", &self.evaluation_point)
+            .field("window_offset", &self.window_offset)
+            .field("fade_out_tail", &self.fade_out_tail)
+            .field("clamp", &self.clamp)
+            .field("interpolation_mode", &self.interpolation_mode)
+            .field("window_function", &self.window_function)
+            .field("aliasing_filter_kernel", &self.aliasing_filter_kernel)
+            .field("on_transform", &self.on_transform.is_some())
+            .field("cached_transforms", &cached_transforms)
+            .field("cached_fft_sizes", &cached_fft_sizes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> fmt::Display
+    for Interpolator<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Interpolator: window_size {}, num_samples {}",
+            self.window_size, self.num_samples
+        )
+    }
 }
@@ -0,0 +1,124 @@
+use std::f32::consts::PI;
+
+use rustfft::num_complex::Complex32;
+
+use super::{Interpolator, SampleProvider};
+
+impl<TSampleProvider, TChannelId, TError> Interpolator<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// Changes the duration of the signal by factor without changing its pitch. (IE, a factor of 2.0 doubles
+    /// the length.) Returns round(num_samples * factor) samples.
+    ///
+    /// This is a phase vocoder: Hann-windowed analysis frames are read every window_size / 4 samples and
+    /// overlap-added every window_size / 4 * factor samples. Each bin's phase is advanced by its measured
+    /// frequency, so that sinusoids stay continuous across frames. The frames are centered on whole samples,
+    /// so the EvaluationPoint and window_offset don't apply
+    pub fn time_stretch(&self, channel_id: TChannelId, factor: f32) -> Result<Vec<f32>, TError> {
+        assert!(factor > 0.0, "factor must be greater than 0");
+
+        let window_size = self.window_size;
+        let half_window_size = (window_size / 2) as isize;
+        let num_bins = window_size / 2 + 1;
+        let analysis_hop = (window_size / 4).max(1);
+
+        let num_samples = self.channel_num_samples(channel_id);
+        let output_len = ((num_samples as f64) * (factor as f64)).round() as usize;
+        let mut output = vec![0.0; output_len];
+        let mut window_sum = vec![0.0; output_len];
+
+        let window: Vec<f32> = (0..window_size)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * (n as f32) / (window_size as f32)).cos())
+            .collect();
+
+        let mut previous_analysis_phases = vec![0.0; num_bins];
+        let mut synthesis_phases = vec![0.0; num_bins];
+        let mut previous_synthesis_position = 0isize;
+        let mut transform = Vec::with_capacity(window_size);
+
+        let mut frame = 0;
+        while frame * analysis_hop < num_samples {
+            let analysis_position = (frame * analysis_hop) as isize;
+            let synthesis_position =
+                ((analysis_position as f64) * (factor as f64)).round() as isize;
+            let synthesis_hop = (synthesis_position - previous_synthesis_position) as f32;
+
+            transform.clear();
+            for (n, window_value) in window.iter().enumerate() {
+                let sample = self.get_boundary_sample_with_len(
+                    channel_id,
+                    num_samples,
+                    analysis_position - half_window_size + n as isize,
+                )?;
+                transform.push(Complex32::new(sample * window_value, 0.0));
+            }
+
+            self.with_fft_cache_entry(window_size, |fft_cache_entry| {
+                fft_cache_entry.forward(&mut transform)
+            });
+
+            for freq_index in 0..num_bins {
+                let (freq_amplitude, analysis_phase) = transform[freq_index].to_polar();
+
+                let synthesis_phase = if frame == 0 {
+                    analysis_phase
+                } else {
+                    // The difference between the measured phase advance and the advance expected for the
+                    // bin's center frequency gives the true frequency of the sinusoid in the bin
+                    let expected_phase_advance =
+                        self.phase_shifts_per_sample[freq_index] * (analysis_hop as f32);
+                    let phase_deviation = wrap_phase(
+                        analysis_phase
+                            - previous_analysis_phases[freq_index]
+                            - expected_phase_advance,
+                    );
+                    let phase_shift_per_sample = self.phase_shifts_per_sample[freq_index]
+                        + phase_deviation / (analysis_hop as f32);
+
+                    synthesis_phases[freq_index] + phase_shift_per_sample * synthesis_hop
+                };
+
+                previous_analysis_phases[freq_index] = analysis_phase;
+                synthesis_phases[freq_index] = synthesis_phase;
+
+                transform[freq_index] = Complex32::from_polar(freq_amplitude, synthesis_phase);
+                let opposite_freq_index = window_size - freq_index;
+                if freq_index != 0 && opposite_freq_index != freq_index {
+                    transform[opposite_freq_index] = transform[freq_index].conj();
+                }
+            }
+
+            let inverse_scale = self.with_fft_cache_entry(window_size, |fft_cache_entry| {
+                fft_cache_entry.inverse(&mut transform);
+                fft_cache_entry.inverse_scale()
+            });
+
+            for n in 0..window_size {
+                let output_index = synthesis_position - half_window_size + n as isize;
+                if output_index >= 0 && (output_index as usize) < output_len {
+                    output[output_index as usize] += transform[n].re / inverse_scale * window[n];
+                    window_sum[output_index as usize] += window[n] * window[n];
+                }
+            }
+
+            previous_synthesis_position = synthesis_position;
+            frame += 1;
+        }
+
+        // Undo the gain of the overlapping analysis and synthesis windows
+        for (sample, window_sum) in output.iter_mut().zip(window_sum) {
+            if window_sum > 1e-3 {
+                *sample /= window_sum;
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+// Wraps phase to -PI..PI
+fn wrap_phase(phase: f32) -> f32 {
+    phase - 2.0 * PI * (phase / (2.0 * PI)).round()
+}
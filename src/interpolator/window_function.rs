@@ -0,0 +1,61 @@
+use std::f32::consts::PI;
+
+/// The window that samples are multiplied by before they are transformed. Windows other than Rectangular
+/// reduce spectral leakage from frequencies that don't fit evenly in the window, at the cost of frequency
+/// resolution. The reconstructed sample is divided by the window's gain, so the window doesn't change the
+/// amplitude of the signal
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowFunction {
+    /// No windowing
+    #[default]
+    Rectangular,
+    Hann,
+    /// Higher beta increases stopband attenuation and widens the transition band. 0.0 is rectangular
+    Kaiser {
+        beta: f32,
+    },
+}
+
+impl WindowFunction {
+    /// The coefficients for a window of window_size samples. The peak is at window_size / 2, which is where the
+    /// interpolator reconstructs samples
+    pub fn coefficients(&self, window_size: usize) -> Vec<f32> {
+        let center = (window_size / 2) as f32;
+        let half_width = window_size as f32 / 2.0;
+
+        (0..window_size)
+            .map(|n| {
+                // -1.0 to 1.0 across the window
+                let t = (n as f32 - center) / half_width;
+
+                match self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 + 0.5 * (PI * t).cos(),
+                    WindowFunction::Kaiser { beta } => kaiser(*beta, t),
+                }
+            })
+            .collect()
+    }
+}
+
+// The Kaiser window at t, which runs from -1.0 to 1.0 across the window
+pub(super) fn kaiser(beta: f32, t: f32) -> f32 {
+    bessel_i0(beta * (1.0 - t * t).max(0.0).sqrt()) / bessel_i0(beta)
+}
+
+// Zeroth-order modified Bessel function of the first kind
+fn bessel_i0(x: f32) -> f32 {
+    let half_x = x as f64 / 2.0;
+    let mut sum = 1.0f64;
+    let mut term = 1.0f64;
+    let mut k = 1.0f64;
+
+    while term > sum * 1e-12 {
+        term *= (half_x / k) * (half_x / k);
+        sum += term;
+        k += 1.0;
+    }
+
+    sum as f32
+}
@@ -1,14 +1,53 @@
+pub mod caching_sample_provider;
+pub mod fixed_point_sample_provider;
+pub mod gain_sample_provider;
 pub mod interpolator;
+pub mod map_error_sample_provider;
+#[cfg(feature = "memmap2")]
+pub mod mmap_sample_provider;
+pub mod overlap_add_reader;
+pub mod player;
+pub mod resampler;
+pub mod src_iterator;
+pub mod streaming_interpolator;
+pub mod sum_sample_provider;
+pub mod sustain_loop;
+#[cfg(feature = "symphonia")]
+pub mod symphonia_sample_provider;
 
 #[cfg(test)]
 mod tests {
     use std::{
-        cell::RefCell, f32::consts::PI, fs, io::{Error, ErrorKind, Result}, path::Path
+        alloc::{GlobalAlloc, Layout, System},
+        cell::{Cell, RefCell},
+        f32::consts::PI,
+        fs,
+        io::{Error, ErrorKind, Result},
+        path::Path,
+        sync::{Arc, Mutex},
     };
 
     use super::*;
 
-    use interpolator::{Interpolator, SampleProvider};
+    use caching_sample_provider::CachingSampleProvider;
+    use fixed_point_sample_provider::{FixedPointSample, FixedPointSampleProvider, I24};
+    use gain_sample_provider::GainSampleProvider;
+    use interpolator::{
+        BoundaryMode, ComplexSampleProvider, EnvelopeMode, EvaluationPoint, FftBackend,
+        FftTransform, InterpolationMode, Interpolator, InterpolatorBuilder, InterpolatorConfig,
+        InterpolatorError, NonFiniteSamples, Normalization, SampleProvider, SampleWriter,
+        WindowFunction, DEFAULT_MAX_OVERSAMPLING_RATIO, PROGRESS_INTERVAL,
+        REPRODUCIBILITY_TOLERANCE,
+    };
+    use map_error_sample_provider::MapErrorSampleProvider;
+    use overlap_add_reader::OverlapAddReader;
+    use player::Player;
+    use resampler::Resampler;
+    use rustfft::{num_complex::Complex32, FftPlanner};
+    use src_iterator::SrcIterator;
+    use streaming_interpolator::StreamingInterpolator;
+    use sum_sample_provider::SumSampleProvider;
+    use sustain_loop::SustainLoop;
     use wave_stream::{
         read_wav_from_file_path,
         samples_by_channel::SamplesByChannel,
@@ -33,9 +72,45 @@ mod tests {
         }
     }
 
+    // Counts the allocations that a thread makes while it's counting, (see count_allocations,) so that tests can
+    // check that reads don't allocate. The other tests run on their own threads, so they aren't counted
+    struct CountingAllocator;
+
+    thread_local! {
+        static COUNTING_ALLOCATIONS: Cell<bool> = const { Cell::new(false) };
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            if COUNTING_ALLOCATIONS.try_with(Cell::get).unwrap_or(false) {
+                ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+            }
+
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    // The number of allocations that read makes on this thread
+    fn count_allocations(read: impl FnOnce()) -> usize {
+        ALLOCATIONS.with(|allocations| allocations.set(0));
+        COUNTING_ALLOCATIONS.with(|counting_allocations| counting_allocations.set(true));
+        read();
+        COUNTING_ALLOCATIONS.with(|counting_allocations| counting_allocations.set(false));
+        ALLOCATIONS.with(Cell::get)
+    }
+
     struct NyquistSampleProvider {}
 
     impl SampleProvider<&str, Error> for NyquistSampleProvider {
+        #[allow(clippy::manual_is_multiple_of)]
         fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
             assert!(channel_id.eq("test"));
 
@@ -53,19 +128,27 @@ mod tests {
 
         assert_eq!(
             1.0,
-            interpolator.get_interpolated_sample("test", 0.0).unwrap()
+            interpolator
+                .get_interpolated_sample("test", 0.0, 1.0)
+                .unwrap()
         );
         assert_eq!(
             -1.0,
-            interpolator.get_interpolated_sample("test", 1.0).unwrap()
+            interpolator
+                .get_interpolated_sample("test", 1.0, 1.0)
+                .unwrap()
         );
         assert_eq!(
             1.0,
-            interpolator.get_interpolated_sample("test", 2.0).unwrap()
+            interpolator
+                .get_interpolated_sample("test", 2.0, 1.0)
+                .unwrap()
         );
         assert_eq!(
             -1.0,
-            interpolator.get_interpolated_sample("test", 3.0).unwrap()
+            interpolator
+                .get_interpolated_sample("test", 3.0, 1.0)
+                .unwrap()
         );
     }
 
@@ -75,26 +158,93 @@ mod tests {
 
         assert(
             0.0,
-            interpolator.get_interpolated_sample("test", 100.5).unwrap(),
+            interpolator
+                .get_interpolated_sample("test", 100.5, 1.0)
+                .unwrap(),
             "Wrong value for 100.5",
         );
         assert(
             0.0,
-            interpolator.get_interpolated_sample("test", 101.5).unwrap(),
+            interpolator
+                .get_interpolated_sample("test", 101.5, 1.0)
+                .unwrap(),
             "Wrong value for 101.5",
         );
         assert(
             0.0,
-            interpolator.get_interpolated_sample("test", 102.5).unwrap(),
+            interpolator
+                .get_interpolated_sample("test", 102.5, 1.0)
+                .unwrap(),
             "Wrong value for 102.5",
         );
         assert(
             0.0,
-            interpolator.get_interpolated_sample("test", 103.5).unwrap(),
+            interpolator
+                .get_interpolated_sample("test", 103.5, 1.0)
+                .unwrap(),
             "Wrong value for 103.5",
         );
     }
 
+    #[test]
+    fn phase_shifts_per_sample() {
+        let window_size = 16;
+        let interpolator = Interpolator::new(window_size, 200, NyquistSampleProvider {});
+
+        let phase_shifts_per_sample = interpolator.phase_shifts_per_sample();
+        assert_eq!(window_size / 2 + 1, phase_shifts_per_sample.len());
+
+        // Nyquist is ambiguous at +/- PI, so it's compared by magnitude
+        for (freq_index, phase_shift) in phase_shifts_per_sample.iter().enumerate() {
+            let expected = 2.0 * PI * (freq_index as f32) / (window_size as f32);
+            if freq_index == window_size / 2 {
+                assert(expected, phase_shift.abs(), "Wrong phase shift for Nyquist");
+            } else {
+                assert(
+                    expected,
+                    *phase_shift,
+                    &format!("Wrong phase shift for frequency {}", freq_index),
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "window_size must be at least 2")]
+    fn window_size_0() {
+        Interpolator::new(0, 200, NyquistSampleProvider {});
+    }
+
+    #[test]
+    #[should_panic(expected = "window_size must be at least 2")]
+    fn window_size_1() {
+        Interpolator::new(1, 200, NyquistSampleProvider {});
+    }
+
+    #[test]
+    fn debug_and_display() {
+        let interpolator = Interpolator::new(20, 200, NyquistSampleProvider {});
+        interpolator
+            .get_interpolated_sample("test", 100.5, 3.0)
+            .unwrap();
+
+        let debug = format!("{:?}", interpolator);
+        assert!(debug.contains("window_size: 20"), "{}", debug);
+        assert!(debug.contains("num_samples: 200"), "{}", debug);
+        assert!(debug.contains("cached_transforms: Some(1)"), "{}", debug);
+        // A relative_speed of 3.0 blends the averages of 2 and 4 points
+        assert!(
+            debug.contains("cached_fft_sizes: Some([2, 4, 20])"),
+            "{}",
+            debug
+        );
+
+        assert_eq!(
+            "Interpolator: window_size 20, num_samples 200",
+            format!("{}", interpolator)
+        );
+    }
+
     struct DCSampleProvider {
         pub result: f32,
     }
@@ -112,7 +262,115 @@ mod tests {
 
         assert_eq!(
             0.75,
-            interpolator.get_interpolated_sample("dc", 100.5).unwrap()
+            interpolator
+                .get_interpolated_sample("dc", 100.5, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn dc_with_aliasing_filter() {
+        let interpolator = Interpolator::new(20, 200, DCSampleProvider { result: 0.75 });
+        assert(
+            0.75,
+            interpolator
+                .get_interpolated_sample("dc", 100.5, 3.0)
+                .unwrap(),
+            "Wrong value for default oversampling",
+        );
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .min_oversampling_ratio(6)
+            .build(DCSampleProvider { result: 0.75 });
+        assert(
+            0.75,
+            interpolator
+                .get_interpolated_sample("dc", 100.5, 3.0)
+                .unwrap(),
+            "Wrong value for min_oversampling_ratio",
+        );
+    }
+
+    #[test]
+    fn dc_every_window_function() {
+        // Windows attenuate the signal; the reconstruction must undo the window's gain so DC keeps its level
+        let window_functions = [
+            WindowFunction::Rectangular,
+            WindowFunction::Hann,
+            WindowFunction::Kaiser { beta: 0.0 },
+            WindowFunction::Kaiser { beta: 4.0 },
+            WindowFunction::Kaiser { beta: 8.6 },
+        ];
+
+        for window_function in window_functions {
+            for window_size in [20, 21] {
+                let interpolator = InterpolatorBuilder::new(window_size, 200)
+                    .window_function(window_function)
+                    .build(DCSampleProvider { result: 0.75 });
+
+                for index in [100.0, 100.25, 100.5] {
+                    for relative_speed in [1.0, 3.0] {
+                        assert(
+                            0.75,
+                            interpolator
+                                .get_interpolated_sample("dc", index, relative_speed)
+                                .unwrap(),
+                            &format!(
+                                "Wrong DC for {window_function:?}, window size {window_size}, index {index}, speed {relative_speed}"
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oversampling_ratio_equals_window_size() {
+        // A relative_speed of 3.0 oversamples by 4, so the anti-aliasing filter and the interpolation share
+        // the same FFT cache entry
+        let interpolator = Interpolator::new(4, 200, DCSampleProvider { result: 0.75 });
+        for index in 0..10 {
+            assert(
+                0.75,
+                interpolator
+                    .get_interpolated_sample("dc", 100.3 + (index as f32) * 3.0, 3.0)
+                    .unwrap(),
+                "Wrong value when oversampling_ratio == window_size",
+            );
+        }
+
+        let interpolator = InterpolatorBuilder::new(8, 200)
+            .min_oversampling_ratio(8)
+            .build(DCSampleProvider { result: 0.75 });
+        assert(
+            0.75,
+            interpolator
+                .get_interpolated_sample("dc", 100.3, 1.5)
+                .unwrap(),
+            "Wrong value when min_oversampling_ratio == window_size",
+        );
+    }
+
+    #[test]
+    fn min_oversampling_ratio_attenuates_nyquist() {
+        let interpolator = Interpolator::new(20, 200, NyquistSampleProvider {});
+        let default_sample = interpolator
+            .get_interpolated_sample("test", 100.0, 3.0)
+            .unwrap();
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .min_oversampling_ratio(16)
+            .build(NyquistSampleProvider {});
+        let oversampled_sample = interpolator
+            .get_interpolated_sample("test", 100.0, 3.0)
+            .unwrap();
+
+        assert!(
+            oversampled_sample.abs() < default_sample.abs(),
+            "More oversampling should attenuate Nyquist more: Default: {}, Oversampled: {}",
+            default_sample,
+            oversampled_sample
         );
     }
 
@@ -136,27 +394,29 @@ mod tests {
 
         assert_eq!(
             0.0,
-            interpolator.get_interpolated_sample("test", 0.0).unwrap()
+            interpolator
+                .get_interpolated_sample("test", 0.0, 1.0)
+                .unwrap()
         );
 
         assert_eq!(
             ErrorKind::BrokenPipe,
             interpolator
-                .get_interpolated_sample("test", 3.0)
+                .get_interpolated_sample("test", 3.0, 1.0)
                 .unwrap_err()
                 .kind()
         );
         assert_eq!(
             ErrorKind::BrokenPipe,
             interpolator
-                .get_interpolated_sample("test", 2.1)
+                .get_interpolated_sample("test", 2.1, 1.0)
                 .unwrap_err()
                 .kind()
         );
         assert_eq!(
             ErrorKind::BrokenPipe,
             interpolator
-                .get_interpolated_sample("test", 3.1)
+                .get_interpolated_sample("test", 3.1, 1.0)
                 .unwrap_err()
                 .kind()
         );
@@ -168,6 +428,7 @@ mod tests {
         fn get_sample_float(&self, index: f32) -> f32;
     }
 
+    #[allow(clippy::manual_range_contains)]
     fn print_waveforms<TSampleProvider, TChannelId>(
         start: f32,
         end: f32,
@@ -273,7 +534,7 @@ mod tests {
             });
 
             let interpolator_sample = interpolator
-                .get_interpolated_sample(channel_id, index)
+                .get_interpolated_sample(channel_id, index, 1.0)
                 .unwrap();
             interpolator_0.push_str(if interpolator_sample < -0.8 { "*" } else { " " });
             interpolator_1.push_str(
@@ -363,6 +624,7 @@ mod tests {
 
     struct FourSampleWavelengthSignalProvider {}
 
+    #[allow(clippy::let_and_return)]
     fn get_four_sample_wavelength_sample(x: f32) -> f32 {
         let arg = x * PI / 2.0;
         let y = arg.cos();
@@ -396,11 +658,14 @@ mod tests {
         );
 
         let expected = get_four_sample_wavelength_sample(10.2);
-        let actual = interpolator.get_interpolated_sample("test", 10.2).unwrap();
+        let actual = interpolator
+            .get_interpolated_sample("test", 10.2, 1.0)
+            .unwrap();
 
         assert(expected, actual, "Wrong value for a four-sample window");
     }
 
+    #[derive(Clone)]
     struct SignalSampleProvider {}
 
     fn get_signal_sample(x: f32) -> f32 {
@@ -437,7 +702,9 @@ mod tests {
         let mut x = 500.0;
         while x <= 1500.0 {
             let expected_sample = get_signal_sample(x);
-            let actual_sample = interpolator.get_interpolated_sample("test", x).unwrap();
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
 
             assert(
                 expected_sample,
@@ -450,12 +717,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clone_per_thread() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        let threads: Vec<_> = (0..4)
+            .map(|thread_ctr| {
+                let interpolator = interpolator.clone();
+                std::thread::spawn(move || {
+                    let mut x = 500.0 + (thread_ctr as f32 * 100.0);
+                    let end = x + 100.0;
+                    while x <= end {
+                        assert(
+                            get_signal_sample(x),
+                            interpolator
+                                .get_interpolated_sample("test", x, 1.0)
+                                .unwrap(),
+                            &format!("When reading from a cloned interpolator at index {}", x),
+                        );
+
+                        x += 0.1;
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(
+            interpolator.phase_shifts_per_sample(),
+            interpolator.clone().phase_shifts_per_sample()
+        );
+    }
+
+    // Reads from another interpolator, so that reading one interpolator's anti-aliasing filter reads through
+    // the next interpolator's anti-aliasing filter
+    struct InterpolatedSampleProvider<TSampleProvider>
+    where
+        TSampleProvider: SampleProvider<&'static str, Error>,
+    {
+        interpolator: Interpolator<TSampleProvider, &'static str, Error>,
+    }
+
+    impl<TSampleProvider> SampleProvider<&str, Error> for InterpolatedSampleProvider<TSampleProvider>
+    where
+        TSampleProvider: SampleProvider<&'static str, Error>,
+    {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            self.interpolator
+                .get_interpolated_sample("test", index as f32 + 0.5, 1.5)
+        }
+    }
+
+    #[test]
+    fn nested_aliasing_filter() {
+        let innermost = Interpolator::new(16, 2000, SignalSampleProvider {});
+        let middle = Interpolator::new(
+            16,
+            2000,
+            InterpolatedSampleProvider {
+                interpolator: innermost,
+            },
+        );
+        let outermost = Interpolator::new(
+            16,
+            2000,
+            InterpolatedSampleProvider {
+                interpolator: middle,
+            },
+        );
+
+        for index in 0..20 {
+            let sample = outermost
+                .get_interpolated_sample("test", 500.0 + (index as f32) * 2.7, 2.7)
+                .unwrap();
+            assert!(sample.is_finite(), "Sample at {} isn't finite", index);
+        }
+    }
+
     #[derive(Debug, Copy, Clone)]
     struct SineSignalProvider {
         wavelength_in_samples: f32,
     }
 
     impl SineSignalProvider {
+        #[allow(clippy::let_and_return)]
         fn get_sine_signal_sample(&self, x: f32) -> f32 {
             let arg = x * (PI / (self.wavelength_in_samples / 2.0));
             // The peak of the waveform must correlate *exactly* with a sample. If the peak isn't exactly on a sample,
@@ -498,7 +848,9 @@ mod tests {
         let mut x = 500.0;
         while x <= 1500.0 {
             let expected_sample = sine_signal_provider.get_sine_signal_sample(x);
-            let actual_sample = interpolator.get_interpolated_sample("test", x).unwrap();
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
 
             assert(
                 expected_sample,
@@ -510,6 +862,137 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rotator_cache_fine_sweep() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 8.0,
+        };
+
+        let interpolator = InterpolatorBuilder::new(8, 2000)
+            .rotator_cache_resolution(4096)
+            .build(sine_signal_provider);
+
+        // Sweep through every quantized fraction more than once, so that cached rotators are reused
+        let mut x = 500.0;
+        while x <= 502.0 {
+            let expected_sample = sine_signal_provider.get_sine_signal_sample(x);
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
+
+            assert(
+                expected_sample,
+                actual_sample,
+                &format!("When reading with cached rotators at index {}", x),
+            );
+
+            x += 1.0 / 8192.0;
+        }
+    }
+
+    #[test]
+    fn rotator_cache_window_gain() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 8.0,
+        };
+
+        let interpolator = InterpolatorBuilder::new(64, 2000)
+            .window_function(WindowFunction::Hann)
+            .build(sine_signal_provider);
+        let mut cached_interpolator = InterpolatorBuilder::new(64, 2000)
+            .window_function(WindowFunction::Hann)
+            .rotator_cache_resolution(4096)
+            .build(sine_signal_provider);
+
+        // The window's gain is cached with the rotators, so it's read from the cache on the second pass
+        for _ in 0..2 {
+            let mut x = 500.0;
+            while x <= 501.0 {
+                let expected_sample = interpolator
+                    .get_interpolated_sample("test", x, 1.0)
+                    .unwrap();
+
+                assert(
+                    expected_sample,
+                    cached_interpolator
+                        .get_interpolated_sample("test", x, 1.0)
+                        .unwrap(),
+                    &format!("When reading with the cached window gain at index {}", x),
+                );
+                assert(
+                    expected_sample,
+                    cached_interpolator
+                        .get_interpolated_sample_mut("test", x, 1.0)
+                        .unwrap(),
+                    &format!(
+                        "When reading mutably with the cached window gain at index {}",
+                        x
+                    ),
+                );
+
+                x += 1.0 / 64.0;
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct DCOffsetSampleProvider {
+        sine_signal_provider: SineSignalProvider,
+        offset: f32,
+    }
+
+    impl SampleProvider<&str, Error> for DCOffsetSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            Ok(self
+                .sine_signal_provider
+                .get_sine_signal_sample(index as f32)
+                + self.offset)
+        }
+    }
+
+    #[test]
+    fn remove_dc() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 8.0,
+        };
+
+        let interpolator =
+            InterpolatorBuilder::new(16, 2000)
+                .remove_dc(true)
+                .build(DCOffsetSampleProvider {
+                    sine_signal_provider,
+                    offset: 0.3,
+                });
+
+        let mut sum = 0.0;
+        let mut count = 0;
+        let mut x = 500.0;
+        while x < 508.0 {
+            let expected_sample = sine_signal_provider.get_sine_signal_sample(x);
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
+
+            assert(
+                expected_sample,
+                actual_sample,
+                &format!("DC offset wasn't removed at index {}", x),
+            );
+
+            sum += actual_sample;
+            count += 1;
+            x += 0.25;
+        }
+
+        assert(
+            0.0,
+            sum / (count as f32),
+            "Reconstructed signal isn't centered on zero",
+        );
+    }
+
     // The wavelength test must be a sin wave that fits within a frequency slot
     // 3, 5, 6, 7 won't work because they aren't an even multiple of the sampling rate
 
@@ -528,64 +1011,4768 @@ mod tests {
         test_wavelength(8.0);
     }
 
-    struct RandomAccessWavReaderSampleProvider {
-        random_access_wav_reader: RefCell<RandomAccessWavReader<f32>>,
+    fn count_zero_crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count()
     }
 
-    impl SampleProvider<&str, Error> for RandomAccessWavReaderSampleProvider {
-        fn get_sample(&self, _channel_id: &str, index: usize) -> std::result::Result<f32, Error> {
-            let mut random_access_wav_reader = self.random_access_wav_reader.borrow_mut();
-            let read_sample_result = random_access_wav_reader.read_sample(index);
-            let samples_by_channel = read_sample_result?;
-            let sample = samples_by_channel.front_left.expect("Can't read the sample");
-            Ok(sample)
+    fn test_time_stretch(factor: f32) {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(256, 4000, sine_signal_provider);
+
+        let stretched = interpolator.time_stretch("test", factor).unwrap();
+        assert_eq!(
+            (4000.0 * factor).round() as usize,
+            stretched.len(),
+            "Wrong number of samples"
+        );
+
+        // Away from the edges, the pitch (zero crossings per sample) and amplitude are unchanged
+        let middle = &stretched[400..(stretched.len() - 400)];
+        let expected_zero_crossings = (middle.len() as f32) / 20.0;
+        let actual_zero_crossings = count_zero_crossings(middle) as f32;
+        assert!(
+            (expected_zero_crossings - actual_zero_crossings).abs() <= 2.0,
+            "Pitch changed: Expected {} zero crossings, Actual: {}",
+            expected_zero_crossings,
+            actual_zero_crossings
+        );
+
+        let peak = middle
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(
+            (peak - 1.0).abs() < 0.1,
+            "Amplitude changed: Expected 1.0, Actual: {}",
+            peak
+        );
+    }
+
+    #[test]
+    fn time_stretch_longer() {
+        test_time_stretch(1.5);
+    }
+
+    #[test]
+    fn time_stretch_shorter() {
+        test_time_stretch(0.75);
+    }
+
+    struct VecSampleProvider {
+        samples: Vec<f32>,
+    }
+
+    impl SampleProvider<&str, Error> for VecSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            Ok(self.samples[index])
         }
     }
 
+    fn convert_sample_rate(samples: Vec<f32>, in_rate: u32, out_rate: u32) -> Vec<f32> {
+        let interpolator = Interpolator::new(120, samples.len(), VecSampleProvider { samples });
+
+        SrcIterator::new(&interpolator, "test", in_rate, out_rate)
+            .map(|sample| sample.unwrap())
+            .collect()
+    }
+
     #[test]
-    fn wave_stream_supported() {
-        let header = WavHeader {
-            sample_format: SampleFormat::Float,
-            channels: Channels::new().front_left(),
-            sample_rate: 44100,
+    fn src_iterator_lengths() {
+        let samples = vec![0.0; 4410];
+
+        let upsampled = convert_sample_rate(samples, 44100, 48000);
+        assert_eq!(
+            4800,
+            upsampled.len(),
+            "Wrong number of samples when upsampling"
+        );
+
+        let downsampled = convert_sample_rate(upsampled, 48000, 44100);
+        assert_eq!(
+            4410,
+            downsampled.len(),
+            "Wrong number of samples when downsampling"
+        );
+    }
+
+    #[test]
+    fn src_iterator_round_trip() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
         };
 
-        let samples = vec![
-            0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0,
-            0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0,
-        ];
+        let samples: Vec<f32> = (0..4410)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
 
-        {
-            let open_wav_writer = write_wav_to_file_path(Path::new("delete_me.wav"), header).unwrap();
+        let upsampled = convert_sample_rate(samples.clone(), 44100, 48000);
+        let round_tripped = convert_sample_rate(upsampled, 48000, 44100);
 
-            let mut random_access_wave_writer = open_wav_writer.get_random_access_f32_writer().unwrap();
+        // The edges are zero-padded, so only the middle of the signal is compared
+        for index in 200..(samples.len() - 200) {
+            let expected_sample = samples[index];
+            let actual_sample = round_tripped[index];
+            let difference = (expected_sample - actual_sample).abs();
 
-            for sample_ctr in 0..samples.len() {
-                random_access_wave_writer
-                    .write_samples(
-                        sample_ctr,
-                        SamplesByChannel::new().front_left(samples[sample_ctr]),
-                    )
-                    .unwrap()
-            }
+            assert!(
+                difference < 0.01,
+                "Round trip error too large at index {}: Expected: {}, Actual: {}",
+                index,
+                expected_sample,
+                actual_sample
+            );
         }
+    }
 
-        let open_wav_reader = read_wav_from_file_path(Path::new("delete_me.wav")).unwrap();
-        let random_access_wav_reader_sample_provider = RandomAccessWavReaderSampleProvider {
-            random_access_wav_reader: RefCell::new(open_wav_reader.get_random_access_f32_reader().unwrap()),
-        };
+    #[test]
+    fn resample_all_upsample() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
 
-        let interpolator = Interpolator::new(
-            4,
-            samples.len(),
-            random_access_wav_reader_sample_provider,
-        );
+        let resampled = interpolator.resample_all("test", 0.75).unwrap();
+        assert_eq!(266, resampled.len(), "Wrong number of samples");
 
-        for sample_ctr in 0..samples.len() {
-            let expected_sample = samples[sample_ctr];
-            let actual_sample = interpolator.get_interpolated_sample("", sample_ctr as f32).unwrap();
-            assert_eq!(expected_sample, actual_sample, "Wrong sample when reading from a wav file");
+        for (output_index, actual_sample) in resampled.iter().enumerate() {
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", (output_index as f32) * 0.75, 0.75)
+                .unwrap();
+
+            assert(
+                expected_sample,
+                *actual_sample,
+                &format!("Wrong sample at output index {}", output_index),
+            );
         }
+    }
+
+    #[test]
+    fn resample_all_downsample() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+
+        let resampled = interpolator.resample_all("test", 2.5).unwrap();
+        assert_eq!(80, resampled.len(), "Wrong number of samples");
+
+        for (output_index, actual_sample) in resampled.iter().enumerate() {
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", (output_index as f32) * 2.5, 2.5)
+                .unwrap();
+
+            assert(
+                expected_sample,
+                *actual_sample,
+                &format!("Wrong sample at output index {}", output_index),
+            );
+        }
+    }
+
+    struct CountingSampleProvider {
+        reads: Cell<usize>,
+    }
+
+    impl SampleProvider<&str, Error> for CountingSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            self.reads.set(self.reads.get() + 1);
+            Ok(get_signal_sample(index as f32))
+        }
+    }
+
+    #[test]
+    fn caching_sample_provider() {
+        let caching_sample_provider = CachingSampleProvider::new(
+            CountingSampleProvider {
+                reads: Cell::new(0),
+            },
+            4,
+        );
+
+        for index in 0..4 {
+            assert_eq!(
+                get_signal_sample(index as f32),
+                caching_sample_provider.get_sample("test", index).unwrap()
+            );
+        }
+        assert_eq!(4, caching_sample_provider.sample_provider().reads.get());
+
+        // Cached samples don't read from the wrapped provider
+        for index in 0..4 {
+            caching_sample_provider.get_sample("test", index).unwrap();
+        }
+        assert_eq!(4, caching_sample_provider.sample_provider().reads.get());
+
+        // Reading a new sample evicts the least recently read
+        caching_sample_provider.get_sample("test", 4).unwrap();
+        assert_eq!(5, caching_sample_provider.sample_provider().reads.get());
+        caching_sample_provider.get_sample("test", 1).unwrap();
+        assert_eq!(5, caching_sample_provider.sample_provider().reads.get());
+        caching_sample_provider.get_sample("test", 0).unwrap();
+        assert_eq!(6, caching_sample_provider.sample_provider().reads.get());
+
+        // Reading a cached sample keeps it from being evicted, even though it was cached before the others
+        caching_sample_provider.get_sample("test", 3).unwrap();
+        caching_sample_provider.get_sample("test", 5).unwrap();
+        caching_sample_provider.get_sample("test", 6).unwrap();
+        assert_eq!(8, caching_sample_provider.sample_provider().reads.get());
+        caching_sample_provider.get_sample("test", 3).unwrap();
+        assert_eq!(8, caching_sample_provider.sample_provider().reads.get());
+    }
+
+    #[test]
+    fn caching_sample_provider_interpolation() {
+        let interpolator = Interpolator::new(
+            120,
+            2000,
+            CachingSampleProvider::new(
+                CountingSampleProvider {
+                    reads: Cell::new(0),
+                },
+                240,
+            ),
+        );
+
+        let mut x = 500.0;
+        while x <= 600.0 {
+            assert(
+                get_signal_sample(x),
+                interpolator
+                    .get_interpolated_sample("test", x, 1.0)
+                    .unwrap(),
+                &format!("Wrong sample at index {}", x),
+            );
+
+            x += 0.25;
+        }
+    }
+
+    #[test]
+    fn resample_with_speed_fn() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(120, 2000, sine_signal_provider);
+
+        // The speed glides from 0.5 to 1.5, switching to the anti-aliasing filter halfway through
+        let speed_fn = |output_index: usize| 0.5 + (output_index as f32) / 200.0;
+        let resampled = interpolator
+            .resample_with_speed_fn("test", 500.25, 200, speed_fn)
+            .unwrap();
+        assert_eq!(200, resampled.len(), "Wrong number of samples");
+
+        let mut index = 500.25f64;
+        for (output_index, actual_sample) in resampled.iter().enumerate() {
+            let relative_speed = speed_fn(output_index);
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", index as f32, relative_speed)
+                .unwrap();
+
+            assert_eq!(
+                expected_sample, *actual_sample,
+                "Wrong sample at output index {}",
+                output_index
+            );
+
+            // A low frequency passes through both reconstruction paths, so there's no step at 1.0
+            let difference =
+                (sine_signal_provider.get_sine_signal_sample(index as f32) - actual_sample).abs();
+            assert!(
+                difference < 0.01,
+                "Discontinuity at output index {}: Difference: {}",
+                output_index,
+                difference
+            );
+
+            index += relative_speed as f64;
+        }
+    }
+
+    #[test]
+    fn peak() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+
+        let expected_peak = (0..200)
+            .map(|index| get_signal_sample(index as f32).abs())
+            .fold(0.0f32, f32::max);
+        assert_eq!(expected_peak, interpolator.peak("test").unwrap());
+
+        let interpolated_peak = interpolator.interpolated_peak("test", 4).unwrap();
+        assert!(
+            interpolated_peak >= expected_peak,
+            "Interpolated peak {} is less than the peak of the samples {}",
+            interpolated_peak,
+            expected_peak
+        );
+    }
+
+    #[test]
+    fn resample_all_normalized() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+
+        let resampled = interpolator
+            .resample_all_normalized("test", 0.75, 0.5)
+            .unwrap();
+        let actual_peak = resampled
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+
+        assert(0.5, actual_peak, "Wrong peak after normalization");
+
+        // Normalizing past the clamp range doesn't push samples past the clamp
+        let clamped_interpolator = InterpolatorBuilder::new(120, 200)
+            .clamp(-0.5, 0.5)
+            .build(SignalSampleProvider {});
+        let resampled = clamped_interpolator
+            .resample_all_normalized("test", 0.75, 0.8)
+            .unwrap();
+        assert!(
+            resampled.iter().all(|sample| sample.abs() <= 0.5),
+            "The normalized output isn't clamped"
+        );
+        assert!(
+            clamped_interpolator.max_overshoot() > 0.0,
+            "The overshoot isn't measured"
+        );
+    }
+
+    fn test_odd_window_size(window_size: usize) {
+        // The tone's wavelength is the window size, so it fits in the window exactly
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: window_size as f32,
+        };
+
+        let interpolator = Interpolator::new(window_size, 2000, sine_signal_provider);
+
+        let mut x = 500.0;
+        while x <= 520.0 {
+            let expected_sample = sine_signal_provider.get_sine_signal_sample(x);
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
+
+            assert(
+                expected_sample,
+                actual_sample,
+                &format!(
+                    "When reading with a window size of {} at index {}",
+                    window_size, x
+                ),
+            );
+
+            x += 0.01;
+        }
+    }
+
+    #[test]
+    fn window_size_5() {
+        test_odd_window_size(5);
+    }
+
+    #[test]
+    fn window_size_7() {
+        test_odd_window_size(7);
+    }
+
+    #[test]
+    fn streaming_interpolator() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+
+        let mut streaming_interpolator = StreamingInterpolator::new(120);
+        assert_eq!(None, streaming_interpolator.pull(0.75));
+
+        let mut pulled = Vec::new();
+        for index in 0..2000 {
+            streaming_interpolator.push(sine_signal_provider.get_sine_signal_sample(index as f32));
+
+            while let Some(sample) = streaming_interpolator.pull(0.75) {
+                pulled.push(sample);
+            }
+        }
+
+        // Reading requires the half window after the read position, so the last read is before index 1941
+        assert_eq!(
+            (1941.0f32 / 0.75).ceil() as usize,
+            pulled.len(),
+            "Wrong number of samples pulled"
+        );
+
+        // Away from the start of the stream, (which is preceded by silence,) the signal is reconstructed
+        for (output_index, actual_sample) in pulled.iter().enumerate().skip(200) {
+            let expected_sample =
+                sine_signal_provider.get_sine_signal_sample(output_index as f32 * 0.75);
+
+            assert(
+                expected_sample,
+                *actual_sample,
+                &format!("Wrong streamed sample at output index {}", output_index),
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_interpolator_downsample() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(120, 4000, sine_signal_provider);
+
+        // Samples are pushed in blocks, and the anti-aliasing filter reads before the read position, so none of
+        // the samples that a pull reads may be discarded yet
+        let mut streaming_interpolator = StreamingInterpolator::with_max_relative_speed(120, 8.0);
+        let mut pulled = Vec::new();
+        for block_start in (0..4000).step_by(256) {
+            for index in block_start..(block_start + 256).min(4000) {
+                streaming_interpolator
+                    .push(sine_signal_provider.get_sine_signal_sample(index as f32));
+            }
+
+            while let Some(sample) = streaming_interpolator.pull(8.0) {
+                pulled.push(sample);
+            }
+
+            assert!(
+                streaming_interpolator.buffered_len() <= 120 + 8 + 256,
+                "The buffer holds more than a window, the filter's span, and a block: {}",
+                streaming_interpolator.buffered_len()
+            );
+        }
+
+        for (output_index, actual_sample) in pulled.iter().enumerate() {
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", output_index as f32 * 8.0, 8.0)
+                .unwrap();
+            assert(
+                expected_sample,
+                *actual_sample,
+                &format!("Wrong streamed sample at output index {}", output_index),
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_interpolator_long_stream() {
+        // The phase is taken from the index in whole wavelengths, so that the signal stays exact late in the
+        // stream
+        fn sine_sample(index: f64) -> f32 {
+            (2.0 * std::f64::consts::PI * (index % 40.0) / 40.0).sin() as f32
+        }
+
+        // Past 2^25 samples, an f32 index is 4 samples apart, so the fraction would be lost and the window would
+        // land on samples that were already discarded. Whole-sample pulls don't transform a window, so they get
+        // there quickly
+        let mut streaming_interpolator = StreamingInterpolator::new(120);
+        let mut num_pushed = 0usize;
+        while num_pushed < (1 << 25) + 1000 {
+            streaming_interpolator.push(sine_sample(num_pushed as f64));
+            num_pushed += 1;
+            while streaming_interpolator.pull(1.0).is_some() {}
+        }
+
+        for _ in 0..400 {
+            streaming_interpolator.push(sine_sample(num_pushed as f64));
+            num_pushed += 1;
+
+            loop {
+                let index = streaming_interpolator.index();
+                match streaming_interpolator.pull(0.75) {
+                    Some(sample) => assert(
+                        sine_sample(index),
+                        sample,
+                        &format!("Wrong streamed sample at {}", index),
+                    ),
+                    None => break,
+                }
+            }
+        }
+
+        assert!(
+            streaming_interpolator.index() > (1 << 25) as f64 + 1000.0,
+            "Nothing was pulled at 0.75"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "relative_speed can't be more than max_relative_speed")]
+    fn streaming_interpolator_faster_than_max() {
+        StreamingInterpolator::new(120).pull(2.0);
+    }
+
+    // The fraction of the spectrum's energy that's more than main_lobe_bins away from the peak
+    fn spectral_leakage(
+        window_function: WindowFunction,
+        samples: &[f32],
+        main_lobe_bins: usize,
+    ) -> f32 {
+        let coefficients = window_function.coefficients(samples.len());
+        let mut spectrum: Vec<Complex32> = samples
+            .iter()
+            .zip(coefficients)
+            .map(|(sample, coefficient)| Complex32::new(sample * coefficient, 0.0))
+            .collect();
+        FftPlanner::new()
+            .plan_fft_forward(samples.len())
+            .process(&mut spectrum);
+
+        let energies: Vec<f32> = spectrum
+            .iter()
+            .take(samples.len() / 2 + 1)
+            .map(|freq| freq.norm_sqr())
+            .collect();
+        let (peak_bin, _) = energies
+            .iter()
+            .enumerate()
+            .fold((0, 0.0f32), |peak, (bin, energy)| {
+                if *energy > peak.1 {
+                    (bin, *energy)
+                } else {
+                    peak
+                }
+            });
+
+        let total_energy: f32 = energies.iter().sum();
+        let leaked_energy: f32 = energies
+            .iter()
+            .enumerate()
+            .filter(|(bin, _)| bin.abs_diff(peak_bin) > main_lobe_bins)
+            .map(|(_, energy)| energy)
+            .sum();
+
+        leaked_energy / total_energy
+    }
+
+    #[test]
+    fn kaiser_window_leakage() {
+        // The tone doesn't fit evenly in the window, so it leaks into the other frequencies
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 10.3,
+        };
+        let samples: Vec<f32> = (0..128)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
+
+        let rectangular_leakage = spectral_leakage(WindowFunction::Rectangular, &samples, 4);
+        let kaiser_leakage = spectral_leakage(WindowFunction::Kaiser { beta: 8.0 }, &samples, 4);
+
+        assert!(
+            kaiser_leakage * 100.0 < rectangular_leakage,
+            "Kaiser leakage {} isn't much lower than rectangular leakage {}",
+            kaiser_leakage,
+            rectangular_leakage
+        );
+    }
+
+    #[test]
+    fn kaiser_window_interpolation() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .window_function(WindowFunction::Kaiser { beta: 8.0 })
+            .build(sine_signal_provider);
+        assert_eq!(
+            WindowFunction::Kaiser { beta: 8.0 },
+            interpolator.window_function()
+        );
+
+        let mut x = 500.0;
+        while x <= 520.0 {
+            let expected_sample = sine_signal_provider.get_sine_signal_sample(x);
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", x, 1.0)
+                .unwrap();
+            assert(
+                expected_sample,
+                actual_sample,
+                &format!("Wrong Kaiser-windowed sample at index {}", x),
+            );
+
+            x += 0.01;
+        }
+
+        // The window's gain is undone, so DC is unchanged
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .window_function(WindowFunction::Kaiser { beta: 8.0 })
+            .build(DCSampleProvider { result: 0.5 });
+        assert(
+            0.5,
+            interpolator
+                .get_interpolated_sample("dc", 500.3, 1.0)
+                .unwrap(),
+            "Wrong Kaiser-windowed DC",
+        );
+    }
+
+    #[test]
+    fn confidence() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(120, 2000, sine_signal_provider);
+        let (sample, confidence) = interpolator
+            .get_interpolated_sample_with_confidence("test", 500.3, 1.0)
+            .unwrap();
+        assert(
+            sine_signal_provider.get_sine_signal_sample(500.3),
+            sample,
+            "Wrong sample with confidence",
+        );
+        assert(1.0, confidence, "Wrong confidence for a low frequency");
+
+        // All of the energy is at Nyquist
+        let interpolator = Interpolator::new(120, 2000, NyquistSampleProvider {});
+        let (_, confidence) = interpolator
+            .get_interpolated_sample_with_confidence("test", 500.3, 1.0)
+            .unwrap();
+        assert(0.0, confidence, "Wrong confidence at Nyquist");
+
+        // The anti-aliasing filter removes Nyquist, so none of the energy that's left is near Nyquist
+        let (_, confidence) = interpolator
+            .get_interpolated_sample_with_confidence("test", 500.3, 2.5)
+            .unwrap();
+        assert(
+            1.0,
+            confidence,
+            "Wrong confidence at Nyquist when downsampling",
+        );
+
+        // The confidence is measured on the window that the sample is interpolated from, which stays cached
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .evaluation_point(EvaluationPoint::FromLeftEdge(70.0))
+            .build(sine_signal_provider);
+        let (sample, confidence) = interpolator
+            .get_interpolated_sample_with_confidence("test", 490.3, 1.0)
+            .unwrap();
+        assert(
+            interpolator
+                .get_interpolated_sample("test", 490.3, 1.0)
+                .unwrap(),
+            sample,
+            "Wrong sample with confidence and an evaluation point",
+        );
+        assert(1.0, confidence, "Wrong confidence with an evaluation point");
+        assert!(interpolator.is_cached("test", 500));
+    }
+
+    #[test]
+    fn resample_all_with_progress() {
+        let interpolator = Interpolator::new(20, 10000, SignalSampleProvider {});
+
+        for ratio in [0.75, 1.5] {
+            let mut progress_reports = Vec::new();
+            let resampled = interpolator
+                .resample_all_with_progress("test", ratio, |fraction| {
+                    progress_reports.push(fraction)
+                })
+                .unwrap();
+
+            assert_eq!(
+                interpolator.resample_all("test", ratio).unwrap(),
+                resampled,
+                "Resampling with progress changed the output"
+            );
+
+            let expected_reports = resampled.len().div_ceil(PROGRESS_INTERVAL) + 1;
+            assert_eq!(expected_reports, progress_reports.len());
+            assert_eq!(Some(&0.0), progress_reports.first());
+            assert_eq!(Some(&1.0), progress_reports.last());
+            assert!(
+                progress_reports.windows(2).all(|pair| pair[0] < pair[1]),
+                "Progress didn't increase: {:?}",
+                progress_reports
+            );
+        }
+    }
+
+    #[test]
+    fn shared_planner() {
+        let planner = Arc::new(Mutex::new(FftPlanner::new()));
+
+        let interpolators: Vec<_> = (0..4)
+            .map(|_| {
+                InterpolatorBuilder::new(120, 200)
+                    .planner(planner.clone())
+                    .build(SignalSampleProvider {})
+            })
+            .collect();
+        assert_eq!(5, Arc::strong_count(&planner));
+
+        let unshared_interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+        for interpolator in interpolators.iter() {
+            for index in [0.5, 50.25, 199.75] {
+                assert_eq!(
+                    unshared_interpolator
+                        .get_interpolated_sample("test", index, 1.0)
+                        .unwrap(),
+                    interpolator
+                        .get_interpolated_sample("test", index, 1.0)
+                        .unwrap(),
+                    "Sharing a planner changed the result at {}",
+                    index
+                );
+            }
+        }
+
+        // Clones share the planner too
+        let cloned_interpolator = interpolators[0].clone();
+        assert_eq!(6, Arc::strong_count(&planner));
+        drop(cloned_interpolator);
+        drop(interpolators);
+        assert_eq!(1, Arc::strong_count(&planner));
+    }
+
+    // Wraps rustfft, but normalizes the inverse transform, and counts how many FFTs were planned
+    struct NormalizingFftBackend {
+        planner: FftPlanner<f32>,
+        plans: usize,
+        inverse_plans: usize,
+    }
+
+    struct NormalizedFftTransform {
+        fft: Arc<dyn FftTransform>,
+        scale: f32,
+    }
+
+    impl FftTransform for NormalizedFftTransform {
+        fn get_inplace_scratch_len(&self) -> usize {
+            self.fft.get_inplace_scratch_len()
+        }
+
+        fn process_with_scratch(&self, buffer: &mut [Complex32], scratch: &mut [Complex32]) {
+            self.fft.process_with_scratch(buffer, scratch);
+            for value in buffer.iter_mut() {
+                *value *= self.scale;
+            }
+        }
+    }
+
+    impl FftBackend for NormalizingFftBackend {
+        fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn FftTransform> {
+            self.plans += 1;
+            FftBackend::plan_fft_forward(&mut self.planner, len)
+        }
+
+        fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn FftTransform> {
+            self.plans += 1;
+            self.inverse_plans += 1;
+            Arc::new(NormalizedFftTransform {
+                fft: FftBackend::plan_fft_inverse(&mut self.planner, len),
+                scale: 1.0 / len as f32,
+            })
+        }
+    }
+
+    #[test]
+    fn fft_backend() {
+        let fft_backend = Arc::new(Mutex::new(NormalizingFftBackend {
+            planner: FftPlanner::new(),
+            plans: 0,
+            inverse_plans: 0,
+        }));
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .fft_backend(fft_backend.clone())
+            .build(SignalSampleProvider {});
+
+        let rustfft_interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        for index in [0.5, 500.25, 1999.75] {
+            for relative_speed in [1.0, 3.0] {
+                assert(
+                    rustfft_interpolator
+                        .get_interpolated_sample("test", index, relative_speed)
+                        .unwrap(),
+                    interpolator
+                        .get_interpolated_sample("test", index, relative_speed)
+                        .unwrap(),
+                    &format!(
+                        "The backend's scale changed the result at {}, speed {}",
+                        index, relative_speed
+                    ),
+                );
+            }
+        }
+
+        // The window size plans forward and inverse, and the aliasing filter's sizes only plan forward
+        assert!(fft_backend.lock().unwrap().plans >= 4);
+        assert_eq!(1, fft_backend.lock().unwrap().inverse_plans);
+
+        // The window function's gain is undone at the backend's scale too
+        let hann_interpolator = InterpolatorBuilder::new(120, 2000)
+            .fft_backend(fft_backend)
+            .window_function(WindowFunction::Hann)
+            .build(SignalSampleProvider {});
+        let rustfft_hann_interpolator = InterpolatorBuilder::new(120, 2000)
+            .window_function(WindowFunction::Hann)
+            .build(SignalSampleProvider {});
+        for relative_speed in [1.0, 3.0] {
+            assert(
+                rustfft_hann_interpolator
+                    .get_interpolated_sample("test", 500.25, relative_speed)
+                    .unwrap(),
+                hann_interpolator
+                    .get_interpolated_sample("test", 500.25, relative_speed)
+                    .unwrap(),
+                &format!(
+                    "The backend's scale changed the windowed result at speed {}",
+                    relative_speed
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn boundary_mode_mirror() {
+        let dc_offset_sample_provider = DCOffsetSampleProvider {
+            sine_signal_provider: SineSignalProvider {
+                wavelength_in_samples: 40.0,
+            },
+            offset: 0.5,
+        };
+
+        let zero_interpolator = Interpolator::new(64, 2000, dc_offset_sample_provider);
+        let mirror_interpolator = InterpolatorBuilder::new(64, 2000)
+            .boundary_mode(BoundaryMode::Mirror)
+            .build(dc_offset_sample_provider);
+        assert_eq!(BoundaryMode::Mirror, mirror_interpolator.boundary_mode());
+
+        // Samples before the start are reflected
+        assert_eq!(
+            dc_offset_sample_provider.get_sample("test", 3).unwrap(),
+            mirror_interpolator
+                .get_interpolated_sample("test", -3.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            dc_offset_sample_provider.get_sample("test", 1996).unwrap(),
+            mirror_interpolator
+                .get_interpolated_sample("test", 2002.0, 1.0)
+                .unwrap()
+        );
+
+        let mut zero_error = 0.0;
+        let mut mirror_error = 0.0;
+        for index in [0.25, 0.5, 0.75, 1.5, 2.5, 3.5] {
+            let expected_sample = dc_offset_sample_provider
+                .sine_signal_provider
+                .get_sine_signal_sample(index)
+                + 0.5;
+            zero_error += (expected_sample
+                - zero_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap())
+            .abs();
+            mirror_error += (expected_sample
+                - mirror_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap())
+            .abs();
+        }
+
+        assert!(
+            mirror_error < zero_error / 4.0,
+            "Mirroring didn't improve the first samples: mirror error {}, zero error {}",
+            mirror_error,
+            zero_error
+        );
+    }
+
+    #[test]
+    fn oversampling_ratio() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+
+        assert!(!interpolator.uses_aliasing_filter(0.5));
+        assert!(!interpolator.uses_aliasing_filter(1.0));
+        assert!(interpolator.uses_aliasing_filter(1.01));
+
+        assert_eq!(1, interpolator.oversampling_ratio(0.5));
+        assert_eq!(1, interpolator.oversampling_ratio(1.0));
+        // Between powers of two, the points of both averages are counted
+        assert_eq!(3, interpolator.oversampling_ratio(1.5));
+        assert_eq!(2, interpolator.oversampling_ratio(2.0));
+        assert_eq!(6, interpolator.oversampling_ratio(2.5));
+        assert_eq!(12, interpolator.oversampling_ratio(7.9));
+        assert_eq!(8, interpolator.oversampling_ratio(8.0));
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .min_oversampling_ratio(16)
+            .build(SignalSampleProvider {});
+        assert_eq!(1, interpolator.oversampling_ratio(1.0));
+        assert_eq!(16, interpolator.oversampling_ratio(1.5));
+        assert_eq!(48, interpolator.oversampling_ratio(20.0));
+    }
+
+    // A mono, 16-bit PCM wav file
+    #[cfg(feature = "symphonia")]
+    fn wav_bytes(samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&44100u32.to_le_bytes());
+        bytes.extend_from_slice(&(44100u32 * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    #[cfg(feature = "symphonia")]
+    #[test]
+    fn symphonia_sample_provider() {
+        use std::io::Cursor;
+
+        use symphonia::core::{
+            formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+        };
+        use symphonia_sample_provider::SymphoniaSampleProvider;
+
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<i16> = (0..20000)
+            .map(|index| {
+                (sine_signal_provider.get_sine_signal_sample(index as f32) * 16384.0) as i16
+            })
+            .collect();
+
+        let media_source_stream = MediaSourceStream::new(
+            Box::new(Cursor::new(wav_bytes(&samples))),
+            Default::default(),
+        );
+        let probed = symphonia::default::get_probe()
+            .format(
+                Hint::new().with_extension("wav"),
+                media_source_stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap();
+
+        let sample_provider = SymphoniaSampleProvider::new(probed.format).unwrap();
+        assert_eq!(Some(samples.len()), sample_provider.num_frames());
+        assert_eq!(Some(1), sample_provider.num_channels());
+
+        // Reads backwards and forwards seek, and reads shortly after the decoded packet decode forward
+        for index in [0, 1, 19999, 5000, 4999, 12000, 12345, 15000, 2] {
+            assert_eq!(
+                samples[index] as f32 / 32768.0,
+                sample_provider.get_sample(0, index).unwrap(),
+                "Wrong decoded sample at {}",
+                index
+            );
+        }
+
+        // A channel that isn't in the track is an error, instead of a panic
+        let error = sample_provider.get_sample(1, 2).unwrap_err();
+        match error {
+            symphonia::core::errors::Error::IoError(error) => {
+                assert_eq!(std::io::ErrorKind::InvalidInput, error.kind())
+            }
+            error => panic!("Wrong error for a channel out of range: {}", error),
+        }
+        assert!(sample_provider.get_sample_if_available(1, 2).is_err());
+
+        // Reading past the end of the stream is the end of the signal
+        assert_eq!(
+            None,
+            sample_provider.get_sample_if_available(0, 20000).unwrap()
+        );
+        assert_eq!(
+            None,
+            sample_provider.get_sample_if_available(0, 25000).unwrap()
+        );
+        assert_eq!(
+            Some(samples[19999] as f32 / 32768.0),
+            sample_provider.get_sample_if_available(0, 19999).unwrap()
+        );
+
+        let interpolator = Interpolator::new(120, samples.len(), sample_provider);
+        let mut x = 10000.0;
+        while x < 10010.0 {
+            assert(
+                sine_signal_provider.get_sine_signal_sample(x) / 2.0,
+                interpolator.get_interpolated_sample(0, x, 1.0).unwrap(),
+                &format!("Wrong interpolated sample from symphonia at {}", x),
+            );
+
+            x += 0.1;
+        }
+    }
+
+    #[test]
+    fn downsample_integer() {
+        // With a window of 128, the new Nyquist frequency after halving is frequency 32. The tones fit in the
+        // window evenly, so they don't leak into other frequencies
+        let below_nyquist = SineSignalProvider {
+            wavelength_in_samples: 128.0 / 16.0,
+        };
+        let above_nyquist = SineSignalProvider {
+            wavelength_in_samples: 128.0 / 40.0,
+        };
+
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| {
+                below_nyquist.get_sine_signal_sample(index as f32)
+                    + above_nyquist.get_sine_signal_sample(index as f32)
+            })
+            .collect();
+        let interpolator = Interpolator::new(
+            128,
+            samples.len(),
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+
+        let downsampled = interpolator.downsample_integer("test", 2).unwrap();
+        assert_eq!(1000, downsampled.len());
+
+        // Away from the ends, which are padded with silence, only the tone below Nyquist remains
+        for (output_index, actual_sample) in downsampled.iter().enumerate().skip(64).take(872) {
+            assert(
+                below_nyquist.get_sine_signal_sample((output_index * 2) as f32),
+                *actual_sample,
+                &format!("Wrong downsampled sample at {}", output_index),
+            );
+        }
+
+        assert_eq!(
+            666,
+            interpolator.downsample_integer("test", 3).unwrap().len()
+        );
+
+        // The tail is faded like resample_all's
+        let fading_interpolator = InterpolatorBuilder::new(128, samples.len())
+            .fade_out_tail(true)
+            .build(VecSampleProvider { samples });
+        let faded = fading_interpolator.downsample_integer("test", 2).unwrap();
+        for output_index in [900, 940, 960, 990, 999] {
+            let fade_position = ((output_index * 2) as f32 - (2000 - 128) as f32) / 128.0;
+            let gain = if fade_position <= 0.0 {
+                1.0
+            } else {
+                0.5 + 0.5 * (PI * fade_position).cos()
+            };
+            assert(
+                downsampled[output_index] * gain,
+                faded[output_index],
+                &format!("Wrong faded sample at {}", output_index),
+            );
+        }
+    }
+
+    #[test]
+    fn fft_scale() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+
+        assert_eq!((120.0, 120.0), interpolator.fft_scale(120));
+        assert_eq!((8.0, 8.0), interpolator.fft_scale(8));
+        assert!(format!("{:?}", interpolator).contains("cached_fft_sizes: Some([8, 120])"));
+    }
+
+    #[test]
+    fn overlap_add_reader() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(64, 2000, sine_signal_provider);
+
+        for overlap in [0.5, 0.75] {
+            let mut overlap_add_reader =
+                OverlapAddReader::new(&interpolator, "test", 500.3, overlap);
+
+            // The blocks are continuous across the seam between them
+            let mut samples = overlap_add_reader.next_block(100).unwrap();
+            samples.extend(overlap_add_reader.next_block(37).unwrap());
+            samples.extend(overlap_add_reader.next_block(100).unwrap());
+            assert_eq!(237, samples.len());
+
+            // Samples away from the center of each window are less accurate than reading each sample on its
+            // own, so the tolerance is lower
+            for (offset, actual_sample) in samples.iter().enumerate() {
+                let index = 500.3 + offset as f32;
+                let difference =
+                    (sine_signal_provider.get_sine_signal_sample(index) - actual_sample).abs();
+                assert!(
+                    difference < 0.01,
+                    "Wrong overlap-added sample at {} with overlap {}: Difference: {}",
+                    index,
+                    overlap,
+                    difference
+                );
+            }
+        }
+
+        let kaiser_interpolator = InterpolatorBuilder::new(64, 2000)
+            .window_function(WindowFunction::Kaiser { beta: 8.0 })
+            .build(sine_signal_provider);
+        let samples = OverlapAddReader::new(&kaiser_interpolator, "test", 500.3, 0.5)
+            .next_block(100)
+            .unwrap();
+        for (offset, actual_sample) in samples.iter().enumerate() {
+            let index = 500.3 + offset as f32;
+            let difference =
+                (sine_signal_provider.get_sine_signal_sample(index) - actual_sample).abs();
+            assert!(
+                difference < 0.01,
+                "Wrong Kaiser-windowed overlap-added sample at {}: Difference: {}",
+                index,
+                difference
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "overlap must be at least 0.5")]
+    fn overlap_add_reader_without_overlap() {
+        let interpolator = Interpolator::new(64, 2000, SignalSampleProvider {});
+        OverlapAddReader::new(&interpolator, "test", 500.3, 0.0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The Hann window doesn't add up to a constant with a hop of 26 samples"
+    )]
+    fn overlap_add_reader_uneven_hop() {
+        let interpolator = Interpolator::new(64, 2000, SignalSampleProvider {});
+        OverlapAddReader::new(&interpolator, "test", 500.3, 0.6);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ProviderError {
+        BrokenPipe,
+    }
+
+    #[test]
+    fn map_error_sample_provider() {
+        let sample_provider =
+            MapErrorSampleProvider::new(ErrorSampleProvider {}, |error: Error| {
+                assert_eq!(ErrorKind::BrokenPipe, error.kind());
+                ProviderError::BrokenPipe
+            });
+        let interpolator = Interpolator::new(20, 200, sample_provider);
+
+        assert_eq!(
+            Ok(2.0),
+            interpolator.get_interpolated_sample("test", 2.0, 1.0)
+        );
+        assert_eq!(
+            Err(ProviderError::BrokenPipe),
+            interpolator.get_interpolated_sample("test", 2.1, 1.0)
+        );
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn map_error_sample_provider_anyhow() {
+        fn read(index: f32) -> anyhow::Result<f32> {
+            let interpolator = Interpolator::new(
+                20,
+                200,
+                MapErrorSampleProvider::anyhow(ErrorSampleProvider {}),
+            );
+            let sample = interpolator.get_interpolated_sample("test", index, 1.0)?;
+            Ok(sample * 2.0)
+        }
+
+        assert_eq!(4.0, read(2.0).unwrap());
+        assert_eq!(
+            ErrorKind::BrokenPipe,
+            read(2.1).unwrap_err().downcast::<Error>().unwrap().kind()
+        );
+    }
+
+    #[test]
+    fn get_window() {
+        let interpolator = Interpolator::new(6, 200, ErrorSampleProvider {});
+
+        assert_eq!(
+            vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0],
+            interpolator.get_window("test", 10).unwrap()
+        );
+        assert_eq!(
+            vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0],
+            interpolator.get_window("test", 0).unwrap()
+        );
+        assert_eq!(
+            vec![197.0, 198.0, 199.0, 0.0, 0.0, 0.0],
+            interpolator.get_window("test", 200).unwrap()
+        );
+        assert_eq!(
+            ErrorKind::BrokenPipe,
+            interpolator.get_window("test", 5).unwrap_err().kind()
+        );
+
+        let interpolator = InterpolatorBuilder::new(5, 200)
+            .boundary_mode(BoundaryMode::Mirror)
+            .build(VecSampleProvider {
+                samples: (0..200).map(|index| index as f32).collect(),
+            });
+        assert_eq!(
+            vec![1.0, 0.0, 1.0, 2.0, 3.0],
+            interpolator.get_window("test", 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn evaluation_point() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+
+        let center_interpolator = Interpolator::new(120, 2000, sine_signal_provider);
+        let left_edge_interpolator = InterpolatorBuilder::new(120, 2000)
+            .evaluation_point(EvaluationPoint::FromLeftEdge(60.0))
+            .build(sine_signal_provider);
+        let half_sample_interpolator = InterpolatorBuilder::new(120, 2000)
+            .evaluation_point(EvaluationPoint::FromLeftEdge(59.5))
+            .build(sine_signal_provider);
+        assert_eq!(
+            EvaluationPoint::FromLeftEdge(59.5),
+            half_sample_interpolator.evaluation_point()
+        );
+
+        for index in [500.0, 500.25, 500.5, 731.9] {
+            let center_sample = center_interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+            assert_eq!(
+                center_sample,
+                left_edge_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                "The window's center is window_size / 2 from the left edge"
+            );
+
+            assert(
+                sine_signal_provider.get_sine_signal_sample(index - 0.5),
+                half_sample_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                &format!("Wrong sample half a sample before {}", index),
+            );
+        }
+
+        let resampled = half_sample_interpolator.resample_all("test", 0.5).unwrap();
+        for (output_index, actual_sample) in resampled.iter().enumerate().skip(200).take(100) {
+            assert(
+                sine_signal_provider.get_sine_signal_sample(output_index as f32 * 0.5 - 0.5),
+                *actual_sample,
+                &format!("Wrong resampled sample at {}", output_index),
+            );
+        }
+
+        let downsampled = half_sample_interpolator
+            .downsample_integer("test", 2)
+            .unwrap();
+        for (output_index, actual_sample) in downsampled.iter().enumerate().skip(100).take(100) {
+            assert(
+                sine_signal_provider.get_sine_signal_sample(output_index as f32 * 2.0 - 0.5),
+                *actual_sample,
+                &format!("Wrong downsampled sample at {}", output_index),
+            );
+        }
+    }
+
+    // A signal whose length isn't known in advance
+    struct LiveSampleProvider {
+        samples: RefCell<Vec<f32>>,
+    }
+
+    impl SampleProvider<&str, Error> for LiveSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            self.get_sample_if_available(channel_id, index)?
+                .ok_or(Error::from(ErrorKind::UnexpectedEof))
+        }
+
+        fn get_sample_if_available(&self, channel_id: &str, index: usize) -> Result<Option<f32>> {
+            assert!(channel_id.eq("test"));
+
+            Ok(self.samples.borrow().get(index).copied())
+        }
+    }
+
+    #[test]
+    fn end_of_stream() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<f32> = (0..300)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32) + 0.25)
+            .collect();
+
+        // The live signal's length is unknown, so num_samples is much longer than the signal
+        let known_length_interpolator = Interpolator::new(
+            64,
+            samples.len(),
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+        let live_interpolator = Interpolator::new(
+            64,
+            100000,
+            CachingSampleProvider::new(
+                LiveSampleProvider {
+                    samples: RefCell::new(samples),
+                },
+                128,
+            ),
+        );
+
+        for index in [250.0, 280.5, 299.0, 299.25, 300.0, 310.75] {
+            assert_eq!(
+                known_length_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                live_interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                "The end of the stream wasn't padded with silence at {}",
+                index
+            );
+        }
+
+        assert_eq!(
+            known_length_interpolator.peak("test").unwrap(),
+            live_interpolator.peak("test").unwrap()
+        );
+    }
+
+    #[test]
+    fn end_of_stream_grows() {
+        let samples: Vec<f32> = (0..364)
+            .map(|index| get_signal_sample(index as f32))
+            .collect();
+        let known_length_interpolator = Interpolator::new(
+            64,
+            samples.len(),
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+        let mut live_interpolator = Interpolator::new(
+            64,
+            100000,
+            LiveSampleProvider {
+                samples: RefCell::new(samples[..300].to_vec()),
+            },
+        );
+
+        // The window at 299 is padded past the end of the live signal, so it isn't cached
+        live_interpolator
+            .get_interpolated_sample("test", 299.25, 1.0)
+            .unwrap();
+        assert!(!live_interpolator.is_cached("test", 299));
+        live_interpolator
+            .get_interpolated_sample_mut("test", 299.25, 1.0)
+            .unwrap();
+        assert!(!live_interpolator.is_cached("test", 299));
+
+        // Once the rest of the window arrives, reading again uses it
+        live_interpolator
+            .sample_provider()
+            .samples
+            .borrow_mut()
+            .extend_from_slice(&samples[300..]);
+        let expected_sample = known_length_interpolator
+            .get_interpolated_sample("test", 299.25, 1.0)
+            .unwrap();
+        assert_eq!(
+            expected_sample,
+            live_interpolator
+                .get_interpolated_sample("test", 299.25, 1.0)
+                .unwrap()
+        );
+        assert!(live_interpolator.is_cached("test", 299));
+        assert_eq!(
+            expected_sample,
+            live_interpolator
+                .get_interpolated_sample_mut("test", 299.25, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn output_len() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+
+        for ratio in [1.0, 0.75, 0.1, 1.5, 3.0, 7.0, 200.0, 201.0] {
+            assert_eq!(
+                interpolator.resample_all("test", ratio).unwrap().len(),
+                interpolator.output_len(ratio),
+                "Wrong output length for ratio {}",
+                ratio
+            );
+        }
+
+        assert_eq!(200, interpolator.output_len(1.0));
+        assert_eq!(266, interpolator.output_len(0.75));
+        assert_eq!(66, interpolator.output_len(3.0));
+        assert_eq!(0, interpolator.output_len(201.0));
+    }
+
+    // The energy of the second difference, which is mostly high frequencies
+    fn high_frequency_energy(samples: &[f32]) -> f32 {
+        samples
+            .windows(3)
+            .map(|window| (window[0] - 2.0 * window[1] + window[2]).powi(2))
+            .sum()
+    }
+
+    #[test]
+    fn fade_out_tail() {
+        // The signal ends at the peak of the tone, so it stops abruptly
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<f32> = (0..1010)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
+
+        let interpolator = Interpolator::new(
+            64,
+            samples.len(),
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+        let fading_interpolator = InterpolatorBuilder::new(64, samples.len())
+            .fade_out_tail(true)
+            .build(VecSampleProvider { samples });
+        assert!(fading_interpolator.fade_out_tail());
+
+        let read_tail = |interpolator: &Interpolator<VecSampleProvider, &str, Error>| -> Vec<f32> {
+            (0..400)
+                .map(|step| {
+                    interpolator
+                        .get_interpolated_sample("test", 930.0 + step as f32 * 0.25, 1.0)
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        let tail = read_tail(&interpolator);
+        let faded_tail = read_tail(&fading_interpolator);
+        assert!(
+            high_frequency_energy(&faded_tail) * 2.0 < high_frequency_energy(&tail),
+            "The fade didn't reduce the click: faded {}, unfaded {}",
+            high_frequency_energy(&faded_tail),
+            high_frequency_energy(&tail)
+        );
+
+        // The signal before the last window is unchanged, and the signal after the end is silent
+        assert_eq!(
+            interpolator
+                .get_interpolated_sample("test", 900.5, 1.0)
+                .unwrap(),
+            fading_interpolator
+                .get_interpolated_sample("test", 900.5, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            0.0,
+            fading_interpolator
+                .get_interpolated_sample("test", 1012.5, 1.0)
+                .unwrap()
+        );
+
+        let resampled = fading_interpolator.resample_all("test", 0.25).unwrap();
+        for (step, faded_sample) in faded_tail.iter().enumerate().take(320) {
+            assert_eq!(*faded_sample, resampled[3720 + step]);
+        }
+    }
+
+    // Each channel is a sine wave with a different wavelength
+    struct MultiChannelSampleProvider {}
+
+    impl SampleProvider<usize, Error> for MultiChannelSampleProvider {
+        fn get_sample(&self, channel_id: usize, index: usize) -> Result<f32> {
+            Ok(SineSignalProvider {
+                wavelength_in_samples: 20.0 + 10.0 * channel_id as f32,
+            }
+            .get_sine_signal_sample(index as f32))
+        }
+    }
+
+    #[test]
+    fn get_interpolated_frame() {
+        let interpolators = [
+            Interpolator::new(64, 2000, MultiChannelSampleProvider {}),
+            InterpolatorBuilder::new(64, 2000)
+                .rotator_cache_resolution(64)
+                .build(MultiChannelSampleProvider {}),
+            InterpolatorBuilder::new(64, 2000)
+                .window_function(WindowFunction::Hann)
+                .build(MultiChannelSampleProvider {}),
+        ];
+
+        for interpolator in interpolators.iter() {
+            for index in [500.0, 500.25, 733.5, 1999.75] {
+                for relative_speed in [1.0, 2.5] {
+                    let frame = interpolator
+                        .get_interpolated_frame(&[2, 0, 1], index, relative_speed)
+                        .unwrap();
+                    let expected_frame: Vec<f32> = [2, 0, 1]
+                        .iter()
+                        .map(|channel_id| {
+                            interpolator
+                                .get_interpolated_sample(*channel_id, index, relative_speed)
+                                .unwrap()
+                        })
+                        .collect();
+
+                    assert_eq!(expected_frame, frame, "Wrong frame at {}", index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn aliasing_filter_epsilon() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+        assert!(!interpolator.uses_aliasing_filter(1.00005));
+        assert!(interpolator.uses_aliasing_filter(1.001));
+        assert_eq!(
+            interpolator
+                .get_interpolated_sample("test", 100.3, 1.0)
+                .unwrap(),
+            interpolator
+                .get_interpolated_sample("test", 100.3, 1.00005)
+                .unwrap()
+        );
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .aliasing_filter_epsilon(0.1)
+            .build(SignalSampleProvider {});
+        assert!(!interpolator.uses_aliasing_filter(1.05));
+        assert!(interpolator.uses_aliasing_filter(1.2));
+        assert_eq!(1, interpolator.oversampling_ratio(1.05));
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .aliasing_filter_epsilon(0.0)
+            .build(SignalSampleProvider {});
+        assert!(interpolator.uses_aliasing_filter(1.00005));
+    }
+
+    #[test]
+    fn from_config() {
+        let interpolator = InterpolatorBuilder::new(32, 200)
+            .boundary_mode(BoundaryMode::Mirror)
+            .window_function(WindowFunction::Kaiser { beta: 6.0 })
+            .rotator_cache_resolution(64)
+            .aliasing_filter_kernel(&[0.25, 0.5, 0.25])
+            .build(SignalSampleProvider {});
+
+        let config = interpolator.config();
+        assert_eq!(32, config.window_size);
+        assert_eq!(200, config.num_samples);
+        assert_eq!(BoundaryMode::Mirror, config.boundary_mode);
+        assert_eq!(WindowFunction::Kaiser { beta: 6.0 }, config.window_function);
+        assert_eq!(Some(64), config.rotator_cache_resolution);
+
+        let reconstructed =
+            Interpolator::from_config(config.clone(), SignalSampleProvider {}).unwrap();
+        assert_eq!(config, reconstructed.config());
+        for (index, relative_speed) in [(0.5, 1.0), (50.25, 1.0), (199.75, 1.0), (100.5, 2.0)] {
+            assert_eq!(
+                interpolator
+                    .get_interpolated_sample("test", index, relative_speed)
+                    .unwrap(),
+                reconstructed
+                    .get_interpolated_sample("test", index, relative_speed)
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(
+            InterpolatorConfig::new(32, 200),
+            Interpolator::new(32, 200, SignalSampleProvider {}).config()
+        );
+    }
+
+    #[test]
+    fn from_config_invalid() {
+        let invalid_configs = [
+            InterpolatorConfig::new(1, 200),
+            InterpolatorConfig {
+                round_window_to_fast_size: true,
+                ..InterpolatorConfig::new(0, 200)
+            },
+            InterpolatorConfig {
+                max_oversampling_ratio: 0,
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                aliasing_filter_epsilon: f32::NAN,
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                clamp: Some((1.0, -1.0)),
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                rotator_cache_resolution: Some(0),
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                window_offset: 9,
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                evaluation_point: EvaluationPoint::FromLeftEdge(f32::INFINITY),
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                interpolation_mode: InterpolationMode::WindowedSinc { taps: 1, beta: 5.0 },
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                aliasing_filter_kernel: Some(Vec::new()),
+                ..InterpolatorConfig::new(20, 200)
+            },
+        ];
+
+        for config in invalid_configs {
+            assert!(config.validate().is_err(), "{:?} is invalid", config);
+            assert!(InterpolatorBuilder::from_config(config.clone()).is_err());
+            assert!(Interpolator::from_config(config, SignalSampleProvider {}).is_err());
+        }
+
+        assert_eq!(
+            "window_size must be at least 2, but was 1",
+            InterpolatorConfig::new(1, 200)
+                .validate()
+                .unwrap_err()
+                .message()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_config() {
+        let config = InterpolatorBuilder::new(64, 1000)
+            .evaluation_point(EvaluationPoint::FromLeftEdge(31.5))
+            .window_function(WindowFunction::Hann)
+            .remove_dc(true)
+            .aliasing_filter_kernel(&[0.25, 0.5, 0.25])
+            .build(SignalSampleProvider {})
+            .config();
+
+        let json = serde_json::to_string(&config).unwrap();
+        let deserialized: InterpolatorConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config, deserialized);
+    }
+
+    #[test]
+    fn cached_rotators_match_uncached() {
+        // Cached rotators are quantized to the cache's resolution, which matches these indexes exactly. 37 has no
+        // Nyquist bin
+        for window_size in [37, 64, 70] {
+            let uncached = Interpolator::new(window_size, 200, SignalSampleProvider {});
+            let cached = InterpolatorBuilder::new(window_size, 200)
+                .rotator_cache_resolution(4)
+                .build(SignalSampleProvider {});
+
+            for index in [10.25, 100.5, 150.75] {
+                assert(
+                    uncached
+                        .get_interpolated_sample("test", index, 1.0)
+                        .unwrap(),
+                    cached.get_interpolated_sample("test", index, 1.0).unwrap(),
+                    &format!(
+                        "Cached rotators don't match at window size {window_size}, index {index}"
+                    ),
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_multiply_matches_scalar() {
+        use interpolator::complex_multiply::{multiply_scalar, multiply_simd};
+
+        // 37 isn't a multiple of the number of lanes, so the last values are multiplied by the scalar loop
+        for len in [0, 4, 37, 64] {
+            let values: Vec<Complex32> = (0..len)
+                .map(|n| Complex32::from_polar(get_signal_sample(n as f32), n as f32))
+                .collect();
+            let rotators: Vec<Complex32> = (0..len)
+                .map(|n| Complex32::from_polar(1.0, n as f32 * 0.37))
+                .collect();
+
+            let mut scalar_values = values.clone();
+            multiply_scalar(&mut scalar_values, &rotators);
+            let mut simd_values = values;
+            multiply_simd(&mut simd_values, &rotators);
+
+            for (scalar_value, simd_value) in scalar_values.iter().zip(&simd_values) {
+                assert(scalar_value.re, simd_value.re, "Wrong real part");
+                assert(scalar_value.im, simd_value.im, "Wrong imaginary part");
+            }
+        }
+    }
+
+    struct SwitchableSampleProvider {
+        offset: Cell<f32>,
+        generation: Cell<u64>,
+    }
+
+    impl SampleProvider<&str, Error> for SwitchableSampleProvider {
+        fn get_sample(&self, _channel_id: &str, index: usize) -> Result<f32> {
+            Ok((index as f32 / 10.0).sin() + self.offset.get())
+        }
+
+        fn generation(&self) -> u64 {
+            self.generation.get()
+        }
+    }
+
+    #[test]
+    fn invalidate_channel() {
+        let sample_provider = SwitchableSampleProvider {
+            offset: Cell::new(0.0),
+            generation: Cell::new(0),
+        };
+        let interpolator = Interpolator::new(20, 200, sample_provider);
+        let before = interpolator
+            .get_interpolated_sample("test", 100.5, 1.0)
+            .unwrap();
+
+        // The cached transform still holds the old samples
+        interpolator.sample_provider().offset.set(1.0);
+        assert_eq!(
+            before,
+            interpolator
+                .get_interpolated_sample("test", 100.5, 1.0)
+                .unwrap()
+        );
+
+        interpolator.invalidate_channel("test");
+        let debug = format!("{:?}", interpolator);
+        assert!(debug.contains("cached_transforms: Some(0)"), "{}", debug);
+
+        assert(
+            before + 1.0,
+            interpolator
+                .get_interpolated_sample("test", 100.5, 1.0)
+                .unwrap(),
+            "Wrong sample after invalidating the channel",
+        );
+
+        // Invalidating a channel that was never read does nothing
+        interpolator.invalidate_channel("other");
+    }
+
+    #[test]
+    fn get_interpolated_sample_from_slice() {
+        let sample_provider = SignalSampleProvider {};
+        let window_size = 20;
+        let interpolator = Interpolator::new(window_size, 200, SignalSampleProvider {});
+
+        // The neighborhood of index 100, with index 100 at window_size / 2
+        let samples: Vec<f32> = (90..110)
+            .map(|index| sample_provider.get_sample("test", index).unwrap())
+            .collect();
+
+        for (index, relative_speed) in [(100.0, 1.0), (100.25, 1.0), (100.5, 0.5), (100.5, 1.5)] {
+            assert_eq!(
+                interpolator
+                    .get_interpolated_sample("test", index, relative_speed)
+                    .unwrap(),
+                interpolator
+                    .get_interpolated_sample_from_slice(&samples, index - 90.0, relative_speed)
+                    .unwrap(),
+                "Wrong sample from the slice at {index}, speed {relative_speed}"
+            );
+        }
+
+        // Samples outside of the slice are 0.0
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample_from_slice(&samples, -1.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample_from_slice(&samples, 20.0, 1.0)
+                .unwrap()
+        );
+    }
+
+    // A full-scale square wave, which overshoots between samples when it's reconstructed
+    struct SquareWaveSampleProvider {}
+
+    impl SampleProvider<&str, Error> for SquareWaveSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            Ok(if (index / 10).is_multiple_of(2) {
+                1.0
+            } else {
+                -1.0
+            })
+        }
+    }
+
+    #[test]
+    fn clamp_and_max_overshoot() {
+        let interpolator = Interpolator::new(32, 200, SquareWaveSampleProvider {});
+        assert_eq!(0.0, interpolator.max_overshoot());
+
+        let unclamped = interpolator.resample_all("test", 0.25).unwrap();
+        let peak = unclamped
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(
+            peak > 1.0,
+            "The square wave should overshoot, but its peak is {}",
+            peak
+        );
+        assert_eq!(peak - 1.0, interpolator.max_overshoot());
+
+        interpolator.reset_max_overshoot();
+        assert_eq!(0.0, interpolator.max_overshoot());
+
+        let interpolator = InterpolatorBuilder::new(32, 200)
+            .clamp(-1.0, 1.0)
+            .build(SquareWaveSampleProvider {});
+        let clamped = interpolator.resample_all("test", 0.25).unwrap();
+        for (clamped, unclamped) in clamped.iter().zip(unclamped.iter()) {
+            assert_eq!(unclamped.clamp(-1.0, 1.0), *clamped);
+        }
+
+        // The overshoot is measured before clamping
+        assert_eq!(peak - 1.0, interpolator.max_overshoot());
+
+        let sample = interpolator
+            .get_interpolated_sample("test", 10.25, 1.0)
+            .unwrap();
+        assert!((-1.0..=1.0).contains(&sample), "{} wasn't clamped", sample);
+        assert_eq!(Some((-1.0, 1.0)), interpolator.clamp());
+    }
+
+    #[test]
+    #[should_panic(expected = "The clamp minimum must be less than the maximum")]
+    fn clamp_invalid() {
+        InterpolatorBuilder::new(32, 200)
+            .clamp(1.0, -1.0)
+            .build(SquareWaveSampleProvider {});
+    }
+
+    #[test]
+    fn catmull_rom() {
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .interpolation_mode(InterpolationMode::CatmullRom)
+            .build(CountingSampleProvider {
+                reads: Cell::new(0),
+            });
+
+        // Only the 4 samples around the index are read
+        let sample = interpolator
+            .get_interpolated_sample("test", 100.25, 1.0)
+            .unwrap();
+        assert_eq!(4, interpolator.sample_provider().reads.get());
+        assert!(
+            (get_signal_sample(100.25) - sample).abs() < 0.01,
+            "Wrong sample: {}",
+            sample
+        );
+
+        // Whole-sample indexes are read directly, and relative_speed doesn't filter
+        assert_eq!(
+            get_signal_sample(100.0),
+            interpolator
+                .get_interpolated_sample("test", 100.0, 3.0)
+                .unwrap()
+        );
+        assert_eq!(5, interpolator.sample_provider().reads.get());
+
+        // A line is reconstructed exactly
+        let interpolator = InterpolatorBuilder::new(20, 10)
+            .interpolation_mode(InterpolationMode::CatmullRom)
+            .build(VecSampleProvider {
+                samples: (0..10).map(|index| index as f32 * 0.1).collect(),
+            });
+        assert(
+            0.45,
+            interpolator
+                .get_interpolated_sample("test", 4.5, 1.0)
+                .unwrap(),
+            "Wrong sample on a line",
+        );
+
+        // Sample -1 is 0.0 with BoundaryMode::Zero, so the curve bends at the start instead of following the line
+        assert(
+            0.01796875,
+            interpolator
+                .get_interpolated_sample("test", 0.25, 1.0)
+                .unwrap(),
+            "Wrong sample at the start",
+        );
+
+        let resampled = interpolator.resample_all("test", 0.5).unwrap();
+        assert_eq!(20, resampled.len());
+        assert(0.35, resampled[7], "Wrong resampled sample");
+        assert_eq!(
+            vec![interpolator
+                .get_interpolated_sample("test", 4.5, 1.0)
+                .unwrap()],
+            interpolator
+                .get_interpolated_frame(&["test"], 4.5, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn get_interpolated_sample_mut() {
+        let configs = [
+            InterpolatorConfig::new(20, 200),
+            InterpolatorConfig {
+                rotator_cache_resolution: Some(4),
+                ..InterpolatorConfig::new(21, 200)
+            },
+            InterpolatorConfig {
+                window_function: WindowFunction::Hann,
+                remove_dc: true,
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                evaluation_point: EvaluationPoint::FromLeftEdge(9.5),
+                ..InterpolatorConfig::new(20, 200)
+            },
+        ];
+        let reads = [
+            (100.25, 1.0),
+            (100.5, 1.0),
+            (100.0, 1.0),
+            (101.75, 0.5),
+            (100.5, 3.0),
+        ];
+
+        for config in configs {
+            let interpolator =
+                Interpolator::from_config(config.clone(), SignalSampleProvider {}).unwrap();
+            let mut interpolator_mut =
+                Interpolator::from_config(config.clone(), SignalSampleProvider {}).unwrap();
+
+            for (index, relative_speed) in reads {
+                assert_eq!(
+                    interpolator
+                        .get_interpolated_sample("test", index, relative_speed)
+                        .unwrap(),
+                    interpolator_mut
+                        .get_interpolated_sample_mut("test", index, relative_speed)
+                        .unwrap(),
+                    "Wrong sample at {index}, speed {relative_speed}, {config:?}"
+                );
+            }
+        }
+
+        // The transform is cached
+        let mut interpolator = Interpolator::new(
+            20,
+            200,
+            CountingSampleProvider {
+                reads: Cell::new(0),
+            },
+        );
+        interpolator
+            .get_interpolated_sample_mut("test", 100.25, 1.0)
+            .unwrap();
+        interpolator
+            .get_interpolated_sample_mut("test", 100.5, 1.0)
+            .unwrap();
+        assert_eq!(20, interpolator.sample_provider().reads.get());
+    }
+
+    #[test]
+    fn sustain_loop() {
+        let interpolator = Interpolator::new(
+            20,
+            100,
+            VecSampleProvider {
+                samples: (0..100).map(|index| (index as f32 / 7.0).sin()).collect(),
+            },
+        );
+        let sustain_loop = SustainLoop::new(&interpolator, "test", 20.0, 60.0, 8.0);
+
+        // Before the crossfade, the signal plays unchanged
+        assert_eq!(
+            interpolator
+                .get_interpolated_sample("test", 50.5, 1.0)
+                .unwrap(),
+            sustain_loop.get_sample(50.5, 1.0).unwrap()
+        );
+
+        // Halfway through the crossfade, the end of the loop and the samples before loop_start have equal power
+        assert(
+            (interpolator
+                .get_interpolated_sample("test", 56.0, 1.0)
+                .unwrap()
+                + interpolator
+                    .get_interpolated_sample("test", 16.0, 1.0)
+                    .unwrap())
+                * (PI / 4.0).cos(),
+            sustain_loop.get_sample(56.0, 1.0).unwrap(),
+            "Wrong sample halfway through the crossfade",
+        );
+
+        // Playback arrives at loop_end sounding like the signal just before loop_start, and then wraps
+        let before_loop_start = interpolator
+            .get_interpolated_sample("test", 19.99, 1.0)
+            .unwrap();
+        let before_loop_end = sustain_loop.get_sample(59.99, 1.0).unwrap();
+        assert!(
+            (before_loop_start - before_loop_end).abs() < 0.01,
+            "The crossfade doesn't end at the signal before loop_start: {} vs {}",
+            before_loop_start,
+            before_loop_end
+        );
+        assert_eq!(20.0, sustain_loop.loop_index(60.0));
+        assert_eq!(25.0, sustain_loop.loop_index(105.0));
+        assert_eq!(
+            interpolator
+                .get_interpolated_sample("test", 25.0, 1.0)
+                .unwrap(),
+            sustain_loop.get_sample(105.0, 1.0).unwrap()
+        );
+
+        let samples = sustain_loop.read(50.0, 100, 0.5).unwrap();
+        assert_eq!(100, samples.len());
+        assert_eq!(sustain_loop.get_sample(99.5, 0.5).unwrap(), samples[99]);
+    }
+
+    #[test]
+    #[should_panic(expected = "crossfade_length must be at least 0.0")]
+    fn sustain_loop_crossfade_too_long() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+        SustainLoop::new(&interpolator, "test", 4.0, 60.0, 8.0);
+    }
+
+    #[test]
+    fn get_interpolated_sample_band() {
+        // A window of 32 fits 2 cycles of the low wave and 8 cycles of the high wave
+        let low = |index: f32| (2.0 * PI * index / 16.0).sin();
+        let high = |index: f32| 0.5 * (2.0 * PI * index / 4.0).cos();
+        let interpolator = Interpolator::new(
+            32,
+            200,
+            VecSampleProvider {
+                samples: (0..200)
+                    .map(|index| low(index as f32) + high(index as f32))
+                    .collect(),
+            },
+        );
+
+        for index in [100.0, 100.25, 101.5] {
+            let low_band = interpolator
+                .get_interpolated_sample_band("test", index, 1.0, 0, 4)
+                .unwrap();
+            let high_band = interpolator
+                .get_interpolated_sample_band("test", index, 1.0, 5, 16)
+                .unwrap();
+
+            assert(low(index), low_band, &format!("Wrong low band at {index}"));
+            assert(
+                high(index),
+                high_band,
+                &format!("Wrong high band at {index}"),
+            );
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                low_band + high_band,
+                &format!("The bands don't sum to the signal at {index}"),
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "hi_bin must not be greater than window_size / 2")]
+    fn get_interpolated_sample_band_invalid() {
+        let interpolator = Interpolator::new(32, 200, SignalSampleProvider {});
+        interpolator
+            .get_interpolated_sample_band("test", 100.5, 1.0, 0, 17)
+            .unwrap();
+    }
+
+    #[test]
+    fn generation() {
+        let sample_provider = SwitchableSampleProvider {
+            offset: Cell::new(0.0),
+            generation: Cell::new(0),
+        };
+        let mut interpolator = Interpolator::new(20, 200, sample_provider);
+        let before = interpolator
+            .get_interpolated_sample("test", 100.5, 1.0)
+            .unwrap();
+
+        // Bumping the interpolator's generation invalidates the cached transform
+        interpolator.sample_provider().offset.set(1.0);
+        interpolator.bump_generation();
+        assert(
+            before + 1.0,
+            interpolator
+                .get_interpolated_sample("test", 100.5, 1.0)
+                .unwrap(),
+            "The transform wasn't invalidated by bump_generation",
+        );
+
+        // So does a change in the provider's generation
+        interpolator.sample_provider().offset.set(2.0);
+        interpolator.sample_provider().generation.set(1);
+        assert(
+            before + 2.0,
+            interpolator
+                .get_interpolated_sample("test", 100.5, 1.0)
+                .unwrap(),
+            "The transform wasn't invalidated by the provider's generation",
+        );
+
+        interpolator.sample_provider().offset.set(3.0);
+        interpolator.sample_provider().generation.set(2);
+        assert(
+            before + 3.0,
+            interpolator
+                .get_interpolated_sample_mut("test", 100.5, 1.0)
+                .unwrap(),
+            "get_interpolated_sample_mut didn't check the provider's generation",
+        );
+
+        // CachingSampleProvider discards its samples when the wrapped provider's generation changes
+        let caching_sample_provider = CachingSampleProvider::new(
+            SwitchableSampleProvider {
+                offset: Cell::new(0.0),
+                generation: Cell::new(0),
+            },
+            4,
+        );
+        let sample = caching_sample_provider.get_sample("test", 2).unwrap();
+        caching_sample_provider.sample_provider().offset.set(1.0);
+        assert_eq!(
+            sample,
+            caching_sample_provider.get_sample("test", 2).unwrap()
+        );
+        caching_sample_provider.sample_provider().generation.set(1);
+        assert_eq!(1, caching_sample_provider.generation());
+        assert_eq!(
+            sample + 1.0,
+            caching_sample_provider.get_sample("test", 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn get_interpolated_with_nearest() {
+        let interpolator = Interpolator::new(
+            20,
+            200,
+            CountingSampleProvider {
+                reads: Cell::new(0),
+            },
+        );
+
+        for (index, nearest_index) in [(100.25, 100), (100.5, 101), (100.75, 101)] {
+            let (interpolated, nearest) = interpolator
+                .get_interpolated_with_nearest("test", index, 1.0)
+                .unwrap();
+            assert_eq!(
+                interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                interpolated
+            );
+            assert_eq!(get_signal_sample(nearest_index as f32), nearest);
+        }
+
+        // A whole-sample index is only read once
+        let reads = interpolator.sample_provider().reads.get();
+        let (interpolated, nearest) = interpolator
+            .get_interpolated_with_nearest("test", 50.0, 1.0)
+            .unwrap();
+        assert_eq!(get_signal_sample(50.0), interpolated);
+        assert_eq!(interpolated, nearest);
+        assert_eq!(reads + 1, interpolator.sample_provider().reads.get());
+
+        // Nearest samples outside of the signal follow the boundary mode
+        let (_, nearest) = interpolator
+            .get_interpolated_with_nearest("test", -0.75, 1.0)
+            .unwrap();
+        assert_eq!(0.0, nearest);
+    }
+
+    #[test]
+    fn oversampling_is_continuous() {
+        let interpolator = Interpolator::new(
+            20,
+            200,
+            VecSampleProvider {
+                samples: (0..200).map(|index| (index as f32 * 2.0).sin()).collect(),
+            },
+        );
+
+        // Sweep across the start of the filter and two powers of two. Without blending, the output would step
+        // where the number of points doubles
+        for center_speed in [1.0, 2.0, 4.0] {
+            let mut previous = interpolator
+                .get_interpolated_sample("test", 100.3, center_speed - 0.05)
+                .unwrap();
+            for step in 1..=100 {
+                let relative_speed = center_speed - 0.05 + step as f32 * 0.001;
+                let sample = interpolator
+                    .get_interpolated_sample("test", 100.3, relative_speed)
+                    .unwrap();
+                assert!(
+                    (sample - previous).abs() < 0.01,
+                    "The output steps from {} to {} at relative_speed {}",
+                    previous,
+                    sample,
+                    relative_speed
+                );
+
+                previous = sample;
+            }
+        }
+    }
+
+    struct PrefetchingSampleProvider {
+        prefetched: RefCell<Vec<(usize, usize)>>,
+    }
+
+    impl SampleProvider<&str, Error> for PrefetchingSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> Result<f32> {
+            assert!(channel_id.eq("test"));
+
+            Ok(get_signal_sample(index as f32))
+        }
+
+        fn prefetch(&self, channel_id: &str, start: usize, len: usize) -> Result<()> {
+            assert!(channel_id.eq("test"));
+
+            self.prefetched.borrow_mut().push((start, len));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn prefetch() {
+        let interpolator = Interpolator::new(
+            20,
+            200,
+            CachingSampleProvider::new(
+                PrefetchingSampleProvider {
+                    prefetched: RefCell::new(Vec::new()),
+                },
+                64,
+            ),
+        );
+
+        interpolator
+            .get_interpolated_sample("test", 100.5, 1.0)
+            .unwrap();
+        // The cached transform is reused, so the window isn't prefetched again
+        interpolator
+            .get_interpolated_sample("test", 100.75, 1.0)
+            .unwrap();
+        // Only the part of the window that's within the signal is prefetched
+        interpolator
+            .get_interpolated_sample("test", 2.5, 1.0)
+            .unwrap();
+        interpolator
+            .get_interpolated_sample("test", 195.5, 1.0)
+            .unwrap();
+
+        assert_eq!(
+            vec![(90, 20), (0, 12), (185, 15)],
+            *interpolator
+                .sample_provider()
+                .sample_provider()
+                .prefetched
+                .borrow()
+        );
+    }
+
+    #[test]
+    fn upsample_integer() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 10.0,
+        };
+        let samples: Vec<f32> = (0..256)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
+        let interpolator = Interpolator::new(64, samples.len(), VecSampleProvider { samples });
+
+        let upsampled = interpolator.upsample_integer("test", 4).unwrap();
+        assert_eq!(1024, upsampled.len());
+
+        for (output_index, sample) in upsampled.iter().enumerate().take(600).skip(400) {
+            let index = output_index as f32 / 4.0;
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                *sample,
+                &format!("Wrong upsampled sample at {}", index),
+            );
+        }
+
+        // Zero-stuffing would put images of the tone at 0.25 +- 0.025 cycles per output sample. Measure the
+        // energy above the original Nyquist frequency, (0.125 cycles per output sample,) away from the edges
+        let middle = &upsampled[256..768];
+        let coefficients = WindowFunction::Hann.coefficients(middle.len());
+        let mut spectrum: Vec<Complex32> = middle
+            .iter()
+            .zip(coefficients)
+            .map(|(sample, coefficient)| Complex32::new(sample * coefficient, 0.0))
+            .collect();
+        FftPlanner::new()
+            .plan_fft_forward(middle.len())
+            .process(&mut spectrum);
+
+        let energies: Vec<f32> = spectrum
+            .iter()
+            .take(middle.len() / 2 + 1)
+            .map(|freq| freq.norm_sqr())
+            .collect();
+        let total_energy: f32 = energies.iter().sum();
+        let image_energy: f32 = energies.iter().skip(middle.len() / 8 + 4).sum();
+        assert!(
+            image_energy / total_energy < 0.0001,
+            "Too much energy above the original Nyquist frequency: {}",
+            image_energy / total_energy
+        );
+
+        // A factor of 1 returns the signal
+        let same = interpolator.upsample_integer("test", 1).unwrap();
+        for (index, sample) in same.iter().enumerate() {
+            assert(
+                sine_signal_provider.get_sine_signal_sample(index as f32),
+                *sample,
+                &format!("Wrong sample at {}", index),
+            );
+        }
+    }
+
+    #[test]
+    fn windowed_sinc() {
+        let fourier_interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        let windowed_sinc_interpolator = InterpolatorBuilder::new(120, 2000)
+            .interpolation_mode(InterpolationMode::WindowedSinc {
+                taps: 32,
+                beta: 8.0,
+            })
+            .build(SignalSampleProvider {});
+
+        // Whole samples are read directly
+        assert_eq!(
+            get_signal_sample(700.0),
+            windowed_sinc_interpolator
+                .get_interpolated_sample("test", 700.0, 1.0)
+                .unwrap()
+        );
+
+        let mut fourier_error: f32 = 0.0;
+        let mut windowed_sinc_error: f32 = 0.0;
+        let mut x = 500.0;
+        while x <= 1500.0 {
+            let expected_sample = get_signal_sample(x);
+            fourier_error = fourier_error.max(
+                (expected_sample
+                    - fourier_interpolator
+                        .get_interpolated_sample("test", x, 1.0)
+                        .unwrap())
+                .abs(),
+            );
+            windowed_sinc_error = windowed_sinc_error.max(
+                (expected_sample
+                    - windowed_sinc_interpolator
+                        .get_interpolated_sample("test", x, 1.0)
+                        .unwrap())
+                .abs(),
+            );
+
+            x += 0.37;
+        }
+
+        assert!(fourier_error < 0.001, "Fourier error: {}", fourier_error);
+        assert!(
+            windowed_sinc_error < 0.001,
+            "Windowed sinc error: {}",
+            windowed_sinc_error
+        );
+    }
+
+    #[test]
+    fn windowed_sinc_unity_dc_gain() {
+        // Few taps and a low beta truncate the kernel the most
+        for (taps, beta) in [(2, 0.0), (5, 2.0), (32, 8.0)] {
+            let interpolator = InterpolatorBuilder::new(120, 2000)
+                .interpolation_mode(InterpolationMode::WindowedSinc { taps, beta })
+                .build(VecSampleProvider {
+                    samples: vec![0.5; 2000],
+                });
+
+            for index in [500.1, 500.5, 500.9] {
+                assert(
+                    0.5,
+                    interpolator
+                        .get_interpolated_sample("test", index, 1.0)
+                        .unwrap(),
+                    &format!("Wrong DC gain with {taps} taps at {index}"),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn windowed_sinc_odd_taps_centered() {
+        // An impulse is read back the same at the same distance on either side of it, so the kernel is centered
+        // on the index, not on the sample before it
+        let mut samples = vec![0.0; 200];
+        samples[100] = 1.0;
+        for taps in [5, 7, 8] {
+            let interpolator = InterpolatorBuilder::new(120, samples.len())
+                .interpolation_mode(InterpolationMode::WindowedSinc { taps, beta: 5.0 })
+                .build(VecSampleProvider {
+                    samples: samples.clone(),
+                });
+
+            for distance in [0.3, 0.7] {
+                let after = interpolator
+                    .get_interpolated_sample("test", 100.0 + distance, 1.0)
+                    .unwrap();
+                let before = interpolator
+                    .get_interpolated_sample("test", 100.0 - distance, 1.0)
+                    .unwrap();
+                assert(
+                    after,
+                    before,
+                    &format!("The kernel isn't centered with {taps} taps at {distance}"),
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "WindowedSinc needs at least 2 taps")]
+    fn windowed_sinc_too_few_taps() {
+        InterpolatorBuilder::new(120, 2000)
+            .interpolation_mode(InterpolationMode::WindowedSinc { taps: 1, beta: 8.0 })
+            .build(SignalSampleProvider {});
+    }
+
+    // A sum of complex exponentials, with one negative frequency, so the signal isn't conjugate-symmetric. Both
+    // frequencies fit evenly in a window of 120 samples, so there's no spectral leakage
+    struct IqSampleProvider {}
+
+    fn get_iq_sample(x: f32) -> Complex32 {
+        Complex32::from_polar(0.5, 2.0 * PI * 0.125 * x)
+            + Complex32::from_polar(0.25, -2.0 * PI * 0.075 * x)
+    }
+
+    impl SampleProvider<&str, Error> for IqSampleProvider {
+        fn get_sample(&self, _channel_id: &str, index: usize) -> Result<f32> {
+            Ok(get_iq_sample(index as f32).re)
+        }
+    }
+
+    impl ComplexSampleProvider<&str, Error> for IqSampleProvider {
+        fn get_complex_sample(&self, _channel_id: &str, index: usize) -> Result<Complex32> {
+            Ok(get_iq_sample(index as f32))
+        }
+    }
+
+    impl ComplexSampleProvider<&str, Error> for SignalSampleProvider {
+        fn get_complex_sample(&self, channel_id: &str, index: usize) -> Result<Complex32> {
+            Ok(Complex32::new(self.get_sample(channel_id, index)?, 0.0))
+        }
+    }
+
+    #[test]
+    fn complex_signal() {
+        let interpolator = Interpolator::new(120, 2000, IqSampleProvider {});
+
+        let mut x = 500.0;
+        while x <= 600.0 {
+            let expected_sample = get_iq_sample(x);
+            let actual_sample = interpolator
+                .get_interpolated_complex_sample("test", x, 1.0)
+                .unwrap();
+
+            assert(
+                expected_sample.re,
+                actual_sample.re,
+                &format!("Wrong real part at index {}", x),
+            );
+            assert(
+                expected_sample.im,
+                actual_sample.im,
+                &format!("Wrong imaginary part at index {}", x),
+            );
+
+            x += 0.13;
+        }
+
+        // A real signal interpolates the same as a complex signal as it does as a real signal
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        for index in [500.25, 750.5, 1000.75] {
+            let complex_sample = interpolator
+                .get_interpolated_complex_sample("test", index, 1.0)
+                .unwrap();
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap(),
+                complex_sample.re,
+                &format!("Real signal interpolated differently at {}", index),
+            );
+            assert(0.0, complex_sample.im, "Real signal has an imaginary part");
+
+            let complex_sample = interpolator
+                .get_interpolated_complex_sample("test", index, 2.5)
+                .unwrap();
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", index, 2.5)
+                    .unwrap(),
+                complex_sample.re,
+                &format!("Real signal downsampled differently at {}", index),
+            );
+        }
+    }
+
+    // resample_all of 256 samples of get_signal_sample, with a window of 64 samples: Outputs 100 through 131 at a
+    // ratio of 0.75, and outputs 40 through 71 at a ratio of 7 / 3, (which uses the anti-aliasing filter)
+    const GOLDEN_UPSAMPLED: [f32; 32] = [
+        -0.13895912,
+        -0.0435855,
+        0.017848462,
+        -0.008753777,
+        -0.120405555,
+        -0.25539622,
+        -0.32498693,
+        -0.2656157,
+        -0.07827897,
+        0.16519794,
+        0.3514819,
+        0.38181758,
+        0.2223928,
+        -0.077277415,
+        -0.40584838,
+        -0.64118856,
+        -0.7075337,
+        -0.60619867,
+        -0.40782094,
+        -0.21120721,
+        -0.08983808,
+        -0.057127126,
+        -0.067153856,
+        -0.047060236,
+        0.05428298,
+        0.23204266,
+        0.4249857,
+        0.5475038,
+        0.5389662,
+        0.4010925,
+        0.2047379,
+        0.05573771,
+    ];
+    const GOLDEN_DOWNSAMPLED: [f32; 32] = [
+        0.14406246,
+        0.51615226,
+        0.12904316,
+        0.3747102,
+        0.6377436,
+        -0.17036621,
+        -0.3903316,
+        -0.4444684,
+        -0.31515196,
+        0.21224107,
+        -0.22558196,
+        -0.20534113,
+        0.32896644,
+        -0.00475341,
+        -0.050843522,
+        -0.14323059,
+        -0.055018328,
+        0.7196474,
+        0.38220856,
+        -0.041431405,
+        0.15313746,
+        -0.20566508,
+        -0.2615939,
+        -0.5180617,
+        -0.72984165,
+        0.19316202,
+        0.35672677,
+        0.07598467,
+        0.2544272,
+        0.036793135,
+        0.264274,
+        0.17084691,
+    ];
+
+    #[test]
+    fn golden_vectors() {
+        let samples: Vec<f32> = (0..256)
+            .map(|index| get_signal_sample(index as f32))
+            .collect();
+        let interpolator = Interpolator::new(64, samples.len(), VecSampleProvider { samples });
+
+        for (ratio, first_output_index, golden) in [
+            (0.75, 100, GOLDEN_UPSAMPLED),
+            (7.0 / 3.0, 40, GOLDEN_DOWNSAMPLED),
+        ] {
+            let resampled = interpolator.resample_all("test", ratio).unwrap();
+            for (golden_index, expected_sample) in golden.iter().enumerate() {
+                let actual_sample = resampled[first_output_index + golden_index];
+                assert!(
+                    (expected_sample - actual_sample).abs() <= REPRODUCIBILITY_TOLERANCE,
+                    "Output {} at ratio {} changed: Expected: {}, Actual: {}",
+                    first_output_index + golden_index,
+                    ratio,
+                    expected_sample,
+                    actual_sample
+                );
+            }
+        }
+
+        // The upsampled references are the signal itself
+        for (golden_index, expected_sample) in GOLDEN_UPSAMPLED.iter().enumerate() {
+            assert(
+                get_signal_sample((100 + golden_index) as f32 * 0.75),
+                *expected_sample,
+                &format!("Golden vector {} is wrong", golden_index),
+            );
+        }
+    }
+
+    #[test]
+    fn get_interpolated_range() {
+        let interpolator = InterpolatorBuilder::new(32, 2000)
+            .fade_out_tail(true)
+            .build(CountingSampleProvider {
+                reads: Cell::new(0),
+            });
+
+        // Each window is read once, and whole-sample indexes are read directly
+        let range = interpolator
+            .get_interpolated_range("test", 500.25, 0.25, 40, 1.0)
+            .unwrap();
+        assert_eq!(40, range.len());
+        assert_eq!(10 * 32 + 10, interpolator.sample_provider().reads.get());
+
+        for (start, step, relative_speed) in
+            [(500.25, 0.25, 1.0), (1990.1, 0.3, 1.0), (700.0, 2.5, 2.5)]
+        {
+            let range = interpolator
+                .get_interpolated_range("test", start, step, 30, relative_speed)
+                .unwrap();
+            for (output_index, sample) in range.iter().enumerate() {
+                let index = start + output_index as f32 * step;
+                assert_eq!(
+                    interpolator
+                        .get_interpolated_sample("test", index, relative_speed)
+                        .unwrap(),
+                    *sample,
+                    "Wrong sample at {}",
+                    index
+                );
+            }
+        }
+
+        assert!(interpolator
+            .get_interpolated_range("test", 500.0, 1.0, 0, 1.0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn window_exceeds_signal() {
+        assert!(!Interpolator::new(64, 64, SignalSampleProvider {}).window_exceeds_signal());
+        assert!(Interpolator::new(65, 64, SignalSampleProvider {}).window_exceeds_signal());
+    }
+
+    #[test]
+    fn get_analytic_sample() {
+        // The analytic signal of a cosine is a complex exponential. 8 samples fit evenly in the window
+        let interpolator = Interpolator::new(
+            64,
+            2000,
+            SineSignalProvider {
+                wavelength_in_samples: 8.0,
+            },
+        );
+
+        let mut x = 500.0;
+        while x <= 510.0 {
+            let analytic_sample = interpolator.get_analytic_sample("test", x).unwrap();
+            let phase = 2.0 * PI * x / 8.0;
+
+            assert(
+                phase.cos(),
+                analytic_sample.re,
+                &format!("Wrong real part at {}", x),
+            );
+            assert(
+                phase.sin(),
+                analytic_sample.im,
+                &format!("Wrong imaginary part at {}", x),
+            );
+            assert(
+                1.0,
+                analytic_sample.norm(),
+                &format!("Wrong amplitude at {}", x),
+            );
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", x, 1.0)
+                    .unwrap(),
+                analytic_sample.re,
+                &format!("Real part isn't the interpolated sample at {}", x),
+            );
+
+            x += 0.15;
+        }
+    }
+
+    #[test]
+    fn boxed_sample_provider() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
+
+        let sample_providers: Vec<Box<dyn SampleProvider<&str, Error>>> = vec![
+            Box::new(sine_signal_provider),
+            Box::new(VecSampleProvider { samples }),
+        ];
+        let interpolators: Vec<_> = sample_providers
+            .into_iter()
+            .map(|sample_provider| Interpolator::new(64, 2000, sample_provider))
+            .collect();
+
+        for interpolator in interpolators.iter() {
+            assert(
+                SineSignalProvider {
+                    wavelength_in_samples: 40.0,
+                }
+                .get_sine_signal_sample(500.25),
+                interpolator
+                    .get_interpolated_sample("test", 500.25, 1.0)
+                    .unwrap(),
+                "Wrong sample from a boxed provider",
+            );
+        }
+    }
+
+    #[test]
+    fn get_interpolated_sample_at_time() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        // 0.0125 seconds at 44100 is sample 551.25
+        assert_eq!(
+            interpolator
+                .get_interpolated_sample("test", 551.25, 1.0)
+                .unwrap(),
+            interpolator
+                .get_interpolated_sample_at_time("test", 0.0125, 44100, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn get_interpolated_derivative() {
+        // 16 samples fit evenly in the window
+        let interpolator = Interpolator::new(
+            64,
+            2000,
+            SineSignalProvider {
+                wavelength_in_samples: 16.0,
+            },
+        );
+        let angular_frequency = 2.0 * PI / 16.0;
+
+        let mut x = 500.0;
+        while x <= 520.0 {
+            let phase = angular_frequency * x;
+
+            assert(
+                interpolator
+                    .get_interpolated_sample("test", x, 1.0)
+                    .unwrap(),
+                interpolator
+                    .get_interpolated_derivative("test", x, 0)
+                    .unwrap(),
+                &format!("Wrong 0th derivative at {}", x),
+            );
+            assert(
+                -angular_frequency * phase.sin(),
+                interpolator
+                    .get_interpolated_derivative("test", x, 1)
+                    .unwrap(),
+                &format!("Wrong 1st derivative at {}", x),
+            );
+            assert(
+                -angular_frequency * angular_frequency * phase.cos(),
+                interpolator
+                    .get_interpolated_derivative("test", x, 2)
+                    .unwrap(),
+                &format!("Wrong 2nd derivative at {}", x),
+            );
+
+            x += 0.35;
+        }
+    }
+
+    #[test]
+    fn window_offset() {
+        let window_size = 64;
+        let causal_window_offset = window_size - 2 - window_size / 2;
+        let interpolator = InterpolatorBuilder::new(window_size, 2000)
+            .window_offset(causal_window_offset)
+            .build(CountingSampleProvider {
+                reads: Cell::new(0),
+            });
+        assert_eq!(causal_window_offset, interpolator.config().window_offset);
+
+        // The window ends at the sample after the index
+        let window = interpolator.get_window("test", 500).unwrap();
+        assert_eq!(get_signal_sample(501.0), window[window_size - 1]);
+        assert_eq!(get_signal_sample(438.0), window[0]);
+
+        // The causal window still reconstructs the signal, but less accurately than a centered window
+        let centered_interpolator = Interpolator::new(window_size, 2000, SignalSampleProvider {});
+        let mut causal_error: f32 = 0.0;
+        let mut centered_error: f32 = 0.0;
+        let mut x = 500.0;
+        while x <= 600.0 {
+            let expected_sample = get_signal_sample(x);
+            causal_error = causal_error.max(
+                (expected_sample
+                    - interpolator
+                        .get_interpolated_sample("test", x, 1.0)
+                        .unwrap())
+                .abs(),
+            );
+            centered_error = centered_error.max(
+                (expected_sample
+                    - centered_interpolator
+                        .get_interpolated_sample("test", x, 1.0)
+                        .unwrap())
+                .abs(),
+            );
+
+            x += 0.1;
+        }
+
+        assert!(
+            causal_error < 0.2,
+            "Causal window is too inaccurate: {}",
+            causal_error
+        );
+        assert!(
+            centered_error < causal_error,
+            "Centered error {} isn't less than causal error {}",
+            centered_error,
+            causal_error
+        );
+
+        // Whole samples are still exact, and every path agrees on where the index is
+        assert_eq!(
+            get_signal_sample(550.0),
+            interpolator
+                .get_interpolated_sample("test", 550.0, 1.0)
+                .unwrap()
+        );
+        let sample = interpolator
+            .get_interpolated_sample("test", 550.25, 1.0)
+            .unwrap();
+        assert(
+            sample,
+            interpolator
+                .get_interpolated_range("test", 550.25, 1.0, 1, 1.0)
+                .unwrap()[0],
+            "get_interpolated_range disagrees",
+        );
+        assert(
+            sample,
+            interpolator
+                .get_interpolated_frame(&["test"], 550.25, 1.0)
+                .unwrap()[0],
+            "get_interpolated_frame disagrees",
+        );
+        assert(
+            get_signal_sample(550.0),
+            interpolator.downsample_integer("test", 1).unwrap()[550],
+            "downsample_integer disagrees",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window_offset can be at most")]
+    fn window_offset_too_large() {
+        InterpolatorBuilder::new(64, 2000)
+            .window_offset(31)
+            .build(SignalSampleProvider {});
+    }
+
+    #[test]
+    fn frequency_response() {
+        let interpolator = Interpolator::new(64, 2000, SignalSampleProvider {});
+
+        // Frequencies 0.0, 0.05, ... 0.5 cycles per sample. The passband is flat, except for spectral leakage
+        // from frequencies that don't fit evenly in the window
+        let response = interpolator.frequency_response(1.0, 11);
+        assert_eq!(11, response.len());
+        for (point, gain) in response.iter().enumerate().take(9) {
+            assert!(
+                (1.0 - gain).abs() < 0.01,
+                "Wrong gain at point {}: {}",
+                point,
+                gain
+            );
+        }
+
+        // At a relative_speed of 4.0, the anti-aliasing filter averages 4 points, one sample apart
+        let response = interpolator.frequency_response(4.0, 11);
+        for (point, gain) in response.iter().enumerate().skip(1) {
+            let frequency = 0.05 * point as f32;
+            let expected_gain =
+                ((4.0 * PI * frequency).sin() / (4.0 * (PI * frequency).sin())).abs();
+            assert(
+                expected_gain,
+                *gain,
+                &format!("Wrong anti-aliased gain at {} cycles per sample", frequency),
+            );
+        }
+        assert(1.0, response[0], "Wrong anti-aliased gain at DC");
+        assert_eq!(0.0, interpolator.max_overshoot());
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn mmap_sample_provider() {
+        use mmap_sample_provider::{Endianness, MmapSampleProvider};
+
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| sine_signal_provider.get_sine_signal_sample(index as f32))
+            .collect();
+
+        // A raw, big-endian, stereo file with a 10 byte header. The right channel is inverted
+        let mut bytes = vec![0xAA; 10];
+        for sample in samples.iter() {
+            bytes.extend_from_slice(&sample.to_be_bytes());
+            bytes.extend_from_slice(&(-sample).to_be_bytes());
+        }
+
+        let path = std::env::temp_dir().join("index_signal_mmap_sample_provider.raw");
+        fs::write(&path, &bytes).unwrap();
+        let file = fs::File::open(&path).unwrap();
+        let sample_provider = MmapSampleProvider::new(&file, 10, 2, Endianness::Big).unwrap();
+        assert_eq!(samples.len(), sample_provider.num_frames());
+
+        for index in [0, 1, 999, 1999] {
+            assert_eq!(
+                samples[index],
+                sample_provider.get_sample(0, index).unwrap()
+            );
+            assert_eq!(
+                -samples[index],
+                sample_provider.get_sample(1, index).unwrap()
+            );
+        }
+
+        assert_eq!(
+            None,
+            sample_provider.get_sample_if_available(0, 2000).unwrap()
+        );
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            sample_provider.get_sample(2, 0).unwrap_err().kind()
+        );
+
+        let interpolator = Interpolator::new(120, samples.len(), sample_provider);
+        assert(
+            -sine_signal_provider.get_sine_signal_sample(1000.5),
+            interpolator
+                .get_interpolated_sample(1, 1000.5, 1.0)
+                .unwrap(),
+            "Wrong interpolated sample from a memory-mapped file",
+        );
+
+        // A mono, little-endian, 32-bit float wav file, with a chunk before the data chunk
+        let data_len = (samples.len() * 4) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(46 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&3u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&44100u32.to_le_bytes());
+        bytes.extend_from_slice(&(44100u32 * 4).to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&32u16.to_le_bytes());
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples.iter() {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        let path = std::env::temp_dir().join("index_signal_mmap_sample_provider.wav");
+        fs::write(&path, &bytes).unwrap();
+        let file = fs::File::open(&path).unwrap();
+        let sample_provider = MmapSampleProvider::from_wav(&file).unwrap();
+        assert_eq!(1, sample_provider.num_channels());
+        assert_eq!(Endianness::Little, sample_provider.endianness());
+        assert_eq!(samples.len(), sample_provider.num_frames());
+        assert_eq!(samples[1234], sample_provider.get_sample(0, 1234).unwrap());
+
+        // A 16-bit wav file isn't supported
+        bytes[20] = 1;
+        fs::write(&path, &bytes).unwrap();
+        let file = fs::File::open(&path).unwrap();
+        assert_eq!(
+            ErrorKind::InvalidData,
+            MmapSampleProvider::from_wav(&file).err().unwrap().kind()
+        );
+
+        fs::remove_file(std::env::temp_dir().join("index_signal_mmap_sample_provider.raw"))
+            .unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn fixed_point_sample_provider() {
+        assert_eq!(-0.5, (-16384i16).to_f32());
+        assert_eq!(-1.0, I24::new(I24::MIN).to_f32());
+        assert_eq!(-1, I24::new(-1).value());
+        assert_eq!(I24::MAX, I24::new(I24::MAX).value());
+        assert_eq!(I24([0xFF, 0xFF, 0x7F]), I24::new(I24::MAX));
+
+        // Requantizing rounds to the nearest integer, and saturates
+        assert_eq!(16384i16, i16::from_f32(16384.4 / 32768.0));
+        assert_eq!(16385i16, i16::from_f32(16384.6 / 32768.0));
+        assert_eq!(i16::MAX, i16::from_f32(1.5));
+        assert_eq!(i16::MIN, i16::from_f32(-1.5));
+        assert_eq!(I24::MIN, I24::from_f32(-2.0).value());
+        for value in [I24::MIN, -12345, 0, 12345, I24::MAX] {
+            assert_eq!(value, I24::from_f32(I24::new(value).to_f32()).value());
+        }
+
+        // Stereo, with the right channel inverted
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let samples: Vec<I24> = (0..2000)
+            .flat_map(|index| {
+                let sample = sine_signal_provider.get_sine_signal_sample(index as f32) / 2.0;
+                [I24::from_f32(sample), I24::from_f32(-sample)]
+            })
+            .collect();
+
+        let sample_provider = FixedPointSampleProvider::new(samples.clone(), 2);
+        assert_eq!(2000, sample_provider.num_frames());
+        assert_eq!(
+            samples[21].to_f32(),
+            sample_provider.get_sample(1, 10).unwrap()
+        );
+        assert_eq!(
+            None,
+            sample_provider.get_sample_if_available(0, 2000).unwrap()
+        );
+        assert_eq!(
+            std::io::ErrorKind::InvalidInput,
+            sample_provider.get_sample(2, 10).unwrap_err().kind()
+        );
+        assert_eq!(
+            std::io::ErrorKind::UnexpectedEof,
+            sample_provider.get_sample(0, 2000).unwrap_err().kind()
+        );
+
+        let interpolator = Interpolator::new(120, 2000, sample_provider);
+        assert(
+            -sine_signal_provider.get_sine_signal_sample(1000.5) / 2.0,
+            interpolator
+                .get_interpolated_sample(1, 1000.5, 1.0)
+                .unwrap(),
+            "Wrong interpolated sample from 24-bit samples",
+        );
+
+        // Resampling whole samples reproduces the input exactly
+        let resampled: Vec<I24> = interpolator.resample_all_fixed_point(0, 1.0).unwrap();
+        assert_eq!(2000, resampled.len());
+        for index in [0, 1, 1000, 1999] {
+            assert_eq!(samples[index * 2], resampled[index]);
+        }
+
+        let resampled: Vec<i16> = interpolator.resample_all_fixed_point(1, 2.0).unwrap();
+        assert_eq!(1000, resampled.len());
+        assert_eq!(
+            i16::from_f32(
+                interpolator
+                    .get_interpolated_sample(1, 1000.0, 2.0)
+                    .unwrap()
+            ),
+            resampled[500]
+        );
+    }
+
+    #[test]
+    fn nearest_and_hold() {
+        for (interpolation_mode, expected_index) in [
+            (InterpolationMode::Nearest, 101),
+            (InterpolationMode::Hold, 100),
+        ] {
+            let interpolator = InterpolatorBuilder::new(20, 200)
+                .interpolation_mode(interpolation_mode)
+                .build(CountingSampleProvider {
+                    reads: Cell::new(0),
+                });
+
+            // Only one sample is read, and relative_speed doesn't filter
+            assert_eq!(
+                get_signal_sample(expected_index as f32),
+                interpolator
+                    .get_interpolated_sample("test", 100.75, 3.0)
+                    .unwrap()
+            );
+            assert_eq!(1, interpolator.sample_provider().reads.get());
+            assert_eq!(
+                get_signal_sample(100.0),
+                interpolator
+                    .get_interpolated_sample("test", 100.25, 1.0)
+                    .unwrap()
+            );
+        }
+
+        // Boundary handling is shared with the other modes
+        let interpolator = InterpolatorBuilder::new(20, 10)
+            .interpolation_mode(InterpolationMode::Nearest)
+            .boundary_mode(BoundaryMode::Mirror)
+            .build(VecSampleProvider {
+                samples: (0..10).map(|index| index as f32).collect(),
+            });
+        assert_eq!(
+            1.0,
+            interpolator
+                .get_interpolated_sample("test", -0.75, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            8.0,
+            interpolator
+                .get_interpolated_sample("test", 9.75, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn is_cached() {
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+        assert!(!interpolator.is_cached("test", 100));
+
+        interpolator
+            .get_interpolated_sample("test", 100.5, 1.0)
+            .unwrap();
+        assert!(interpolator.is_cached("test", 100));
+        assert!(!interpolator.is_cached("test", 101));
+        assert!(!interpolator.is_cached("other", 100));
+
+        interpolator.bump_generation();
+        assert!(!interpolator.is_cached("test", 100));
+
+        interpolator
+            .get_interpolated_sample("test", 100.5, 1.0)
+            .unwrap();
+        assert!(interpolator.is_cached("test", 100));
+        interpolator.invalidate_channel("test");
+        assert!(!interpolator.is_cached("test", 100));
+    }
+
+    #[test]
+    fn channel_num_samples() {
+        struct TwoChannelSampleProvider {}
+
+        impl SampleProvider<&str, Error> for TwoChannelSampleProvider {
+            fn get_sample(&self, _channel_id: &str, index: usize) -> Result<f32> {
+                Ok(get_signal_sample(index as f32))
+            }
+        }
+
+        let mut interpolator = InterpolatorBuilder::new(20, 200)
+            .fade_out_tail(true)
+            .build(TwoChannelSampleProvider {});
+        interpolator.set_channel_num_samples("transient", 100);
+        assert_eq!(100, interpolator.channel_num_samples("transient"));
+        assert_eq!(200, interpolator.channel_num_samples("sustain"));
+
+        // Each channel's window is zero-padded past its own end
+        let mut window = interpolator.get_window("sustain", 100).unwrap();
+        assert!(window[15..].iter().all(|sample| *sample != 0.0));
+        window = interpolator.get_window("transient", 100).unwrap();
+        assert!(window[10..].iter().all(|sample| *sample == 0.0));
+        assert_eq!(get_signal_sample(99.0), window[9]);
+
+        assert_eq!(
+            200,
+            interpolator.resample_all("sustain", 1.0).unwrap().len()
+        );
+        assert_eq!(
+            50,
+            interpolator.resample_all("transient", 2.0).unwrap().len()
+        );
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample("transient", 100.5, 1.0)
+                .unwrap()
+        );
+        assert!(
+            interpolator
+                .get_interpolated_sample("sustain", 100.5, 1.0)
+                .unwrap()
+                != 0.0
+        );
+
+        // Each channel's tail fades out before its own end
+        assert(
+            get_signal_sample(150.0),
+            interpolator
+                .get_interpolated_sample("sustain", 150.0, 1.0)
+                .unwrap(),
+            "Sustain shouldn't fade before its own tail",
+        );
+        assert(
+            get_signal_sample(90.0) * 0.5,
+            interpolator
+                .get_interpolated_sample("transient", 90.0, 1.0)
+                .unwrap(),
+            "Transient should be halfway through its fade",
+        );
+    }
+
+    #[test]
+    fn resample_fused() {
+        // As in downsample_integer, the tones fit in the window evenly
+        let below_nyquist = SineSignalProvider {
+            wavelength_in_samples: 128.0 / 16.0,
+        };
+        let above_nyquist = SineSignalProvider {
+            wavelength_in_samples: 128.0 / 40.0,
+        };
+
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| {
+                below_nyquist.get_sine_signal_sample(index as f32)
+                    + above_nyquist.get_sine_signal_sample(index as f32)
+            })
+            .collect();
+        let interpolator = Interpolator::new(128, samples.len(), VecSampleProvider { samples });
+
+        // An integer ratio is the same as downsample_integer
+        let fused = interpolator.resample_fused("test", 2.0).unwrap();
+        let downsampled = interpolator.downsample_integer("test", 2).unwrap();
+        assert_eq!(downsampled.len(), fused.len());
+        for (output_index, (expected, actual)) in downsampled.iter().zip(fused.iter()).enumerate() {
+            assert(
+                *expected,
+                *actual,
+                &format!("Wrong fused sample at {}", output_index),
+            );
+        }
+
+        // With a ratio of 2.5, the new Nyquist frequency is frequency 25, so only the tone below it remains
+        let fused = interpolator.resample_fused("test", 2.5).unwrap();
+        assert_eq!(800, fused.len());
+        for (output_index, actual_sample) in fused.iter().enumerate().skip(52).take(696) {
+            assert(
+                below_nyquist.get_sine_signal_sample(output_index as f32 * 2.5),
+                *actual_sample,
+                &format!("Wrong fused sample at {}", output_index),
+            );
+        }
+
+        // Ratios of 1.0 or less aren't filtered
+        assert_eq!(
+            interpolator.resample_all("test", 0.75).unwrap(),
+            interpolator.resample_fused("test", 0.75).unwrap()
+        );
+    }
+
+    #[test]
+    fn non_finite_samples() {
+        let mut samples: Vec<f32> = (0..200)
+            .map(|index| get_signal_sample(index as f32))
+            .collect();
+        samples[100] = f32::NAN;
+        samples[101] = f32::INFINITY;
+
+        let interpolator = Interpolator::new(
+            20,
+            200,
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+        assert_eq!(
+            Some(100),
+            interpolator.find_non_finite("test", 0, 200).unwrap()
+        );
+        assert_eq!(
+            Some(101),
+            interpolator.find_non_finite("test", 101, 10).unwrap()
+        );
+        assert_eq!(
+            None,
+            interpolator.find_non_finite("test", 102, 500).unwrap()
+        );
+        assert_eq!(None, interpolator.find_non_finite("test", 300, 10).unwrap());
+
+        // A non-finite sample spreads to every window that contains it
+        assert!(interpolator
+            .get_interpolated_sample("test", 105.5, 1.0)
+            .unwrap()
+            .is_nan());
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .non_finite_samples(NonFiniteSamples::Zero)
+            .build(VecSampleProvider {
+                samples: samples.clone(),
+            });
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample("test", 100.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample("test", 101.0, 1.0)
+                .unwrap()
+        );
+        assert!(interpolator
+            .get_interpolated_sample("test", 105.5, 1.0)
+            .unwrap()
+            .is_finite());
+
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .non_finite_samples(NonFiniteSamples::Previous)
+            .build(VecSampleProvider {
+                samples: samples.clone(),
+            });
+        assert_eq!(
+            samples[99],
+            interpolator
+                .get_interpolated_sample("test", 100.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            samples[99],
+            interpolator
+                .get_interpolated_sample("test", 101.0, 1.0)
+                .unwrap()
+        );
+        assert!(interpolator
+            .get_interpolated_sample("test", 105.5, 1.0)
+            .unwrap()
+            .is_finite());
+        assert_eq!(
+            NonFiniteSamples::Previous,
+            interpolator.config().non_finite_samples
+        );
+
+        // Sanitizing doesn't change the samples that find_non_finite sees
+        assert_eq!(
+            Some(100),
+            interpolator.find_non_finite("test", 0, 200).unwrap()
+        );
+
+        // The search for a previous finite sample stops window_size samples back
+        samples[100..190].fill(f32::NAN);
+        let interpolator = InterpolatorBuilder::new(20, 200)
+            .non_finite_samples(NonFiniteSamples::Previous)
+            .build(VecSampleProvider {
+                samples: samples.clone(),
+            });
+        assert_eq!(
+            samples[99],
+            interpolator
+                .get_interpolated_sample("test", 119.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample("test", 120.0, 1.0)
+                .unwrap()
+        );
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_interpolated_sample("test", 180.0, 1.0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn accessed_indices() {
+        struct RecordingSampleProvider {
+            indices: RefCell<Vec<isize>>,
+        }
+
+        impl SampleProvider<&str, Error> for RecordingSampleProvider {
+            fn get_sample(&self, _channel_id: &str, index: usize) -> Result<f32> {
+                self.indices.borrow_mut().push(index as isize);
+                Ok(get_signal_sample(index as f32))
+            }
+        }
+
+        let configs = [
+            InterpolatorConfig::new(20, 200),
+            InterpolatorConfig {
+                evaluation_point: EvaluationPoint::FromLeftEdge(9.5),
+                window_offset: 3,
+                ..InterpolatorConfig::new(21, 200)
+            },
+            InterpolatorConfig {
+                interpolation_mode: InterpolationMode::CatmullRom,
+                ..InterpolatorConfig::new(20, 200)
+            },
+            InterpolatorConfig {
+                interpolation_mode: InterpolationMode::WindowedSinc { taps: 9, beta: 5.0 },
+                ..InterpolatorConfig::new(20, 200)
+            },
+        ];
+        let reads = [
+            (100.25, 1.0),
+            (100.0, 1.0),
+            (100.3, 2.5),
+            (100.3, 3.7),
+            (5.5, 1.0),
+            (0.0, 4.0),
+        ];
+
+        for config in configs {
+            for (index, relative_speed) in reads {
+                let interpolator = Interpolator::from_config(
+                    config.clone(),
+                    RecordingSampleProvider {
+                        indices: RefCell::new(Vec::new()),
+                    },
+                )
+                .unwrap();
+                let accessed_indices = interpolator.accessed_indices(index, relative_speed);
+                interpolator
+                    .get_interpolated_sample("test", index, relative_speed)
+                    .unwrap();
+
+                // Indexes before the start of the signal are padded with 0.0 instead of read
+                let mut read_indices = interpolator.sample_provider().indices.borrow().clone();
+                read_indices.sort_unstable();
+                read_indices.dedup();
+                let accessed_indices: Vec<isize> = accessed_indices
+                    .into_iter()
+                    .filter(|index| *index >= 0)
+                    .collect();
+                assert_eq!(
+                    read_indices, accessed_indices,
+                    "Wrong accessed indices at {} at speed {} with {:?}",
+                    index, relative_speed, config
+                );
+            }
+        }
+
+        let interpolator = Interpolator::new(20, 200, SignalSampleProvider {});
+        assert_eq!(vec![100], interpolator.accessed_indices(100.0, 1.0));
+        assert_eq!(
+            (-10..10).collect::<Vec<isize>>(),
+            interpolator.accessed_indices(0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn normalization() {
+        // A DC offset of 0.5, and a cosine that fits in the window 4 times
+        let samples: Vec<f32> = (0..200)
+            .map(|index| 0.5 + (2.0 * PI * 4.0 * (index as f32) / 32.0).cos())
+            .collect();
+
+        for (normalization, expected_gain) in [
+            (Normalization::Backward, 32.0),
+            (Normalization::Forward, 1.0),
+            (Normalization::Ortho, 32.0f32.sqrt()),
+        ] {
+            let interpolator = InterpolatorBuilder::new(32, 200)
+                .normalization(normalization)
+                .build(VecSampleProvider {
+                    samples: samples.clone(),
+                });
+            assert_eq!(normalization, interpolator.config().normalization);
+
+            // The window starts at a multiple of the cosine's wavelength, so it's in phase
+            let spectrum = interpolator.get_spectrum("test", 112).unwrap();
+            assert_eq!(32, spectrum.len());
+            assert(0.5 * expected_gain, spectrum[0].re, "Wrong DC frequency");
+            assert(0.5 * expected_gain, spectrum[4].re, "Wrong frequency 4");
+            assert(
+                0.5 * expected_gain,
+                spectrum[28].re,
+                "Wrong mirror of frequency 4",
+            );
+            assert(0.0, spectrum[5].norm(), "Wrong frequency 5");
+
+            // Reconstruction doesn't depend on the normalization
+            assert(
+                0.5 + (2.0 * PI * 4.0 * 100.25 / 32.0).cos(),
+                interpolator
+                    .get_interpolated_sample("test", 100.25, 1.0)
+                    .unwrap(),
+                "Wrong interpolated sample",
+            );
+        }
+    }
+
+    #[test]
+    fn get_envelope() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(20, 2000, sine_signal_provider);
+
+        // Over whole wavelengths, a sine's RMS is its peak / sqrt(2)
+        assert(
+            0.5f32.sqrt(),
+            interpolator
+                .get_envelope("test", 1000.0, 80, EnvelopeMode::Rms)
+                .unwrap(),
+            "Wrong RMS",
+        );
+        assert(
+            0.5f32.sqrt(),
+            interpolator
+                .get_envelope("test", 1000.3, 80, EnvelopeMode::Rms)
+                .unwrap(),
+            "Wrong RMS between samples",
+        );
+        assert_eq!(
+            1.0,
+            interpolator
+                .get_envelope("test", 1000.5, 40, EnvelopeMode::Peak)
+                .unwrap()
+        );
+
+        // Between whole-sample indexes, the envelope moves continuously from one window to the next
+        let samples = VecSampleProvider {
+            samples: (0..10)
+                .map(|index| if index < 5 { 0.0 } else { 1.0 })
+                .collect(),
+        };
+        let interpolator = Interpolator::new(20, 10, samples);
+        assert_eq!(
+            0.0,
+            interpolator
+                .get_envelope("test", 3.0, 4, EnvelopeMode::Peak)
+                .unwrap()
+        );
+        assert_eq!(
+            1.0,
+            interpolator
+                .get_envelope("test", 4.0, 4, EnvelopeMode::Peak)
+                .unwrap()
+        );
+        assert_eq!(
+            0.25,
+            interpolator
+                .get_envelope("test", 3.25, 4, EnvelopeMode::Peak)
+                .unwrap()
+        );
+        assert_eq!(
+            0.5,
+            interpolator
+                .get_envelope("test", 4.0, 4, EnvelopeMode::Rms)
+                .unwrap()
+        );
+        assert(
+            0.125f32.sqrt(),
+            interpolator
+                .get_envelope("test", 3.5, 4, EnvelopeMode::Rms)
+                .unwrap(),
+            "Wrong RMS between windows",
+        );
+
+        // Samples after the end of the signal are 0.0
+        assert(
+            0.5f32.sqrt(),
+            interpolator
+                .get_envelope("test", 10.0, 4, EnvelopeMode::Rms)
+                .unwrap(),
+            "Wrong RMS at the end",
+        );
+    }
+
+    #[test]
+    fn resample_to_length() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let interpolator = Interpolator::new(120, 2000, sine_signal_provider);
+
+        // Lengths that don't divide the signal evenly still produce exactly target_len samples
+        for target_len in [1, 7, 999, 1000, 1333, 2000, 2999, 4410] {
+            assert_eq!(
+                target_len,
+                interpolator
+                    .resample_to_length("test", target_len)
+                    .unwrap()
+                    .len()
+            );
+        }
+        assert!(interpolator
+            .resample_to_length("test", 0)
+            .unwrap()
+            .is_empty());
+
+        // Stretching is the same as resample_all with the same ratio
+        let stretched = interpolator.resample_to_length("test", 4000).unwrap();
+        assert_eq!(interpolator.resample_all("test", 0.5).unwrap(), stretched);
+
+        // Compressing uses the anti-aliasing filter, and the step between samples is 2000 / 1333
+        let compressed = interpolator.resample_to_length("test", 1333).unwrap();
+        let step = 2000.0 / 1333.0;
+        assert(
+            interpolator
+                .get_interpolated_sample("test", 500.0 * step, step)
+                .unwrap(),
+            compressed[500],
+            "Wrong compressed sample",
+        );
+    }
+
+    #[test]
+    fn complex_signal_cached_rotators() {
+        // Complex and analytic signals rotate their negative frequencies on their own, with or without the
+        // rotator cache. Quarter samples are exact at a resolution of 4
+        let interpolator = Interpolator::new(120, 2000, IqSampleProvider {});
+        let cached_interpolator = InterpolatorBuilder::new(120, 2000)
+            .rotator_cache_resolution(4)
+            .build(IqSampleProvider {});
+        let analytic_interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        let cached_analytic_interpolator = InterpolatorBuilder::new(120, 2000)
+            .rotator_cache_resolution(4)
+            .build(SignalSampleProvider {});
+
+        for index in [500.25, 750.5, 1000.75] {
+            let expected_sample = interpolator
+                .get_interpolated_complex_sample("test", index, 1.0)
+                .unwrap();
+            let actual_sample = cached_interpolator
+                .get_interpolated_complex_sample("test", index, 1.0)
+                .unwrap();
+            assert(
+                expected_sample.re,
+                actual_sample.re,
+                "Wrong real part with cached rotators",
+            );
+            assert(
+                expected_sample.im,
+                actual_sample.im,
+                "Wrong imaginary part with cached rotators",
+            );
+
+            let expected_sample = analytic_interpolator
+                .get_analytic_sample("test", index)
+                .unwrap();
+            let actual_sample = cached_analytic_interpolator
+                .get_analytic_sample("test", index)
+                .unwrap();
+            assert(
+                expected_sample.re,
+                actual_sample.re,
+                "Wrong analytic real part with cached rotators",
+            );
+            assert(
+                expected_sample.im,
+                actual_sample.im,
+                "Wrong analytic imaginary part with cached rotators",
+            );
+        }
+    }
+
+    #[test]
+    fn resampler() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+
+        let mut resampler = Resampler::new(&interpolator, "test", 10.0, 0.75);
+        for output_index in 0..4 {
+            let index = 10.0 + (output_index as f32) * 0.75;
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", index, 0.75)
+                .unwrap();
+            let actual_sample = resampler.next_sample().unwrap().unwrap();
+            assert(expected_sample, actual_sample, "Wrong sample");
+        }
+
+        assert_eq!(13.0, resampler.cursor(), "Wrong cursor");
+
+        // Steps that aren't representable in binary accumulate in f64
+        let mut resampler = Resampler::new(&interpolator, "test", 0.0, 0.1);
+        for _ in 0..10 {
+            resampler.next_sample().unwrap().unwrap();
+        }
+
+        assert!(
+            (resampler.cursor() - 1.0).abs() < 1e-12,
+            "The cursor drifted"
+        );
+
+        resampler.set_cursor(0.0);
+        resampler.set_step(1.5);
+        assert_eq!(134, resampler.count(), "Wrong number of samples");
+    }
+
+    #[test]
+    fn max_reliable_frequency() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        // The highest bin below Nyquist is 59 / 120 cycles per sample
+        assert_eq!(
+            21_682.5,
+            interpolator.max_reliable_frequency(44100, 1.0),
+            "Wrong frequency"
+        );
+        assert_eq!(
+            21_682.5,
+            interpolator.max_reliable_frequency(44100, 0.5),
+            "Wrong frequency when upsampling"
+        );
+
+        // The anti-aliasing filter limits reads to the output's Nyquist frequency
+        assert_eq!(
+            11_025.0,
+            interpolator.max_reliable_frequency(44100, 2.0),
+            "Wrong frequency when downsampling"
+        );
+
+        let interpolator = Interpolator::new(7, 2000, SignalSampleProvider {});
+        assert_eq!(
+            3.0 * 44100.0 / 7.0,
+            interpolator.max_reliable_frequency(44100, 1.0),
+            "Wrong frequency"
+        );
+    }
+
+    #[test]
+    fn fill_interleaved() {
+        let mut interpolator = Interpolator::new(120, 200, IqSampleProvider {});
+        interpolator.set_channel_num_samples("i", 190);
+
+        // The last frames are past the end of both channels
+        let mut out = [1.0; 16];
+        interpolator
+            .fill_interleaved(&["i", "q"], &mut out, 185.25, 2.5, 1.0)
+            .unwrap();
+
+        for (frame_index, frame) in out.chunks(2).enumerate() {
+            let index = 185.25 + (frame_index as f32) * 2.5;
+            for (channel_id, actual_sample) in ["i", "q"].into_iter().zip(frame) {
+                let expected_sample = if index < interpolator.channel_num_samples(channel_id) as f32
+                {
+                    interpolator
+                        .get_interpolated_sample(channel_id, index, 1.0)
+                        .unwrap()
+                } else {
+                    0.0
+                };
+
+                assert(expected_sample, *actual_sample, "Wrong sample");
+            }
+        }
+
+        assert_eq!(0.0, out[4], "The i channel's end isn't padded");
+        assert_ne!(0.0, out[5], "The q channel is padded too early");
+        assert_eq!([0.0; 4], out[12..], "The end of the signal isn't padded");
+    }
+
+    #[test]
+    fn resample_whole_samples() {
+        let samples: Vec<f32> = (0..200)
+            .map(|index| ((index * 37) % 101) as f32 / 101.0 - 0.5)
+            .collect();
+
+        // Reading at a ratio of 1.0 returns the original samples exactly, in every mode
+        for interpolation_mode in [
+            InterpolationMode::BandLimited,
+            InterpolationMode::CatmullRom,
+        ] {
+            let interpolator = InterpolatorBuilder::new(120, samples.len())
+                .interpolation_mode(interpolation_mode)
+                .build(VecSampleProvider {
+                    samples: samples.clone(),
+                });
+
+            let resampled = interpolator.resample_all("test", 1.0).unwrap();
+            assert_eq!(samples, resampled, "Samples changed at a ratio of 1.0");
+        }
+
+        // Without the anti-aliasing filter, an integer ratio is a clean decimation
+        let interpolator = InterpolatorBuilder::new(120, samples.len())
+            .aliasing_filter_epsilon(2.0)
+            .build(VecSampleProvider {
+                samples: samples.clone(),
+            });
+
+        let decimated = interpolator.resample_all("test", 3.0).unwrap();
+        let expected: Vec<f32> = samples.iter().step_by(3).copied().take(66).collect();
+        assert_eq!(expected, decimated, "Wrong decimation");
+
+        // With the filter, an integer ratio still interpolates
+        let interpolator = Interpolator::new(120, samples.len(), VecSampleProvider { samples });
+        let resampled = interpolator.resample_all("test", 3.0).unwrap();
+        let expected_sample = interpolator
+            .get_interpolated_sample("test", 30.0, 3.0)
+            .unwrap();
+        assert(
+            expected_sample,
+            resampled[10],
+            "Wrong sample with the anti-aliasing filter",
+        );
+    }
+
+    #[test]
+    fn aliasing_filter_kernel() {
+        let taps = [0.25, 0.5, 0.25];
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .aliasing_filter_kernel(&taps)
+            .build(SignalSampleProvider {});
+        let unfiltered_interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        assert_eq!(
+            Some(&taps[..]),
+            interpolator.aliasing_filter_kernel(),
+            "Wrong kernel"
+        );
+        assert_eq!(
+            Some(taps.to_vec()),
+            interpolator.config().aliasing_filter_kernel,
+            "The kernel isn't in the config"
+        );
+        assert_eq!(
+            3,
+            interpolator.oversampling_ratio(2.0),
+            "Wrong number of points"
+        );
+        assert_eq!(
+            1,
+            interpolator.oversampling_ratio(1.0),
+            "The kernel is used without downsampling"
+        );
+
+        for index in [500.0, 750.25, 1000.5] {
+            let expected_sample: f32 = taps
+                .iter()
+                .enumerate()
+                .map(|(tap_index, tap)| {
+                    let tap_index = index + tap_index as f32 - 1.0;
+                    tap * unfiltered_interpolator
+                        .get_interpolated_sample("test", tap_index, 1.0)
+                        .unwrap()
+                })
+                .sum();
+
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", index, 2.0)
+                .unwrap();
+            assert(expected_sample, actual_sample, "Wrong filtered sample");
+
+            // Reading at 1.0 doesn't use the kernel
+            let expected_sample = unfiltered_interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+            assert(
+                expected_sample,
+                actual_sample,
+                "The kernel is used without downsampling",
+            );
+        }
+
+        // This kernel passes DC and removes Nyquist
+        let frequency_response = interpolator.frequency_response(2.0, 3);
+        assert(1.0, frequency_response[0], "Wrong gain at DC");
+        assert(0.0, frequency_response[2], "Wrong gain at Nyquist");
+
+        let expected_indices: Vec<isize> = (439..=560).collect();
+        assert_eq!(
+            expected_indices,
+            interpolator.accessed_indices(500.5, 2.0),
+            "Wrong accessed indices"
+        );
+    }
+
+    #[test]
+    fn estimate_period() {
+        let sine_signal_provider = SineSignalProvider {
+            wavelength_in_samples: 40.3,
+        };
+        let interpolator = Interpolator::new(240, 2000, sine_signal_provider);
+        let period = interpolator.estimate_period("test", 1000, 20, 100).unwrap();
+        assert!((period - 40.3).abs() < 0.01, "Wrong period: {}", period);
+
+        // The second harmonic doesn't halve the period, and twice the period isn't chosen
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| {
+                let phase = 2.0 * PI * index as f32 / 50.0;
+                0.5 * phase.sin() + 0.25 * (2.0 * phase).sin()
+            })
+            .collect();
+        let interpolator = Interpolator::new(240, samples.len(), VecSampleProvider { samples });
+        let period = interpolator.estimate_period("test", 1000, 20, 120).unwrap();
+        assert!(
+            (period - 50.0).abs() < 0.01,
+            "Wrong period with a harmonic: {}",
+            period
+        );
+
+        let interpolator = Interpolator::new(
+            240,
+            2000,
+            VecSampleProvider {
+                samples: vec![0.0; 2000],
+            },
+        );
+        assert_eq!(
+            0.0,
+            interpolator.estimate_period("test", 1000, 20, 120).unwrap(),
+            "Silence has a period"
+        );
+    }
+
+    #[test]
+    fn fill_interleaved_channel_map() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        // A mono channel played in both channels of a stereo buffer
+        let mut out = [0.0; 16];
+        interpolator
+            .fill_interleaved(&["test", "test"], &mut out, 500.25, 1.0, 1.0)
+            .unwrap();
+
+        for frame in out.chunks(2) {
+            assert_eq!(frame[0], frame[1], "The left and right channels differ");
+        }
+
+        let mut mono_out = [0.0; 8];
+        interpolator
+            .fill_interleaved(&["test"], &mut mono_out, 500.25, 1.0, 1.0)
+            .unwrap();
+
+        for (frame, mono_sample) in out.chunks(2).zip(mono_out) {
+            assert_eq!(
+                mono_sample, frame[0],
+                "The stereo buffer doesn't match the mono channel"
+            );
+        }
+    }
+
+    #[test]
+    fn fill_interleaved_doesnt_allocate() {
+        for window_function in [WindowFunction::Rectangular, WindowFunction::Hann] {
+            let interpolator = InterpolatorBuilder::new(120, 2000)
+                .window_function(window_function)
+                .build(IqSampleProvider {});
+
+            // With and without the anti-aliasing filter
+            for relative_speed in [0.75, 2.5] {
+                let mut out = [0.0; 256];
+                let mut fill = |start| {
+                    interpolator
+                        .fill_interleaved(
+                            &["i", "q"],
+                            &mut out,
+                            start,
+                            relative_speed,
+                            relative_speed,
+                        )
+                        .unwrap()
+                };
+
+                // The first fill plans the transforms, and fills the caches and the pool of buffers
+                fill(100.25);
+                let allocations = count_allocations(|| fill(500.25));
+                assert_eq!(
+                    0, allocations,
+                    "fill_interleaved allocated with {:?} at {}",
+                    window_function, relative_speed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_mode_clamp() {
+        let samples = vec![0.5; 200];
+        let zero_interpolator = Interpolator::new(
+            120,
+            samples.len(),
+            VecSampleProvider {
+                samples: samples.clone(),
+            },
+        );
+
+        // Zero-padding puts a step from the signal to silence in the window, which distorts fractional reads near
+        // the last sample
+        let sample = zero_interpolator
+            .get_interpolated_sample("test", 198.9, 1.0)
+            .unwrap();
+        assert!(
+            (sample - 0.5).abs() > 0.01,
+            "The zero-padded read isn't distorted: {}",
+            sample
+        );
+
+        for boundary_mode in [BoundaryMode::Clamp, BoundaryMode::Mirror] {
+            let interpolator = InterpolatorBuilder::new(120, samples.len())
+                .boundary_mode(boundary_mode)
+                .build(VecSampleProvider {
+                    samples: samples.clone(),
+                });
+
+            for index in [0.1, 0.5, 198.5, 198.9, 199.0] {
+                let sample = interpolator
+                    .get_interpolated_sample("test", index, 1.0)
+                    .unwrap();
+                assert(0.5, sample, "DC is attenuated near the ends");
+            }
+        }
+
+        let interpolator = InterpolatorBuilder::new(120, samples.len())
+            .boundary_mode(BoundaryMode::Clamp)
+            .build(VecSampleProvider { samples });
+        assert_eq!(
+            vec![0.5; 120],
+            interpolator.get_window("test", 199).unwrap(),
+            "The last sample isn't held"
+        );
+    }
+
+    #[test]
+    fn on_transform() {
+        let transforms = Arc::new(Mutex::new(Vec::<(isize, Vec<Complex32>)>::new()));
+        let recorded_transforms = transforms.clone();
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .on_transform(move |index, transform| {
+                recorded_transforms
+                    .lock()
+                    .unwrap()
+                    .push((index, transform.to_vec()));
+            })
+            .build(SignalSampleProvider {});
+
+        // The second read uses the cached transform
+        interpolator
+            .get_interpolated_sample("test", 500.25, 1.0)
+            .unwrap();
+        interpolator
+            .get_interpolated_sample("test", 500.75, 1.0)
+            .unwrap();
+        {
+            let transforms = transforms.lock().unwrap();
+            assert_eq!(1, transforms.len(), "Wrong number of transforms");
+            assert_eq!(500, transforms[0].0, "Wrong index");
+            assert_eq!(120, transforms[0].1.len(), "Wrong number of frequencies");
+
+            let window_sum: f32 = interpolator.get_window("test", 500).unwrap().iter().sum();
+            assert(window_sum, transforms[0].1[0].re, "Wrong DC frequency");
+        }
+
+        // Every window of a resample pass is reported
+        transforms.lock().unwrap().clear();
+        interpolator
+            .get_interpolated_range("test", 600.5, 1.0, 3, 1.0)
+            .unwrap();
+        let indexes: Vec<isize> = transforms
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(index, _)| *index)
+            .collect();
+        assert_eq!(vec![600, 601, 602], indexes, "Wrong indexes");
+
+        // Reads through &mut self are reported, and the transforms are scaled like get_spectrum's
+        let transforms = Arc::new(Mutex::new(Vec::<(isize, Vec<Complex32>)>::new()));
+        let recorded_transforms = transforms.clone();
+        let mut interpolator = InterpolatorBuilder::new(120, 2000)
+            .normalization(Normalization::Forward)
+            .on_transform(move |index, transform| {
+                recorded_transforms
+                    .lock()
+                    .unwrap()
+                    .push((index, transform.to_vec()));
+            })
+            .build(SignalSampleProvider {});
+
+        interpolator
+            .get_interpolated_sample_mut("test", 700.25, 1.0)
+            .unwrap();
+        let spectrum = interpolator.get_spectrum("test", 700).unwrap();
+        let transforms = transforms.lock().unwrap();
+        assert_eq!(
+            700, transforms[0].0,
+            "get_interpolated_sample_mut's transform isn't reported"
+        );
+        for (expected, actual) in spectrum.iter().zip(&transforms[0].1) {
+            assert(expected.re, actual.re, "The real part isn't normalized");
+            assert(
+                expected.im,
+                actual.im,
+                "The imaginary part isn't normalized",
+            );
+        }
+    }
+
+    #[test]
+    fn as_fn() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        let signal = interpolator.as_fn("test", 1.0);
+
+        for index in [500.0, 750.25, 1000.5] {
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+            assert(expected_sample, signal(index).unwrap(), "Wrong sample");
+        }
+
+        // A midpoint-rule integral over a few samples, as an integrator would take the function
+        fn integrate(f: impl Fn(f32) -> f32, start: f32, end: f32, steps: usize) -> f32 {
+            let width = (end - start) / steps as f32;
+            (0..steps)
+                .map(|step| f(start + (step as f32 + 0.5) * width) * width)
+                .sum()
+        }
+
+        let integral = integrate(|index| signal(index).unwrap(), 500.0, 502.0, 4);
+        let expected_integral: f32 = [500.25, 500.75, 501.25, 501.75]
+            .iter()
+            .map(|index| {
+                interpolator
+                    .get_interpolated_sample("test", *index, 1.0)
+                    .unwrap()
+                    * 0.5
+            })
+            .sum();
+        assert(expected_integral, integral, "Wrong integral");
+    }
+
+    #[test]
+    fn max_oversampling_ratio() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+        for relative_speed in [1e9, f32::INFINITY] {
+            assert_eq!(
+                DEFAULT_MAX_OVERSAMPLING_RATIO,
+                interpolator.oversampling_ratio(relative_speed),
+                "The oversampling ratio isn't clamped"
+            );
+        }
+
+        let sample = interpolator
+            .get_interpolated_sample("test", 1000.5, 1e9)
+            .unwrap();
+        assert!(
+            sample.is_finite(),
+            "Wrong sample at an absurd speed: {}",
+            sample
+        );
+
+        // Speeds that need fewer points than the maximum aren't affected
+        let interpolator = InterpolatorBuilder::new(120, 2000)
+            .max_oversampling_ratio(16)
+            .build(SignalSampleProvider {});
+        assert_eq!(
+            12,
+            interpolator.oversampling_ratio(6.0),
+            "Wrong oversampling ratio below the maximum"
+        );
+        assert_eq!(
+            16,
+            interpolator.oversampling_ratio(100.0),
+            "The oversampling ratio isn't clamped"
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "max_oversampling_ratio must be at least 1 and at least min_oversampling_ratio"
+    )]
+    fn max_oversampling_ratio_less_than_min() {
+        InterpolatorBuilder::new(120, 2000)
+            .min_oversampling_ratio(8)
+            .max_oversampling_ratio(4)
+            .build(SignalSampleProvider {});
+    }
+
+    #[test]
+    fn get_interpolated_sample_with_bin_gains() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        for index in [500.0, 750.25, 1000.5] {
+            let expected_sample = interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+
+            let actual_sample = interpolator
+                .get_interpolated_sample_with_bin_gains("test", index, 1.0, &[1.0; 61])
+                .unwrap();
+            assert(
+                expected_sample,
+                actual_sample,
+                "Unity gains changed the sample",
+            );
+
+            let actual_sample = interpolator
+                .get_interpolated_sample_with_bin_gains("test", index, 1.0, &[2.0; 61])
+                .unwrap();
+            assert(
+                expected_sample * 2.0,
+                actual_sample,
+                "Wrong sample with a gain of 2.0",
+            );
+
+            // Zeroing the bins outside of a band matches the band
+            let mut bin_gains = [0.0; 61];
+            bin_gains[4..=10].fill(1.0);
+            let expected_sample = interpolator
+                .get_interpolated_sample_band("test", index, 1.0, 4, 10)
+                .unwrap();
+            let actual_sample = interpolator
+                .get_interpolated_sample_with_bin_gains("test", index, 1.0, &bin_gains)
+                .unwrap();
+            assert(
+                expected_sample,
+                actual_sample,
+                "Wrong sample with bins zeroed",
+            );
+        }
+    }
+
+    #[test]
+    fn interpolator_error() {
+        let interpolator = Interpolator::new(120, 2000, SignalSampleProvider {});
+
+        let expected_sample = interpolator
+            .get_interpolated_sample("test", 500.25, 1.0)
+            .unwrap();
+        let actual_sample = interpolator
+            .try_get_interpolated_sample("test", 500.25, 1.0)
+            .unwrap();
+        assert_eq!(expected_sample, actual_sample, "Wrong sample");
+
+        assert!(matches!(
+            interpolator.try_get_interpolated_sample("test", f32::NAN, 1.0),
+            Err(InterpolatorError::InvalidIndex(index)) if index.is_nan()
+        ));
+        assert!(matches!(
+            interpolator.try_get_interpolated_sample("test", 500.25, f32::INFINITY),
+            Err(InterpolatorError::InvalidSpeed(f32::INFINITY))
+        ));
+        assert!(matches!(
+            interpolator.try_resample_all("test", 0.0),
+            Err(InterpolatorError::InvalidSpeed(0.0))
+        ));
+
+        let interpolator = Interpolator::new(120, 100, SignalSampleProvider {});
+        assert!(matches!(
+            interpolator.try_resample_all("test", 1.0),
+            Err(InterpolatorError::WindowTooLarge {
+                window_size: 120,
+                num_samples: 100
+            })
+        ));
+
+        // Provider errors are wrapped, and are the error's source
+        let interpolator = Interpolator::new(20, 200, ErrorSampleProvider {});
+        let error = interpolator
+            .try_get_interpolated_sample("test", 3.0, 1.0)
+            .unwrap_err();
+        assert!(
+            matches!(error, InterpolatorError::Provider(_)),
+            "Wrong error: {:?}",
+            error
+        );
+        assert!(
+            std::error::Error::source(&error).is_some(),
+            "The provider's error isn't the source"
+        );
+        assert_eq!(
+            ErrorKind::BrokenPipe,
+            error.into_provider_error().unwrap().kind(),
+            "Wrong provider error"
+        );
+    }
+
+    #[test]
+    fn sum_sample_provider() {
+        let first_layer = SineSignalProvider {
+            wavelength_in_samples: 40.0,
+        };
+        let second_layer = SineSignalProvider {
+            wavelength_in_samples: 25.0,
+        };
+        let expected_mix = |index: f32| {
+            0.5 * first_layer.get_sine_signal_sample(index)
+                + 0.25 * second_layer.get_sine_signal_sample(index)
+        };
+
+        let second_layer_samples: Vec<f32> = (0..2000)
+            .map(|index| second_layer.get_sine_signal_sample(index as f32))
+            .collect();
+        let sources: Vec<Box<dyn SampleProvider<&str, Error>>> = vec![
+            Box::new(GainSampleProvider::new(first_layer, 0.5)),
+            Box::new(VecSampleProvider {
+                samples: second_layer_samples,
+            }),
+        ];
+        let mix = SumSampleProvider::with_gains(sources.into_iter().zip([1.0, 0.25]).collect());
+        let interpolator = Interpolator::new(120, 2000, mix);
+
+        for index in [500.0, 750.25, 1000.5] {
+            let actual_sample = interpolator
+                .get_interpolated_sample("test", index, 1.0)
+                .unwrap();
+            assert(expected_mix(index), actual_sample, "Wrong mixed sample");
+        }
+
+        // Sources that end are silent, until every source has ended
+        struct EndingSampleProvider {
+            len: usize,
+        }
+
+        impl SampleProvider<&str, Error> for EndingSampleProvider {
+            fn get_sample(&self, _channel_id: &str, _index: usize) -> Result<f32> {
+                Ok(1.0)
+            }
+
+            fn get_sample_if_available(
+                &self,
+                _channel_id: &str,
+                index: usize,
+            ) -> Result<Option<f32>> {
+                Ok((index < self.len).then_some(1.0))
+            }
+        }
+
+        let mix = SumSampleProvider::new(vec![
+            EndingSampleProvider { len: 10 },
+            EndingSampleProvider { len: 20 },
+        ]);
+        assert_eq!(2, mix.sources().len(), "Wrong number of sources");
+        assert_eq!(
+            Some(2.0),
+            mix.get_sample_if_available("test", 5).unwrap(),
+            "Wrong sum"
+        );
+        assert_eq!(
+            Some(1.0),
+            mix.get_sample_if_available("test", 15).unwrap(),
+            "An ended source isn't silent"
+        );
+        assert_eq!(
+            None,
+            mix.get_sample_if_available("test", 25).unwrap(),
+            "The mix didn't end"
+        );
+    }
+
+    #[test]
+    fn player() {
+        let interpolator = Interpolator::new(120, 200, SignalSampleProvider {});
+        let expected_samples = interpolator
+            .get_interpolated_range("test", 0.0, 0.75, 267, 0.75)
+            .unwrap();
+
+        // A mono channel played in stereo, in blocks that don't divide the signal evenly
+        let mut player = Player::new(&interpolator, &["test", "test"], 0.0, 0.75);
+        let mut block = [0.0; 128];
+        let mut actual_samples = Vec::new();
+        let mut frames_before_end = Vec::new();
+        while !player.is_finished() {
+            frames_before_end.push(player.fill(&mut block).unwrap());
+            for frame in block.chunks(2) {
+                assert_eq!(frame[0], frame[1], "The channels differ");
+                actual_samples.push(frame[0]);
+            }
+        }
+
+        assert_eq!(
+            vec![64, 64, 64, 64, 11],
+            frames_before_end,
+            "Wrong number of frames before the end"
+        );
+        assert_eq!(320.0 * 0.75, player.cursor(), "Wrong cursor");
+
+        for (expected_sample, actual_sample) in expected_samples.iter().zip(&actual_samples) {
+            assert(*expected_sample, *actual_sample, "Wrong sample");
+        }
+
+        assert!(
+            actual_samples[267..].iter().all(|sample| *sample == 0.0),
+            "The end isn't silent"
+        );
+        assert_eq!(0, player.fill(&mut block).unwrap(), "Frames after the end");
+    }
+
+    #[test]
+    fn player_doesnt_allocate() {
+        let interpolator = Interpolator::new(120, 2000, IqSampleProvider {});
+        let mut player = Player::new(&interpolator, &["i", "q"], 100.25, 0.75);
+        let mut block = [0.0; 128];
+
+        // With and without the anti-aliasing filter
+        for relative_speed in [0.75, 2.5] {
+            player.set_relative_speed(relative_speed);
+            player.prepare(64).unwrap();
+
+            let allocations = count_allocations(|| {
+                for _ in 0..4 {
+                    player.fill(&mut block).unwrap();
+                }
+            });
+            assert_eq!(0, allocations, "fill allocated at {}", relative_speed);
+        }
+    }
+
+    #[test]
+    fn round_window_to_fast_size() {
+        for (window_size, expected_window_size) in
+            [(127, 128), (1009, 1024), (121, 125), (97, 98), (120, 120)]
+        {
+            let interpolator = InterpolatorBuilder::new(window_size, 2000)
+                .round_window_to_fast_size(true)
+                .build(SignalSampleProvider {});
+            assert_eq!(
+                expected_window_size,
+                interpolator.window_size(),
+                "Wrong window size for {}",
+                window_size
+            );
+            assert!(interpolator.round_window_to_fast_size());
+
+            // The config holds the size that's used, and building from it again doesn't change it
+            let config = interpolator.config();
+            assert_eq!(
+                expected_window_size, config.window_size,
+                "Wrong window size in the config"
+            );
+            let interpolator = Interpolator::from_config(config, SignalSampleProvider {}).unwrap();
+            assert_eq!(
+                expected_window_size,
+                interpolator.window_size(),
+                "Rebuilding changed the window size"
+            );
+        }
+
+        let interpolator = Interpolator::new(127, 2000, SignalSampleProvider {});
+        assert_eq!(
+            127,
+            interpolator.window_size(),
+            "The window size is rounded by default"
+        );
+    }
+
+    #[test]
+    fn interpolate_offsets() {
+        let fracs: Vec<f32> = (0..8).map(|n| n as f32 / 8.0).collect();
+        let fracs_across_windows = [-0.5, 0.25, 1.5, 2.75];
+
+        let interpolators = [
+            Interpolator::new(120, 2000, SignalSampleProvider {}),
+            Interpolator::new(121, 2000, SignalSampleProvider {}),
+            InterpolatorBuilder::new(120, 2000)
+                .window_function(WindowFunction::Hann)
+                .remove_dc(true)
+                .build(SignalSampleProvider {}),
+            InterpolatorBuilder::new(120, 2000)
+                .interpolation_mode(InterpolationMode::CatmullRom)
+                .build(SignalSampleProvider {}),
+        ];
+
+        for interpolator in interpolators.iter() {
+            for fracs in [&fracs[..], &fracs_across_windows[..]] {
+                let samples = interpolator
+                    .interpolate_offsets("test", 500, fracs)
+                    .unwrap();
+                assert_eq!(fracs.len(), samples.len(), "Wrong number of samples");
+
+                for (fract, actual_sample) in fracs.iter().zip(samples) {
+                    let expected_sample = interpolator
+                        .get_interpolated_sample("test", 500.0 + fract, 1.0)
+                        .unwrap();
+                    assert(
+                        expected_sample,
+                        actual_sample,
+                        &format!("Wrong sample at offset {}", fract),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resample_to_writer() {
+        struct VecSampleWriter {
+            samples: Vec<(&'static str, usize, f32)>,
+            fail_at: Option<usize>,
+        }
+
+        impl SampleWriter<&'static str, Error> for VecSampleWriter {
+            fn write_sample(
+                &mut self,
+                channel_id: &'static str,
+                index: usize,
+                sample: f32,
+            ) -> Result<()> {
+                if self.fail_at == Some(index) {
+                    return Err(Error::new(ErrorKind::WriteZero, "The writer is full"));
+                }
+
+                self.samples.push((channel_id, index, sample));
+                Ok(())
+            }
+        }
+
+        let mut interpolator = Interpolator::new(120, 200, IqSampleProvider {});
+        interpolator.set_channel_num_samples("q", 150);
+
+        let mut writer = VecSampleWriter {
+            samples: Vec::new(),
+            fail_at: None,
+        };
+        let num_frames = interpolator
+            .resample_to_writer(&["i", "q"], 1.5, &mut writer)
+            .unwrap();
+        assert_eq!(133, num_frames, "Wrong number of frames");
+
+        // The channels are interleaved until the shorter channel ends
+        let expected_i = interpolator.resample_all("i", 1.5).unwrap();
+        let expected_q = interpolator.resample_all("q", 1.5).unwrap();
+        assert_eq!(133 + 100, writer.samples.len(), "Wrong number of samples");
+        assert_eq!(("i", 0), (writer.samples[0].0, writer.samples[0].1));
+        assert_eq!(("q", 0), (writer.samples[1].0, writer.samples[1].1));
+        assert_eq!(("i", 100), (writer.samples[200].0, writer.samples[200].1));
+
+        for (channel_id, index, actual_sample) in writer.samples {
+            let expected_sample = if channel_id == "i" {
+                expected_i[index]
+            } else {
+                expected_q[index]
+            };
+            assert(expected_sample, actual_sample, "Wrong sample");
+        }
+
+        // The writer's errors stop resampling
+        let mut writer = VecSampleWriter {
+            samples: Vec::new(),
+            fail_at: Some(5),
+        };
+        let error = interpolator
+            .resample_to_writer(&["i", "q"], 1.5, &mut writer)
+            .unwrap_err();
+        assert_eq!(ErrorKind::WriteZero, error.kind(), "Wrong error");
+        assert_eq!(
+            10,
+            writer.samples.len(),
+            "Samples were written after the error"
+        );
+    }
+
+    #[test]
+    fn resample_all_energy_preserving() {
+        fn rms(samples: &[f32]) -> f32 {
+            (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32)
+                .sqrt()
+        }
+
+        let samples: Vec<f32> = (0..2000)
+            .map(|index| {
+                0.5 * (2.0 * PI * index as f32 / 40.0).sin()
+                    + 0.25 * (2.0 * PI * index as f32 / 3.0).sin()
+            })
+            .collect();
+        let interpolator = InterpolatorBuilder::new(120, samples.len())
+            .fade_out_tail(true)
+            .build(VecSampleProvider {
+                samples: samples.clone(),
+            });
+
+        for ratio in [0.75, 1.5] {
+            let resampled = interpolator.resample_all("test", ratio).unwrap();
+            let preserved = interpolator
+                .resample_all_energy_preserving("test", ratio)
+                .unwrap();
+            assert_eq!(resampled.len(), preserved.len(), "Wrong number of samples");
+            assert(
+                rms(&samples),
+                rms(&preserved),
+                &format!("The RMS isn't preserved at a ratio of {}", ratio),
+            );
+
+            // The whole output is scaled by the same gain
+            let gain = preserved[100] / resampled[100];
+            for (resampled_sample, preserved_sample) in resampled.iter().zip(&preserved) {
+                assert(
+                    resampled_sample * gain,
+                    *preserved_sample,
+                    "The output isn't scaled evenly",
+                );
+            }
+        }
+
+        // The resampled peak is about 0.5, and the gain doesn't push it past the clamp
+        let clamped_interpolator = InterpolatorBuilder::new(120, samples.len())
+            .clamp(-0.55, 0.55)
+            .build(VecSampleProvider { samples });
+        let preserved = clamped_interpolator
+            .resample_all_energy_preserving("test", 1.5)
+            .unwrap();
+        assert!(
+            preserved.iter().all(|sample| sample.abs() <= 0.55),
+            "The scaled output isn't clamped"
+        );
+        assert!(
+            clamped_interpolator.max_overshoot() > 0.0,
+            "The overshoot isn't measured"
+        );
+
+        let interpolator = Interpolator::new(
+            120,
+            200,
+            VecSampleProvider {
+                samples: vec![0.0; 200],
+            },
+        );
+        let preserved = interpolator
+            .resample_all_energy_preserving("test", 1.5)
+            .unwrap();
+        assert!(
+            preserved.iter().all(|sample| *sample == 0.0),
+            "Silence was scaled"
+        );
+    }
+
+    struct RandomAccessWavReaderSampleProvider {
+        random_access_wav_reader: RefCell<RandomAccessWavReader<f32>>,
+    }
+
+    // Channel "front_right" is the front right channel; any other channel id is the front left channel
+    impl SampleProvider<&str, Error> for RandomAccessWavReaderSampleProvider {
+        fn get_sample(&self, channel_id: &str, index: usize) -> std::result::Result<f32, Error> {
+            let mut random_access_wav_reader = self.random_access_wav_reader.borrow_mut();
+            let read_sample_result = random_access_wav_reader.read_sample(index);
+            let samples_by_channel = read_sample_result?;
+            let sample = match channel_id {
+                "front_right" => samples_by_channel.front_right,
+                _ => samples_by_channel.front_left,
+            };
+
+            sample.ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("The wav file doesn't have channel {}", channel_id),
+                )
+            })
+        }
+    }
+
+    #[test]
+    #[allow(clippy::useless_vec, clippy::needless_range_loop)]
+    fn wave_stream_supported() {
+        let header = WavHeader {
+            sample_format: SampleFormat::Float,
+            channels: Channels::new().front_left(),
+            sample_rate: 44100,
+        };
+
+        let samples = vec![
+            0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0,
+            0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0,
+        ];
+
+        {
+            let open_wav_writer = write_wav_to_file_path(Path::new("delete_me.wav"), header).unwrap();
+
+            let mut random_access_wave_writer = open_wav_writer.get_random_access_f32_writer().unwrap();
+
+            for sample_ctr in 0..samples.len() {
+                random_access_wave_writer
+                    .write_samples(
+                        sample_ctr,
+                        SamplesByChannel::new().front_left(samples[sample_ctr]),
+                    )
+                    .unwrap()
+            }
+        }
+
+        let open_wav_reader = read_wav_from_file_path(Path::new("delete_me.wav")).unwrap();
+        let random_access_wav_reader_sample_provider = RandomAccessWavReaderSampleProvider {
+            random_access_wav_reader: RefCell::new(open_wav_reader.get_random_access_f32_reader().unwrap()),
+        };
+
+        let interpolator = Interpolator::new(
+            4,
+            samples.len(),
+            random_access_wav_reader_sample_provider,
+        );
+
+        for sample_ctr in 0..samples.len() {
+            let expected_sample = samples[sample_ctr];
+            let actual_sample = interpolator
+                .get_interpolated_sample("", sample_ctr as f32, 1.0)
+                .unwrap();
+            assert_eq!(
+                expected_sample, actual_sample,
+                "Wrong sample when reading from a wav file"
+            );
+        }
+
+        // The file is mono, so reading the right channel fails instead of panicking
+        assert_eq!(
+            ErrorKind::NotFound,
+            interpolator
+                .get_interpolated_sample("front_right", 2.5, 1.0)
+                .unwrap_err()
+                .kind()
+        );
 
         fs::remove_file(Path::new("delete_me.wav")).unwrap();
     }
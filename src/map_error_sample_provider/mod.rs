@@ -0,0 +1,96 @@
+use std::marker::PhantomData;
+
+use crate::interpolator::SampleProvider;
+
+/// Wraps a SampleProvider and converts its errors with map_error, so that an Interpolator's results use the
+/// error type of the code that calls it
+pub struct MapErrorSampleProvider<TSampleProvider, TChannelId, TError, TMapError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    sample_provider: TSampleProvider,
+    map_error: TMapError,
+
+    _phantom_data: PhantomData<(TChannelId, TError)>,
+}
+
+impl<TSampleProvider, TChannelId, TError, TMapError>
+    MapErrorSampleProvider<TSampleProvider, TChannelId, TError, TMapError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    pub fn new(
+        sample_provider: TSampleProvider,
+        map_error: TMapError,
+    ) -> MapErrorSampleProvider<TSampleProvider, TChannelId, TError, TMapError> {
+        MapErrorSampleProvider {
+            sample_provider,
+            map_error,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    pub fn sample_provider(&self) -> &TSampleProvider {
+        &self.sample_provider
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl<TSampleProvider, TChannelId, TError>
+    MapErrorSampleProvider<TSampleProvider, TChannelId, TError, fn(TError) -> anyhow::Error>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+    TError: Into<anyhow::Error>,
+{
+    /// Converts errors into anyhow::Error, so that an Interpolator's results can be propagated into
+    /// anyhow::Result with ?
+    pub fn anyhow(
+        sample_provider: TSampleProvider,
+    ) -> MapErrorSampleProvider<TSampleProvider, TChannelId, TError, fn(TError) -> anyhow::Error>
+    {
+        MapErrorSampleProvider::new(sample_provider, Into::into)
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError, TMappedError, TMapError>
+    SampleProvider<TChannelId, TMappedError>
+    for MapErrorSampleProvider<TSampleProvider, TChannelId, TError, TMapError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+    TMapError: Fn(TError) -> TMappedError,
+{
+    fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TMappedError> {
+        self.sample_provider
+            .get_sample(channel_id, index)
+            .map_err(&self.map_error)
+    }
+
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TMappedError> {
+        self.sample_provider
+            .get_sample_if_available(channel_id, index)
+            .map_err(&self.map_error)
+    }
+
+    fn generation(&self) -> u64 {
+        self.sample_provider.generation()
+    }
+
+    fn prefetch(
+        &self,
+        channel_id: TChannelId,
+        start: usize,
+        len: usize,
+    ) -> Result<(), TMappedError> {
+        self.sample_provider
+            .prefetch(channel_id, start, len)
+            .map_err(&self.map_error)
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{Error, ErrorKind},
+};
+
+use memmap2::Mmap;
+
+use crate::interpolator::SampleProvider;
+
+const SAMPLE_SIZE: usize = 4;
+
+// WAVE_FORMAT_IEEE_FLOAT, and WAVE_FORMAT_EXTENSIBLE, whose subformat is in the extension
+const FORMAT_IEEE_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    fn read_u16(self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_u32(self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_f32(self, bytes: &[u8]) -> f32 {
+        f32::from_bits(self.read_u32(bytes))
+    }
+}
+
+/// Reads interleaved f32 samples from a memory-mapped file, so that files that are too large to load into
+/// memory can be interpolated, without a read call for each sample. The operating system pages in the parts
+/// of the file that are read.
+///
+/// The channel id is the index of the channel within each frame, and samples are indexed by frame. The file
+/// must not be modified while it's mapped: Another process changing or truncating it is undefined behavior
+pub struct MmapSampleProvider {
+    mmap: Mmap,
+    data_offset: usize,
+    num_frames: usize,
+    num_channels: usize,
+    endianness: Endianness,
+}
+
+impl MmapSampleProvider {
+    /// Maps a raw file whose samples start data_offset bytes into it, after any header, and continue to the
+    /// end of the file. A partial frame at the end is ignored
+    pub fn new(
+        file: &File,
+        data_offset: usize,
+        num_channels: usize,
+        endianness: Endianness,
+    ) -> Result<MmapSampleProvider, Error> {
+        let mmap = map(file)?;
+        let data_len = mmap.len().checked_sub(data_offset).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "data_offset is after the end of the file",
+            )
+        })?;
+
+        MmapSampleProvider::from_mmap(mmap, data_offset, data_len, num_channels, endianness)
+    }
+
+    /// Maps a 32-bit float wav file. RIFF files are little-endian, and RIFX files are big-endian
+    pub fn from_wav(file: &File) -> Result<MmapSampleProvider, Error> {
+        let mmap = map(file)?;
+        let bytes = &mmap[..];
+        if bytes.len() < 12 || &bytes[8..12] != b"WAVE" {
+            return Err(invalid_wav("the file isn't a wav file"));
+        }
+
+        let endianness = match &bytes[0..4] {
+            b"RIFF" => Endianness::Little,
+            b"RIFX" => Endianness::Big,
+            _ => return Err(invalid_wav("the file isn't a wav file")),
+        };
+
+        let mut num_channels = None;
+        let mut chunk_start = 12;
+        while chunk_start + 8 <= bytes.len() {
+            let chunk_id = &bytes[chunk_start..chunk_start + 4];
+            let chunk_len = endianness.read_u32(&bytes[chunk_start + 4..]) as usize;
+            let chunk = &bytes[chunk_start + 8..];
+
+            match chunk_id {
+                b"fmt " => {
+                    if chunk_len < 16 || chunk.len() < chunk_len {
+                        return Err(invalid_wav("the fmt chunk is too short"));
+                    }
+
+                    let mut format = endianness.read_u16(chunk);
+                    if format == FORMAT_EXTENSIBLE && chunk_len >= 26 {
+                        // The subformat GUID starts with the format
+                        format = endianness.read_u16(&chunk[24..]);
+                    }
+
+                    if format != FORMAT_IEEE_FLOAT || endianness.read_u16(&chunk[14..]) != 32 {
+                        return Err(invalid_wav("the samples aren't 32-bit floats"));
+                    }
+
+                    num_channels = Some(endianness.read_u16(&chunk[2..]) as usize);
+                }
+                b"data" => {
+                    let num_channels = num_channels
+                        .ok_or_else(|| invalid_wav("the data chunk is before the fmt chunk"))?;
+
+                    // A file that was truncated while it was written has a shorter data chunk than its
+                    // header says
+                    let data_len = chunk_len.min(chunk.len());
+                    return MmapSampleProvider::from_mmap(
+                        mmap,
+                        chunk_start + 8,
+                        data_len,
+                        num_channels,
+                        endianness,
+                    );
+                }
+                _ => {}
+            }
+
+            // Chunks are padded to an even length
+            chunk_start += 8 + chunk_len + chunk_len % 2;
+        }
+
+        Err(invalid_wav("there is no data chunk"))
+    }
+
+    fn from_mmap(
+        mmap: Mmap,
+        data_offset: usize,
+        data_len: usize,
+        num_channels: usize,
+        endianness: Endianness,
+    ) -> Result<MmapSampleProvider, Error> {
+        if num_channels == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "there must be at least 1 channel",
+            ));
+        }
+
+        Ok(MmapSampleProvider {
+            mmap,
+            data_offset,
+            num_frames: data_len / (SAMPLE_SIZE * num_channels),
+            num_channels,
+            endianness,
+        })
+    }
+
+    /// The number of frames in the file. This is the num_samples for an Interpolator
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+}
+
+impl SampleProvider<usize, Error> for MmapSampleProvider {
+    fn get_sample(&self, channel_id: usize, index: usize) -> Result<f32, Error> {
+        if channel_id >= self.num_channels {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "channel {} is out of range, there are {} channels",
+                    channel_id, self.num_channels
+                ),
+            ));
+        }
+
+        if index >= self.num_frames {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "sample {} is after the end of the file, there are {} frames",
+                    index, self.num_frames
+                ),
+            ));
+        }
+
+        let offset = self.data_offset + (index * self.num_channels + channel_id) * SAMPLE_SIZE;
+        Ok(self.endianness.read_f32(&self.mmap[offset..]))
+    }
+
+    // Reading past the end of the file is the end of the signal, instead of an error
+    fn get_sample_if_available(
+        &self,
+        channel_id: usize,
+        index: usize,
+    ) -> Result<Option<f32>, Error> {
+        match self.get_sample(channel_id, index) {
+            Ok(sample) => Ok(Some(sample)),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+fn map(file: &File) -> Result<Mmap, Error> {
+    // Safety: The mapping is only read, and MmapSampleProvider documents that the file must not be modified
+    // while it's mapped
+    unsafe { Mmap::map(file) }
+}
+
+fn invalid_wav(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,161 @@
+use std::{collections::VecDeque, f32::consts::PI};
+
+use crate::interpolator::{Interpolator, SampleProvider};
+
+/// Reads consecutive samples, starting at a fractional index, in blocks. Instead of reconstructing each
+/// sample on its own, whole windows are reconstructed every hop samples, weighted by a Hann window, and
+/// summed. Each output sample blends several windows, so there are no seams where one window ends and the
+/// next begins, including between blocks. In exchange, samples are slightly less accurate than reading
+/// each one with get_interpolated_sample, because most of them are away from the center of their windows
+pub struct OverlapAddReader<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+    channel_id: TChannelId,
+    fract: f32,
+    hop: usize,
+    synthesis_window: Vec<f32>,
+    next_frame_index: isize,
+    // The sum of the weighted frames, and the sum of their weights, starting at output_index
+    accumulated_samples: VecDeque<f32>,
+    accumulated_weights: VecDeque<f32>,
+    output_index: isize,
+}
+
+impl<'a, TSampleProvider, TChannelId, TError>
+    OverlapAddReader<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// Reads samples start_index, start_index + 1.0, start_index + 2.0, etc. overlap is the fraction of each
+    /// window that overlaps the next, from 0.5 up to, (but not including,) 1.0. 0.5 is typical. The Hann
+    /// windows must add up to a constant, so that the output has no ripple at the hop: Panics unless the hop,
+    /// (window_size * (1.0 - overlap), rounded,) divides window_size evenly, (IE, overlaps of 0.5 and 0.75 with
+    /// a window_size of 64)
+    pub fn new(
+        interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+        channel_id: TChannelId,
+        start_index: f32,
+        overlap: f32,
+    ) -> OverlapAddReader<'a, TSampleProvider, TChannelId, TError> {
+        assert!(
+            (0.5..1.0).contains(&overlap),
+            "overlap must be at least 0.5 and less than 1.0, but was {}",
+            overlap
+        );
+
+        let window_size = interpolator.window_size();
+        let hop = ((window_size as f32 * (1.0 - overlap)).round() as usize).max(1);
+        let synthesis_window: Vec<f32> = (0..window_size)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * (n as f32) / (window_size as f32)).cos())
+            .collect();
+        assert!(
+            adds_to_constant(&synthesis_window, hop),
+            "The Hann window doesn't add up to a constant with a hop of {} samples, because window_size, ({},) \
+             isn't a multiple of it",
+            hop,
+            window_size
+        );
+
+        let start_floor = start_index.floor();
+        let output_index = start_floor as isize;
+
+        // The first window starts early enough that the first output sample is covered by as many windows
+        // as every other sample
+        let next_frame_index = output_index - ((window_size / 2 / hop) * hop) as isize;
+
+        OverlapAddReader {
+            interpolator,
+            channel_id,
+            fract: start_index - start_floor,
+            hop,
+            synthesis_window,
+            next_frame_index,
+            accumulated_samples: VecDeque::with_capacity(window_size),
+            accumulated_weights: VecDeque::with_capacity(window_size),
+            output_index,
+        }
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// Reads the next block_len samples
+    pub fn next_block(&mut self, block_len: usize) -> Result<Vec<f32>, TError> {
+        let half_window_size = (self.interpolator.window_size() / 2) as isize;
+        let mut block = Vec::with_capacity(block_len);
+
+        while block.len() < block_len {
+            // A sample is complete once the next window starts after it
+            if self.next_frame_index - half_window_size > self.output_index {
+                if let (Some(sample), Some(weight)) = (
+                    self.accumulated_samples.pop_front(),
+                    self.accumulated_weights.pop_front(),
+                ) {
+                    block.push(if weight > 0.0 { sample / weight } else { 0.0 });
+                    self.output_index += 1;
+                    continue;
+                }
+            }
+
+            self.add_frame()?;
+        }
+
+        Ok(block)
+    }
+
+    fn add_frame(&mut self) -> Result<(), TError> {
+        let window_size = self.interpolator.window_size();
+        let frame_start = self.next_frame_index - (window_size / 2) as isize;
+
+        let (frame, window_frame) = self.interpolator.reconstruct_frame(
+            self.channel_id,
+            self.next_frame_index,
+            self.fract,
+        )?;
+
+        let frame_end = (frame_start + window_size as isize - self.output_index) as usize;
+        if self.accumulated_samples.len() < frame_end {
+            self.accumulated_samples.resize(frame_end, 0.0);
+            self.accumulated_weights.resize(frame_end, 0.0);
+        }
+
+        for (n, sample) in frame.iter().enumerate() {
+            // Samples before output_index are only in the first windows, which start early
+            let accumulated_index = frame_start + n as isize - self.output_index;
+            if accumulated_index < 0 {
+                continue;
+            }
+
+            // The window function that the samples were read with is undone by dividing by it, (see
+            // Interpolator::reconstruct_sample,) so it's part of the weight
+            let weight = match &window_frame {
+                Some(window_frame) => self.synthesis_window[n] * window_frame[n],
+                None => self.synthesis_window[n],
+            };
+
+            self.accumulated_samples[accumulated_index as usize] +=
+                sample * self.synthesis_window[n];
+            self.accumulated_weights[accumulated_index as usize] += weight;
+        }
+
+        self.next_frame_index += self.hop as isize;
+        Ok(())
+    }
+}
+
+// Whether the window, repeated every hop samples, adds up to the same weight at every sample
+fn adds_to_constant(window: &[f32], hop: usize) -> bool {
+    let sums: Vec<f32> = (0..hop)
+        .map(|offset| window.iter().skip(offset).step_by(hop).sum())
+        .collect();
+    let expected_sum = sums[0];
+    expected_sum > 0.0
+        && sums
+            .iter()
+            .all(|sum| (sum - expected_sum).abs() <= expected_sum * 0.0001)
+}
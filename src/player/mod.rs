@@ -0,0 +1,124 @@
+use crate::interpolator::{Interpolator, SampleProvider};
+
+/// Plays channels at a fixed relative_speed into buffers of whatever size an audio callback asks for. The
+/// cursor is an f64 that carries over from one block to the next, so there are no seams between blocks, and
+/// each block is written into the caller's buffer. Once the end of the signal is reached, the rest of each
+/// block is silence. This is the streaming counterpart to Interpolator::resample_all
+///
+/// fill doesn't allocate once the transforms are planned and the interpolator's caches and buffers are filled,
+/// so it can run in the audio callback: Call prepare before starting the callback, and again after a
+/// set_relative_speed that changes how many points the anti-aliasing filter averages
+pub struct Player<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+    channel_ids: Vec<TChannelId>,
+    cursor: f64,
+    relative_speed: f32,
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> Player<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// Plays channel_ids, interleaved in the same order, starting at start. channel_ids is a channel map, as
+    /// in Interpolator::fill_interleaved, so a channel may be repeated. relative_speed is how many input
+    /// samples each frame advances, and the anti-aliasing filter is used when it's greater than 1.0
+    pub fn new(
+        interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+        channel_ids: &[TChannelId],
+        start: f64,
+        relative_speed: f32,
+    ) -> Player<'a, TSampleProvider, TChannelId, TError> {
+        assert!(!channel_ids.is_empty(), "channel_ids must not be empty");
+        assert!(
+            relative_speed > 0.0,
+            "relative_speed must be greater than 0.0"
+        );
+
+        Player {
+            interpolator,
+            channel_ids: channel_ids.to_vec(),
+            cursor: start,
+            relative_speed,
+        }
+    }
+
+    pub fn channel_ids(&self) -> &[TChannelId] {
+        &self.channel_ids
+    }
+
+    /// The index of the next frame
+    pub fn cursor(&self) -> f64 {
+        self.cursor
+    }
+
+    /// Moves the cursor, for seeking
+    pub fn set_cursor(&mut self, cursor: f64) {
+        self.cursor = cursor;
+    }
+
+    pub fn relative_speed(&self) -> f32 {
+        self.relative_speed
+    }
+
+    /// Changes the speed, starting with the next block
+    pub fn set_relative_speed(&mut self, relative_speed: f32) {
+        assert!(
+            relative_speed > 0.0,
+            "relative_speed must be greater than 0.0"
+        );
+        self.relative_speed = relative_speed;
+    }
+
+    /// Whether the cursor is at or after the end of every channel
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.num_samples() as f64
+    }
+
+    /// Reads a block of num_frames frames at the cursor, without advancing it, so that the transforms are
+    /// planned, and the interpolator's caches and buffers are filled, before fill is called. (See Player)
+    pub fn prepare(&self, num_frames: usize) -> Result<(), TError> {
+        let mut block = vec![0.0; num_frames * self.channel_ids.len()];
+        self.read_block(&mut block)
+    }
+
+    /// Fills out with interleaved frames, and advances the cursor past them. out's length must be a multiple
+    /// of the number of channels. Returns the number of frames before the end of the signal: When it's less
+    /// than the number of frames in out, the end was reached within this block, and the rest of out is 0.0
+    pub fn fill(&mut self, out: &mut [f32]) -> Result<usize, TError> {
+        self.read_block(out)?;
+
+        let num_frames = out.len() / self.channel_ids.len();
+        let remaining = ((self.num_samples() as f64 - self.cursor) / self.relative_speed as f64)
+            .ceil()
+            .max(0.0);
+        let frames_before_end = (remaining as usize).min(num_frames);
+
+        self.cursor += num_frames as f64 * self.relative_speed as f64;
+        Ok(frames_before_end)
+    }
+
+    // Reads the frames starting at the cursor into out
+    fn read_block(&self, out: &mut [f32]) -> Result<(), TError> {
+        self.interpolator.fill_interleaved_f64(
+            &self.channel_ids,
+            out,
+            self.cursor,
+            self.relative_speed as f64,
+            self.relative_speed,
+        )
+    }
+
+    // The length of the longest channel, which is where playback ends
+    fn num_samples(&self) -> usize {
+        self.channel_ids
+            .iter()
+            .map(|channel_id| self.interpolator.channel_num_samples(*channel_id))
+            .max()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::interpolator::{Interpolator, SampleProvider};
+
+/// Reads a channel at a fixed step, keeping track of its own read position, until the cursor passes the end of
+/// the channel. The cursor is an f64, even though samples are f32, so that adding the step millions of times
+/// doesn't drift the way an f32 accumulator would over a long file
+pub struct Resampler<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+    channel_id: TChannelId,
+    cursor: f64,
+    step: f64,
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> Resampler<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// Starts reading channel_id at cursor, advancing by step input samples for each output sample. A step
+    /// greater than 1.0 downsamples, and uses the anti-aliasing filter
+    pub fn new(
+        interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+        channel_id: TChannelId,
+        cursor: f64,
+        step: f64,
+    ) -> Resampler<'a, TSampleProvider, TChannelId, TError> {
+        assert!(step > 0.0, "step must be greater than 0.0");
+
+        Resampler {
+            interpolator,
+            channel_id,
+            cursor,
+            step,
+        }
+    }
+
+    pub fn channel_id(&self) -> TChannelId {
+        self.channel_id
+    }
+
+    /// The index of the next sample
+    pub fn cursor(&self) -> f64 {
+        self.cursor
+    }
+
+    /// Moves the cursor, for seeking
+    pub fn set_cursor(&mut self, cursor: f64) {
+        self.cursor = cursor;
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Changes the step, starting with the next sample
+    pub fn set_step(&mut self, step: f64) {
+        assert!(step > 0.0, "step must be greater than 0.0");
+        self.step = step;
+    }
+
+    /// Gets the sample at the cursor, and then advances the cursor by the step. Returns None once the cursor
+    /// is past the end of the channel
+    pub fn next_sample(&mut self) -> Option<Result<f32, TError>> {
+        if self.cursor >= self.interpolator.channel_num_samples(self.channel_id) as f64 {
+            return None;
+        }
+
+        let sample = self.interpolator.get_interpolated_sample(
+            self.channel_id,
+            self.cursor as f32,
+            self.step as f32,
+        );
+        self.cursor += self.step;
+        Some(sample)
+    }
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> Iterator
+    for Resampler<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    type Item = Result<f32, TError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample()
+    }
+}
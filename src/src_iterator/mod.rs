@@ -0,0 +1,70 @@
+use crate::interpolator::{Interpolator, SampleProvider};
+
+/// Converts a signal from one integer sample rate to another, yielding each output sample in order until the
+/// input is exhausted. When downsampling, (in_rate > out_rate,) the anti-aliasing filter is used.
+pub struct SrcIterator<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+    channel_id: TChannelId,
+    in_rate: u32,
+    out_rate: u32,
+    relative_speed: f32,
+    output_index: usize,
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> SrcIterator<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    pub fn new(
+        interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+        channel_id: TChannelId,
+        in_rate: u32,
+        out_rate: u32,
+    ) -> SrcIterator<'a, TSampleProvider, TChannelId, TError> {
+        assert!(in_rate > 0, "in_rate must be greater than 0");
+        assert!(out_rate > 0, "out_rate must be greater than 0");
+
+        SrcIterator {
+            interpolator,
+            channel_id,
+            in_rate,
+            out_rate,
+            relative_speed: in_rate as f32 / out_rate as f32,
+            output_index: 0,
+        }
+    }
+
+    // The cursor is calculated from the output index with an exact ratio, instead of accumulating
+    // in_rate / out_rate, so that rounding errors don't drift over long signals
+    fn input_index(&self) -> f64 {
+        (self.output_index as f64) * (self.in_rate as f64) / (self.out_rate as f64)
+    }
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> Iterator
+    for SrcIterator<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    type Item = Result<f32, TError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input_index = self.input_index();
+        if input_index >= self.interpolator.num_samples() as f64 {
+            return None;
+        }
+
+        self.output_index += 1;
+        Some(self.interpolator.get_interpolated_sample(
+            self.channel_id,
+            input_index as f32,
+            self.relative_speed,
+        ))
+    }
+}
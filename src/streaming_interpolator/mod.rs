@@ -0,0 +1,191 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    convert::Infallible,
+};
+
+use crate::interpolator::{Interpolator, SampleProvider};
+
+// How far, in samples, the oldest kept sample may get ahead of the buffer's origin before the origin is moved up
+// to it. The interpolator's indexes are relative to the origin, so they stay small enough for an f32 to hold
+// their fraction no matter how long the stream runs
+const REBASE_INTERVAL: usize = 1 << 10;
+
+// The pushed samples that a pull may still read. Samples are only discarded once the read position has moved
+// past every window that can read them, so the buffer holds the window, the anti-aliasing filter's span, and
+// any samples that were pushed but not yet pulled
+struct StreamBuffer {
+    samples: RefCell<VecDeque<f32>>,
+    num_pushed: Cell<usize>,
+    // The index in the stream of the interpolator's index 0
+    origin: Cell<usize>,
+}
+
+impl StreamBuffer {
+    fn push(&self, sample: f32) {
+        self.samples.borrow_mut().push_back(sample);
+        self.num_pushed.set(self.num_pushed.get() + 1);
+    }
+
+    fn oldest_index(&self) -> usize {
+        self.num_pushed.get() - self.samples.borrow().len()
+    }
+
+    // Discards the samples before index
+    fn discard_before(&self, index: usize) {
+        let num_discarded = index.saturating_sub(self.oldest_index());
+        let mut samples = self.samples.borrow_mut();
+        let num_discarded = num_discarded.min(samples.len());
+        samples.drain(..num_discarded);
+    }
+
+    // Moves the origin up to the oldest kept sample, once it's REBASE_INTERVAL samples behind it. The
+    // interpolator never reads before the oldest kept sample, so its indexes are never negative. Returns
+    // whether the origin moved
+    fn rebase(&self) -> bool {
+        let oldest_index = self.oldest_index();
+        if oldest_index - self.origin.get() < REBASE_INTERVAL {
+            return false;
+        }
+
+        self.origin.set(oldest_index);
+        true
+    }
+}
+
+// Samples before the start of the stream are padding, and are never read from the buffer. index is relative
+// to the origin
+impl SampleProvider<(), Infallible> for StreamBuffer {
+    fn get_sample(&self, _channel_id: (), index: usize) -> Result<f32, Infallible> {
+        let buffer_index = (self.origin.get() + index)
+            .checked_sub(self.oldest_index())
+            .expect("StreamingInterpolator read a sample that it already discarded");
+
+        Ok(self
+            .samples
+            .borrow()
+            .get(buffer_index)
+            .copied()
+            .unwrap_or(0.0))
+    }
+}
+
+/// Interpolates a signal that arrives a sample at a time, such as live input, instead of reading from a
+/// SampleProvider. Samples are pushed in, and interpolated samples are pulled out once enough samples have been
+/// pushed to fill the window around the read position.
+///
+/// Pushed samples are kept until the read position has passed every window that can read them, so the
+/// buffer grows while samples are pushed faster than they're pulled, and holds about window_size +
+/// max_relative_speed samples when they're pulled as they arrive
+pub struct StreamingInterpolator {
+    interpolator: Interpolator<StreamBuffer, (), Infallible>,
+    index: f64,
+    max_relative_speed: f32,
+}
+
+impl StreamingInterpolator {
+    /// A StreamingInterpolator whose pulls have a relative_speed of at most 1.0, so they never use the
+    /// anti-aliasing filter. See with_max_relative_speed to downsample
+    pub fn new(window_size: usize) -> StreamingInterpolator {
+        StreamingInterpolator::with_max_relative_speed(window_size, 1.0)
+    }
+
+    /// A StreamingInterpolator whose pulls have a relative_speed of at most max_relative_speed. The
+    /// anti-aliasing filter reads relative_speed / 2 samples before the read position, so the samples that
+    /// a pull at max_relative_speed reads are kept in the buffer
+    pub fn with_max_relative_speed(
+        window_size: usize,
+        max_relative_speed: f32,
+    ) -> StreamingInterpolator {
+        assert!(
+            max_relative_speed.is_finite() && max_relative_speed > 0.0,
+            "max_relative_speed must be finite and greater than 0"
+        );
+
+        let stream_buffer = StreamBuffer {
+            samples: RefCell::new(VecDeque::with_capacity(
+                window_size + max_relative_speed.ceil() as usize,
+            )),
+            num_pushed: Cell::new(0),
+            origin: Cell::new(0),
+        };
+
+        StreamingInterpolator {
+            // The stream has no known end
+            interpolator: Interpolator::new(window_size, isize::MAX as usize, stream_buffer),
+            index: 0.0,
+            max_relative_speed,
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.interpolator.sample_provider().push(sample);
+    }
+
+    /// Reads the sample at the current read position and then advances it by relative_speed. Returns None,
+    /// without advancing, when not enough samples have been pushed to fill the window around the read position.
+    /// relative_speed can't be more than max_relative_speed
+    pub fn pull(&mut self, relative_speed: f32) -> Option<f32> {
+        assert!(
+            relative_speed <= self.max_relative_speed,
+            "relative_speed can't be more than max_relative_speed"
+        );
+
+        // The anti-aliasing filter reads windows up to half of relative_speed past the read position
+        let window_size = self.interpolator.window_size();
+        let last_index = (self.index + filter_span(relative_speed)).floor() as usize
+            + (window_size - window_size / 2 - self.interpolator.window_offset());
+        if last_index > self.interpolator.sample_provider().num_pushed.get() {
+            return None;
+        }
+
+        // Only the offset from the origin is rounded to f32, so the fraction isn't lost late in the stream
+        let stream_buffer = self.interpolator.sample_provider();
+        let relative_index = (self.index - stream_buffer.origin.get() as f64) as f32;
+        let sample = self
+            .interpolator
+            .get_interpolated_sample((), relative_index, relative_speed)
+            .unwrap_or_else(|never| match never {});
+
+        self.index += relative_speed as f64;
+
+        // Later pulls read from later positions, so the samples before the first window that a pull at
+        // max_relative_speed from here reads are no longer needed. One more sample is kept, because the
+        // read position is rounded to f32 when it's interpolated
+        let first_index = (self.index - filter_span(self.max_relative_speed)).floor() as isize
+            - (window_size / 2 + self.interpolator.window_offset()) as isize
+            - 1;
+        let stream_buffer = self.interpolator.sample_provider();
+        stream_buffer.discard_before(first_index.max(0) as usize);
+
+        // The cached transform is at the old origin's indexes
+        if stream_buffer.rebase() {
+            self.interpolator.invalidate_channel(());
+        }
+
+        Some(sample)
+    }
+
+    pub fn max_relative_speed(&self) -> f32 {
+        self.max_relative_speed
+    }
+
+    /// The number of pushed samples that are kept for later pulls
+    pub fn buffered_len(&self) -> usize {
+        self.interpolator.sample_provider().samples.borrow().len()
+    }
+
+    /// The position, in pushed samples, of the next pull
+    pub fn index(&self) -> f64 {
+        self.index
+    }
+}
+
+// How far the anti-aliasing filter reads on either side of the read position at relative_speed
+fn filter_span(relative_speed: f32) -> f64 {
+    if relative_speed > 1.0 {
+        relative_speed as f64 / 2.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,91 @@
+use std::marker::PhantomData;
+
+use crate::interpolator::SampleProvider;
+
+/// Mixes several SampleProviders into one: Each sample is the sum of the sources' samples at the same index,
+/// each multiplied by its source's gain. Interpolating the mix is the same as mixing the interpolated sources,
+/// but transforms each window once instead of once per source. Sources of different types can be mixed as
+/// Box<dyn SampleProvider>
+pub struct SumSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    sources: Vec<(TSampleProvider, f32)>,
+
+    _phantom_data: PhantomData<(TChannelId, TError)>,
+}
+
+impl<TSampleProvider, TChannelId, TError> SumSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    /// Sums sources at unity gain
+    pub fn new(
+        sources: Vec<TSampleProvider>,
+    ) -> SumSampleProvider<TSampleProvider, TChannelId, TError> {
+        SumSampleProvider::with_gains(sources.into_iter().map(|source| (source, 1.0)).collect())
+    }
+
+    /// Sums each source multiplied by its gain
+    pub fn with_gains(
+        sources: Vec<(TSampleProvider, f32)>,
+    ) -> SumSampleProvider<TSampleProvider, TChannelId, TError> {
+        SumSampleProvider {
+            sources,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    pub fn sources(&self) -> &[(TSampleProvider, f32)] {
+        &self.sources
+    }
+}
+
+impl<TSampleProvider, TChannelId, TError> SampleProvider<TChannelId, TError>
+    for SumSampleProvider<TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy,
+{
+    fn get_sample(&self, channel_id: TChannelId, index: usize) -> Result<f32, TError> {
+        let mut sum = 0.0;
+        for (source, gain) in &self.sources {
+            sum += source.get_sample(channel_id, index)? * gain;
+        }
+
+        Ok(sum)
+    }
+
+    // Sources that have ended are silent, and the mix ends when every source has ended
+    fn get_sample_if_available(
+        &self,
+        channel_id: TChannelId,
+        index: usize,
+    ) -> Result<Option<f32>, TError> {
+        let mut sum = None;
+        for (source, gain) in &self.sources {
+            if let Some(sample) = source.get_sample_if_available(channel_id, index)? {
+                sum = Some(sum.unwrap_or(0.0) + sample * gain);
+            }
+        }
+
+        Ok(sum)
+    }
+
+    // Changes whenever any source's generation changes, as long as generations only increase
+    fn generation(&self) -> u64 {
+        self.sources.iter().fold(0, |generation, (source, _)| {
+            generation.wrapping_add(source.generation())
+        })
+    }
+
+    fn prefetch(&self, channel_id: TChannelId, start: usize, len: usize) -> Result<(), TError> {
+        for (source, _) in &self.sources {
+            source.prefetch(channel_id, start, len)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::interpolator::{Interpolator, SampleProvider};
+
+/// Plays a signal with a sustain loop, as in a sampler instrument: Playback runs from the start of the signal
+/// to loop_end, and then repeats loop_start through loop_end for as long as it continues. To hide the seam,
+/// the last crossfade_length samples before loop_end are crossfaded, with equal power, into the samples just
+/// before loop_start, so that playback arrives at loop_end sounding like the signal at loop_start
+pub struct SustainLoop<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+    channel_id: TChannelId,
+    loop_start: f64,
+    loop_end: f64,
+    crossfade_length: f64,
+}
+
+impl<'a, TSampleProvider, TChannelId, TError> SustainLoop<'a, TSampleProvider, TChannelId, TError>
+where
+    TSampleProvider: SampleProvider<TChannelId, TError>,
+    TChannelId: Copy + std::cmp::Eq + std::hash::Hash,
+{
+    /// loop_start must be before loop_end. crossfade_length may be 0.0, for no crossfade, and can't be longer
+    /// than the loop, or than the signal before loop_start
+    pub fn new(
+        interpolator: &'a Interpolator<TSampleProvider, TChannelId, TError>,
+        channel_id: TChannelId,
+        loop_start: f64,
+        loop_end: f64,
+        crossfade_length: f64,
+    ) -> SustainLoop<'a, TSampleProvider, TChannelId, TError> {
+        assert!(
+            loop_start >= 0.0 && loop_start < loop_end,
+            "loop_start must be at least 0.0 and less than loop_end"
+        );
+        assert!(
+            crossfade_length >= 0.0
+                && crossfade_length <= loop_end - loop_start
+                && crossfade_length <= loop_start,
+            "crossfade_length must be at least 0.0, and no longer than the loop or than loop_start"
+        );
+
+        SustainLoop {
+            interpolator,
+            channel_id,
+            loop_start,
+            loop_end,
+            crossfade_length,
+        }
+    }
+
+    pub fn loop_start(&self) -> f64 {
+        self.loop_start
+    }
+
+    pub fn loop_end(&self) -> f64 {
+        self.loop_end
+    }
+
+    pub fn crossfade_length(&self) -> f64 {
+        self.crossfade_length
+    }
+
+    /// The index in the signal that playback_index plays, before crossfading
+    pub fn loop_index(&self, playback_index: f64) -> f64 {
+        if playback_index < self.loop_end {
+            playback_index
+        } else {
+            self.loop_start + (playback_index - self.loop_start) % (self.loop_end - self.loop_start)
+        }
+    }
+
+    /// Gets the sample at playback_index, where playback_index counts samples since the start of playback.
+    /// (See Interpolator::get_interpolated_sample for relative_speed.) playback_index is an f64 so that long
+    /// sustains don't lose precision
+    pub fn get_sample(&self, playback_index: f64, relative_speed: f32) -> Result<f32, TError> {
+        let index = self.loop_index(playback_index);
+        let sample = self.interpolator.get_interpolated_sample(
+            self.channel_id,
+            index as f32,
+            relative_speed,
+        )?;
+
+        let crossfade_start = self.loop_end - self.crossfade_length;
+        if index < crossfade_start {
+            return Ok(sample);
+        }
+
+        let crossfade_position = ((index - crossfade_start) / self.crossfade_length) as f32;
+        let loop_start_sample = self.interpolator.get_interpolated_sample(
+            self.channel_id,
+            (index - (self.loop_end - self.loop_start)) as f32,
+            relative_speed,
+        )?;
+
+        Ok(sample * (crossfade_position * FRAC_PI_2).cos()
+            + loop_start_sample * (crossfade_position * FRAC_PI_2).sin())
+    }
+
+    /// Reads count samples starting at start_playback_index, advancing by relative_speed for each sample
+    pub fn read(
+        &self,
+        start_playback_index: f64,
+        count: usize,
+        relative_speed: f32,
+    ) -> Result<Vec<f32>, TError> {
+        (0..count)
+            .map(|output_index| {
+                let playback_index =
+                    start_playback_index + (output_index as f64) * (relative_speed as f64);
+                self.get_sample(playback_index, relative_speed)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    cell::RefCell,
+    io::{self, ErrorKind},
+};
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{Decoder, DecoderOptions},
+    errors::{Error, SeekErrorKind},
+    formats::{FormatReader, SeekMode, SeekTo},
+};
+
+use crate::interpolator::SampleProvider;
+
+// When a requested sample is no more than this many frames after the decoded packet, decoding forward is
+// cheaper than seeking
+const DECODE_FORWARD_FRAMES: usize = 1 << 16;
+
+// A decoded packet, with the samples for each frame interleaved
+struct DecodedPacket {
+    start: usize,
+    num_channels: usize,
+    samples: Vec<f32>,
+}
+
+impl DecodedPacket {
+    fn end(&self) -> usize {
+        self.start + self.samples.len() / self.num_channels
+    }
+
+    // None if index isn't in the packet
+    fn get_sample(&self, channel: usize, index: usize) -> Result<Option<f32>, Error> {
+        if channel >= self.num_channels {
+            return Err(Error::IoError(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "channel {} is out of range, there are {} channels",
+                    channel, self.num_channels
+                ),
+            )));
+        }
+
+        if index < self.start || index >= self.end() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.samples[(index - self.start) * self.num_channels + channel],
+        ))
+    }
+}
+
+struct DecoderState {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    packet: Option<DecodedPacket>,
+    sample_buffer: Option<SampleBuffer<f32>>,
+}
+
+impl DecoderState {
+    fn seek(&mut self, index: usize) -> Result<(), Error> {
+        self.packet = None;
+        self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::TimeStamp {
+                ts: index as u64,
+                track_id: self.track_id,
+            },
+        )?;
+        self.decoder.reset();
+
+        Ok(())
+    }
+
+    // Decodes packets until one contains index. Reading past the end of the stream returns the format
+    // reader's end of stream error
+    fn decode_until(&mut self, channel: usize, index: usize) -> Result<f32, Error> {
+        loop {
+            let packet = self.format.next_packet()?;
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = self.decoder.decode(&packet)?;
+            let spec = *decoded.spec();
+            let capacity = decoded.capacity() as u64;
+            let sample_buffer = match &mut self.sample_buffer {
+                Some(sample_buffer) if sample_buffer.capacity() >= decoded.capacity() => {
+                    sample_buffer
+                }
+                sample_buffer => sample_buffer.insert(SampleBuffer::new(capacity, spec)),
+            };
+            sample_buffer.copy_interleaved_ref(decoded);
+
+            let decoded_packet = DecodedPacket {
+                start: packet.ts() as usize,
+                num_channels: spec.channels.count(),
+                samples: sample_buffer.samples().to_vec(),
+            };
+
+            // Seeking always lands before index, so a packet that starts after it means that the stream
+            // skipped it
+            if decoded_packet.start > index {
+                return Err(Error::DecodeError("the requested sample was not decoded"));
+            }
+
+            let sample = decoded_packet.get_sample(channel, index)?;
+            self.packet = Some(decoded_packet);
+            if let Some(sample) = sample {
+                return Ok(sample);
+            }
+        }
+    }
+}
+
+/// Reads samples from audio that symphonia decodes, so that compressed audio can be interpolated without
+/// decoding all of it first. Only the most recently decoded packet is kept: Reading a sample within it is a
+/// lookup, reading a sample shortly after it decodes forward, and reading any other sample seeks. Wrap this
+/// in a CachingSampleProvider if reads jump back and forth.
+///
+/// The channel id is the index of the channel within each frame. Samples are indexed by frame, which assumes
+/// that the track's timestamps count frames, as they do for PCM, FLAC, and Vorbis
+pub struct SymphoniaSampleProvider {
+    state: RefCell<DecoderState>,
+    num_frames: Option<usize>,
+    num_channels: Option<usize>,
+}
+
+impl SymphoniaSampleProvider {
+    /// Decodes the format reader's default track, with a decoder from symphonia's default codec registry
+    pub fn new(format: Box<dyn FormatReader>) -> Result<SymphoniaSampleProvider, Error> {
+        let track = format
+            .default_track()
+            .ok_or(Error::Unsupported("there are no tracks"))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        let track_id = track.id;
+        let num_frames = track
+            .codec_params
+            .n_frames
+            .map(|n_frames| n_frames as usize);
+        let num_channels = track.codec_params.channels.map(|channels| channels.count());
+
+        Ok(SymphoniaSampleProvider {
+            state: RefCell::new(DecoderState {
+                format,
+                decoder,
+                track_id,
+                packet: None,
+                sample_buffer: None,
+            }),
+            num_frames,
+            num_channels,
+        })
+    }
+
+    /// The number of frames in the track, if the container specifies it. This is the num_samples for an
+    /// Interpolator
+    pub fn num_frames(&self) -> Option<usize> {
+        self.num_frames
+    }
+
+    pub fn num_channels(&self) -> Option<usize> {
+        self.num_channels
+    }
+}
+
+impl SampleProvider<usize, Error> for SymphoniaSampleProvider {
+    fn get_sample(&self, channel_id: usize, index: usize) -> Result<f32, Error> {
+        let mut state = self.state.borrow_mut();
+
+        let decode_forward = match &state.packet {
+            Some(packet) => {
+                if let Some(sample) = packet.get_sample(channel_id, index)? {
+                    return Ok(sample);
+                }
+
+                index >= packet.end() && index - packet.end() < DECODE_FORWARD_FRAMES
+            }
+            None => false,
+        };
+
+        if !decode_forward {
+            state.seek(index)?;
+        }
+
+        state.decode_until(channel_id, index)
+    }
+
+    // Reading or seeking past the end of the stream is the end of the signal, instead of an error
+    fn get_sample_if_available(
+        &self,
+        channel_id: usize,
+        index: usize,
+    ) -> Result<Option<f32>, Error> {
+        match self.get_sample(channel_id, index) {
+            Ok(sample) => Ok(Some(sample)),
+            Err(Error::IoError(error)) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(Error::SeekError(SeekErrorKind::OutOfRange)) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}